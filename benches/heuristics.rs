@@ -0,0 +1,96 @@
+// compares walking distance against manhattan distance by how long A* takes with each
+//
+//     cargo bench --bench heuristics
+//
+// the walking distance tables are built before timing starts, so only the searches are measured.
+// `ai_homework analyze-heuristic --heuristic <name>` reports the nodes expanded for the same
+// heuristics if those are more interesting than the times.
+
+extern crate ai_homework;
+extern crate criterion;
+
+use std::sync::atomic::Ordering;
+
+use ai_homework::walking_distance::walking_distance_heuristic;
+use ai_homework::{
+    linear_conflict_heuristic, manhattan_distance_heuristic, search_with_options,
+    walking_linear_conflict_heuristic, Algorithm, EightPuzzle, Problem, SearchLimits,
+    PRINT_EXPANDING_NODES_TOGGLE,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+type Heuristic = fn(&EightPuzzle, &EightPuzzle) -> u64;
+
+const HEURISTICS: [(&str, Heuristic); 4] = [
+    ("manhattan", manhattan_distance_heuristic),
+    ("linear-conflict", linear_conflict_heuristic),
+    ("walking", walking_distance_heuristic),
+    ("walking-linear-conflict", walking_linear_conflict_heuristic),
+];
+
+// the solvable presets from the interactive program
+const PRESETS: [(&str, &str); 5] = [
+    ("trivial", "123456780"),
+    ("very easy", "123456708"),
+    ("easy", "120453786"),
+    ("doable", "012453786"),
+    ("oh boy", "871602543"),
+];
+
+// random walks from the goal, with their optimal lengths, picked so manhattan distance still
+// solves each of them in well under a second
+const FIFTEEN_PUZZLES: [(&str, &str); 5] = [
+    ("20 moves", "2 4 7 8/5 1 10 3/0 6 9 12/13 14 11 15"),
+    ("26 moves", "5 2 4 8/9 1 3 12/14 6 0 15/10 11 13 7"),
+    ("28 moves", "0 3 4 8/2 1 6 12/10 11 7 9/13 5 14 15"),
+    ("28 moves, harder", "1 2 8 3/9 10 5 4/0 14 7 12/13 15 11 6"),
+    ("34 moves", "1 3 9 8/7 4 2 0/14 6 15 11/5 13 12 10"),
+];
+
+fn solve(initial: &EightPuzzle, heuristic: Heuristic) {
+    let problem = Problem::new(
+        initial.clone(),
+        EightPuzzle::solved(initial.rows, initial.cols),
+    );
+    let (outcome, _) = search_with_options(
+        problem,
+        heuristic,
+        Algorithm::AStar,
+        SearchLimits::default(),
+    );
+    assert!(outcome.solution().is_some());
+}
+
+fn bench_puzzles(criterion: &mut Criterion, group_name: &str, puzzles: &[(&str, &str)]) {
+    PRINT_EXPANDING_NODES_TOGGLE.store(false, Ordering::Relaxed);
+
+    let mut group = criterion.benchmark_group(group_name);
+    group.sample_size(10);
+
+    for (puzzle_name, puzzle) in puzzles.iter() {
+        let puzzle: EightPuzzle = puzzle.parse().unwrap();
+        // builds the tables for this size
+        walking_distance_heuristic(&puzzle, &EightPuzzle::solved(puzzle.rows, puzzle.cols));
+
+        for (heuristic_name, heuristic) in HEURISTICS.iter() {
+            group.bench_with_input(
+                BenchmarkId::new(*heuristic_name, puzzle_name),
+                &puzzle,
+                |bencher, puzzle| bencher.iter(|| solve(puzzle, *heuristic)),
+            );
+        }
+    }
+
+    group.finish();
+}
+
+fn presets(criterion: &mut Criterion) {
+    bench_puzzles(criterion, "presets", &PRESETS);
+}
+
+fn fifteen_puzzles(criterion: &mut Criterion) {
+    bench_puzzles(criterion, "15-puzzle", &FIFTEEN_PUZZLES);
+}
+
+criterion_group!(benches, presets, fifteen_puzzles);
+criterion_main!(benches);
@@ -0,0 +1,93 @@
+/* generated by `ai_homework ffi-header` from src/ffi.rs, don't edit by hand */
+
+#ifndef AI_HOMEWORK_H
+#define AI_HOMEWORK_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* bumped whenever a function or struct in this file changes in a way old callers would notice */
+#define PUZZLE_ABI_VERSION 1
+
+#define PUZZLE_ALGORITHM_ASTAR 0
+#define PUZZLE_ALGORITHM_UCS 1
+#define PUZZLE_ALGORITHM_GREEDY 2
+
+#define PUZZLE_HEURISTIC_UCS 0
+#define PUZZLE_HEURISTIC_MISPLACED 1
+#define PUZZLE_HEURISTIC_MANHATTAN 2
+#define PUZZLE_HEURISTIC_LINEAR_CONFLICT 3
+#define PUZZLE_HEURISTIC_WALKING 4
+#define PUZZLE_HEURISTIC_WALKING_LINEAR_CONFLICT 5
+#define PUZZLE_HEURISTIC_GASCHNIG 6
+#define PUZZLE_HEURISTIC_INVERSION_DISTANCE 7
+
+#define PUZZLE_STATUS_SOLVED 0
+#define PUZZLE_STATUS_UNSOLVABLE 1
+#define PUZZLE_STATUS_EXPANSION_LIMIT 2
+#define PUZZLE_STATUS_TIME_LIMIT 3
+
+/* moves of the space, as written by puzzle_result_moves */
+#define PUZZLE_MOVE_UP 0
+#define PUZZLE_MOVE_DOWN 1
+#define PUZZLE_MOVE_LEFT 2
+#define PUZZLE_MOVE_RIGHT 3
+
+/* a board, made by puzzle_board_new */
+typedef struct PuzzleBoard PuzzleBoard;
+
+/* what a search found, made by puzzle_solve */
+typedef struct PuzzleResult PuzzleResult;
+
+typedef struct PuzzleStats {
+    uint64_t expanded;
+    uint64_t max_queue;
+} PuzzleStats;
+
+/* the PUZZLE_ABI_VERSION the library was built with, to check against the header's */
+uint32_t puzzle_abi_version(void);
+
+/* why the last call on this thread failed, empty if it didn't */
+/* the text belongs to the library and is only good until the next call */
+const char *puzzle_last_error(void);
+
+/* makes a board from rows * cols tiles in row order, 0 is the space */
+/* returns null if the tiles don't make a valid board */
+PuzzleBoard *puzzle_board_new(const uint16_t *tiles, size_t rows, size_t cols);
+
+void puzzle_board_free(PuzzleBoard *board);
+
+/* 1 if the goal can be reached from the initial board, 0 if not, -1 on bad arguments */
+/* a null goal means the usual one, tiles counting up with the space in the bottom right corner */
+int32_t puzzle_is_solvable(const PuzzleBoard *initial, const PuzzleBoard *goal);
+
+/* searches from the initial board to the goal, a null goal means the usual one */
+/* algorithm and heuristic are PUZZLE_ALGORITHM_* and PUZZLE_HEURISTIC_* values, a limit of 0 means */
+/* no limit. returns null on bad arguments, otherwise a result to read and then free */
+PuzzleResult *puzzle_solve(const PuzzleBoard *initial, const PuzzleBoard *goal, uint32_t algorithm, uint32_t heuristic, uint64_t max_expansions, uint64_t timeout_ms);
+
+/* one of the PUZZLE_STATUS_* values */
+uint32_t puzzle_result_status(const PuzzleResult *result);
+
+/* how many moves the solution takes, 0 unless the status is PUZZLE_STATUS_SOLVED */
+size_t puzzle_result_move_count(const PuzzleResult *result);
+
+/* copies up to capacity PUZZLE_MOVE_* values into moves and returns how many were copied */
+size_t puzzle_result_moves(const PuzzleResult *result, uint8_t *moves, size_t capacity);
+
+/* the solution in move notation, like "RRDD", owned by the result */
+const char *puzzle_result_move_string(const PuzzleResult *result);
+
+PuzzleStats puzzle_result_stats(const PuzzleResult *result);
+
+void puzzle_result_free(PuzzleResult *result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* AI_HOMEWORK_H */
@@ -0,0 +1,406 @@
+// checks how good a heuristic is by comparing it against the true number of moves to the goal
+//
+// boards with at most 9 spots are small enough to find the true distance of every board with a
+// breadth first search back from the goal. bigger boards are sampled instead: random walks away
+// from the goal are solved with A* and manhattan distance, and every board along each optimal path
+// gets its true distance from there. that's only right because manhattan distance never
+// overestimates, so don't change the heuristic used for the sampling without checking that first.
+//
+// the report has
+// * admissibility: boards where h(n) is more than the true distance h*(n)
+// * consistency: moves from n to n' where h(n) > 1 + h(n'), checked on every move out of a board
+// * the mean of h(n) / h*(n) over every board except the goal, 1 would be perfect
+// * a histogram of h*(n) - h(n), negative errors are the overestimates
+// * how many nodes A* expands with the heuristic on some of the boards, and whether the paths it
+//   found were still the shortest
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::Ordering;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::heuristic::{ComponentCount, Heuristic};
+use crate::{
+    manhattan_distance_heuristic, search_with_heuristic, search_with_options, Algorithm,
+    EightPuzzle, Moves, Problem, SearchLimits, SearchOutcome, PRINT_EXPANDING_NODES_TOGGLE,
+};
+
+// the most spots a board can have and still be searched exhaustively, 9! / 2 = 181440 boards
+pub const EXHAUSTIVE_SPOTS: usize = 9;
+
+const MOVES: [Moves; 4] = [Moves::Up, Moves::Down, Moves::Left, Moves::Right];
+
+#[derive(Clone, Copy, Debug)]
+pub struct AnalysisOptions {
+    pub rows: usize,
+    pub cols: usize,
+    // random walks solved for the true distances when the board is too big to do them all
+    pub samples: usize,
+    pub walk_length: usize,
+    // boards A* is run on with the heuristic to count the nodes it expands
+    pub searches: usize,
+    // so one slow search can't hold up the whole report
+    pub max_expansions: u64,
+    // how many counterexamples of each kind are kept
+    pub examples: usize,
+    pub seed: u64,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> AnalysisOptions {
+        AnalysisOptions {
+            rows: 3,
+            cols: 3,
+            samples: 20,
+            walk_length: 30,
+            searches: 20,
+            max_expansions: 100_000,
+            examples: 5,
+            seed: 0,
+        }
+    }
+}
+
+// a board where the heuristic guessed more moves than it really takes
+#[derive(Clone, Debug)]
+pub struct AdmissibilityViolation {
+    pub puzzle: EightPuzzle,
+    pub estimate: u64,
+    pub distance: u64,
+}
+
+// a move where the heuristic dropped by more than the move costs
+#[derive(Clone, Debug)]
+pub struct ConsistencyViolation {
+    pub puzzle: EightPuzzle,
+    pub space_move: Moves,
+    pub estimate: u64,
+    pub next_estimate: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HeuristicReport {
+    pub rows: usize,
+    pub cols: usize,
+    // every board was checked, rather than the ones on sampled paths
+    pub exhaustive: bool,
+    pub boards: usize,
+    pub moves_checked: usize,
+    pub admissibility_violations: usize,
+    pub admissibility_examples: Vec<AdmissibilityViolation>,
+    pub consistency_violations: usize,
+    pub consistency_examples: Vec<ConsistencyViolation>,
+    pub mean_ratio: f64,
+    // h*(n) - h(n) and how many boards were off by that much
+    pub error_histogram: BTreeMap<i64, u64>,
+    pub searches: usize,
+    pub total_expanded: u64,
+    pub max_expanded: u64,
+    // searches that found a path longer than the shortest one
+    pub suboptimal_searches: usize,
+    // searches that hit max_expansions before finding the goal
+    pub stopped_searches: usize,
+    // for max() heuristics, how often each part was the largest over all the searches
+    pub max_components: Vec<ComponentCount>,
+}
+
+impl HeuristicReport {
+    pub fn is_admissible(&self) -> bool {
+        self.admissibility_violations == 0
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.consistency_violations == 0
+    }
+
+    pub fn mean_expanded(&self) -> f64 {
+        if self.searches == 0 {
+            0.0
+        } else {
+            self.total_expanded as f64 / self.searches as f64
+        }
+    }
+}
+
+// the boards a move away, paired with the move of the space that gets there
+fn neighbors(puzzle: &EightPuzzle) -> Vec<(Moves, EightPuzzle)> {
+    MOVES
+        .iter()
+        .map(|space_move| (*space_move, puzzle.apply_move(*space_move)))
+        .filter(|(_, next)| next != puzzle)
+        .collect()
+}
+
+// the true distance to the goal of every board that can reach it, in the order they were found
+pub fn exact_distances(goal: &EightPuzzle) -> Vec<(EightPuzzle, u64)> {
+    let mut seen: HashMap<EightPuzzle, u64> = HashMap::new();
+    let mut order = vec![(goal.clone(), 0)];
+    seen.insert(goal.clone(), 0);
+
+    // every move can be undone, so a board's distance back from the goal is its distance to it
+    let mut next = 0;
+    while next < order.len() {
+        let (puzzle, distance) = order[next].clone();
+        next += 1;
+
+        for (_, neighbor) in neighbors(&puzzle) {
+            if !seen.contains_key(&neighbor) {
+                seen.insert(neighbor.clone(), distance + 1);
+                order.push((neighbor, distance + 1));
+            }
+        }
+    }
+
+    order
+}
+
+// a board some moves from the goal, never undoing the move just made
+fn random_walk(goal: &EightPuzzle, length: usize, rng: &mut StdRng) -> EightPuzzle {
+    let mut puzzle = goal.clone();
+    let mut previous: Option<EightPuzzle> = None;
+
+    for _ in 0..length {
+        let choices: Vec<EightPuzzle> = neighbors(&puzzle)
+            .into_iter()
+            .map(|(_, next)| next)
+            .filter(|next| Some(next) != previous.as_ref())
+            .collect();
+        let next = choices[rng.gen_range(0, choices.len())].clone();
+        previous = Some(puzzle);
+        puzzle = next;
+    }
+
+    puzzle
+}
+
+// the boards on the optimal paths from sampled random walks, with their true distances
+// the walks themselves are returned too since they're the natural boards to time searches on
+fn sampled_distances(
+    goal: &EightPuzzle,
+    options: &AnalysisOptions,
+    rng: &mut StdRng,
+) -> (Vec<(EightPuzzle, u64)>, Vec<EightPuzzle>) {
+    let mut seen: HashMap<EightPuzzle, u64> = HashMap::new();
+    let mut order = Vec::new();
+    let mut starts = Vec::new();
+
+    for _ in 0..options.samples {
+        let start = random_walk(goal, options.walk_length, rng);
+        let problem = Problem::new(start.clone(), goal.clone());
+        let (outcome, _) = search_with_options(
+            problem,
+            manhattan_distance_heuristic,
+            Algorithm::AStar,
+            SearchLimits::default(),
+        );
+        let moves = match outcome.into_solution() {
+            Some(solution) => solution.moves,
+            None => continue,
+        };
+
+        let mut puzzle = start.clone();
+        for (index, space_move) in moves.iter().enumerate() {
+            let distance = (moves.len() - index) as u64;
+            if !seen.contains_key(&puzzle) {
+                seen.insert(puzzle.clone(), distance);
+                order.push((puzzle.clone(), distance));
+            }
+            puzzle = puzzle.apply_move(*space_move);
+        }
+        if !seen.contains_key(goal) {
+            seen.insert(goal.clone(), 0);
+            order.push((goal.clone(), 0));
+        }
+
+        starts.push(start);
+    }
+
+    (order, starts)
+}
+
+pub fn analyze(heuristic: &dyn Heuristic, options: &AnalysisOptions) -> HeuristicReport {
+    // thousands of searches would bury the report in expanded boards
+    PRINT_EXPANDING_NODES_TOGGLE.store(false, Ordering::Relaxed);
+
+    let goal = EightPuzzle::solved(options.rows, options.cols);
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let exhaustive = options.rows * options.cols <= EXHAUSTIVE_SPOTS;
+
+    let (distances, starts) = if exhaustive {
+        let distances = exact_distances(&goal);
+        let starts = (0..options.searches)
+            .map(|_| distances[rng.gen_range(0, distances.len())].0.clone())
+            .collect();
+        (distances, starts)
+    } else {
+        sampled_distances(&goal, options, &mut rng)
+    };
+
+    let mut report = HeuristicReport {
+        rows: options.rows,
+        cols: options.cols,
+        exhaustive,
+        boards: distances.len(),
+        ..HeuristicReport::default()
+    };
+
+    let mut ratio_total = 0.0;
+    let mut ratio_count = 0;
+    for (puzzle, distance) in distances.iter() {
+        let estimate = heuristic.estimate(puzzle, &goal);
+
+        if estimate > *distance {
+            report.admissibility_violations += 1;
+            if report.admissibility_examples.len() < options.examples {
+                report.admissibility_examples.push(AdmissibilityViolation {
+                    puzzle: puzzle.clone(),
+                    estimate,
+                    distance: *distance,
+                });
+            }
+        }
+
+        if *distance > 0 {
+            ratio_total += estimate as f64 / *distance as f64;
+            ratio_count += 1;
+        }
+        *report
+            .error_histogram
+            .entry(*distance as i64 - estimate as i64)
+            .or_insert(0) += 1;
+
+        // every move costs 1, so h can drop by at most 1 along it
+        for (space_move, neighbor) in neighbors(puzzle) {
+            let next_estimate = heuristic.estimate(&neighbor, &goal);
+            report.moves_checked += 1;
+
+            if estimate > next_estimate + 1 {
+                report.consistency_violations += 1;
+                if report.consistency_examples.len() < options.examples {
+                    report.consistency_examples.push(ConsistencyViolation {
+                        puzzle: puzzle.clone(),
+                        space_move,
+                        estimate,
+                        next_estimate,
+                    });
+                }
+            }
+        }
+    }
+    if ratio_count > 0 {
+        report.mean_ratio = ratio_total / ratio_count as f64;
+    }
+
+    let true_distance: HashMap<&EightPuzzle, u64> = distances
+        .iter()
+        .map(|(puzzle, distance)| (puzzle, *distance))
+        .collect();
+    for start in starts.iter().take(options.searches) {
+        let problem = Problem::new(start.clone(), goal.clone());
+        let limits = SearchLimits {
+            max_expansions: Some(options.max_expansions),
+            ..SearchLimits::default()
+        };
+        let (outcome, stats) = search_with_heuristic(problem, heuristic, Algorithm::AStar, limits);
+
+        report.searches += 1;
+        report.total_expanded += stats.expanded;
+        report.max_expanded = report.max_expanded.max(stats.expanded);
+        for component in stats.max_components {
+            match report
+                .max_components
+                .iter_mut()
+                .find(|total| total.name == component.name)
+            {
+                Some(total) => total.count += component.count,
+                None => report.max_components.push(component),
+            }
+        }
+
+        match outcome {
+            SearchOutcome::Solved(solution) => {
+                if let Some(distance) = true_distance.get(start) {
+                    if solution.moves.len() as u64 > *distance {
+                        report.suboptimal_searches += 1;
+                    }
+                }
+            }
+            _ => report.stopped_searches += 1,
+        }
+    }
+
+    report
+}
+
+pub fn print_report(name: &str, report: &HeuristicReport) {
+    println!(
+        "Heuristic report for {} on {}x{} boards",
+        name, report.rows, report.cols
+    );
+    if report.exhaustive {
+        println!(
+            "\t boards checked: {} (every solvable board)",
+            report.boards
+        );
+    } else {
+        println!(
+            "\t boards checked: {} (from sampled optimal paths)",
+            report.boards
+        );
+    }
+    println!("\t moves checked: {}", report.moves_checked);
+    println!("\t mean h(n) / h*(n): {:.3}", report.mean_ratio);
+
+    println!(
+        "Admissible: {} ({} boards overestimated)",
+        if report.is_admissible() { "yes" } else { "no" },
+        report.admissibility_violations
+    );
+    for example in report.admissibility_examples.iter() {
+        println!(
+            "\t {} \t h(n) = {} \t h*(n) = {}",
+            example.puzzle, example.estimate, example.distance
+        );
+    }
+
+    println!(
+        "Consistent: {} ({} moves where h(n) dropped by more than 1)",
+        if report.is_consistent() { "yes" } else { "no" },
+        report.consistency_violations
+    );
+    for example in report.consistency_examples.iter() {
+        println!(
+            "\t {} \t moving {} \t h(n) = {} \t h(n') = {}",
+            example.puzzle,
+            example.space_move.name(),
+            example.estimate,
+            example.next_estimate
+        );
+    }
+
+    println!("Error h*(n) - h(n):");
+    for (error, count) in report.error_histogram.iter() {
+        println!("\t {} \t {}", error, count);
+    }
+
+    println!("A* with this heuristic:");
+    println!("\t searches: {}", report.searches);
+    println!("\t mean nodes expanded: {:.1}", report.mean_expanded());
+    println!("\t most nodes expanded: {}", report.max_expanded);
+    println!(
+        "\t paths longer than the shortest: {}",
+        report.suboptimal_searches
+    );
+    println!(
+        "\t stopped at the expansion limit: {}",
+        report.stopped_searches
+    );
+
+    if !report.max_components.is_empty() {
+        println!("Largest part of max() during those searches:");
+        for component in report.max_components.iter() {
+            println!("\t {} \t {}", component.name, component.count);
+        }
+    }
+}
@@ -0,0 +1,292 @@
+// saving a long search every so often, so a crash or a stop doesn't lose the work done
+//
+// a checkpoint is everything the search has made: the nodes in its queue, the boards it closed,
+// the parent and move of every node it expanded (what the path is rebuilt from) and its counters,
+// with the problem, algorithm and heuristic it's for. it's a document like those in schema.rs,
+// JSON or YAML by its extension:
+//     {
+//       "version": 1,
+//       "problem": { ... },
+//       "algorithm": "astar",
+//       "heuristic": "manhattan",
+//       "next_id": 1234,
+//       "stats": { "expanded": 456, "max_queue": 789 },
+//       "queue": [{ "id": 1230, "parent": 455, "puzzle": [[1, 2, 3], ...], "g": 12, "h": 8,
+//                   "depth": 12, "slide": "1,2U1" }, ...],
+//       "closed": [[[1, 2, 3], ...], ...],
+//       "expanded": [{ "id": 455, "parent": 300, "slide": "1,1L1" }, ...]
+//     }
+// it's written next to the old one and renamed over it, so a crash while saving still leaves the
+// checkpoint before. the search only ever moves on from what's saved, so carrying on from a
+// checkpoint expands the same nodes in the same order and ends with the same answer and counts as
+// a search that never stopped. once the search is over the checkpoint is deleted.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::heuristic::{parse_heuristic, Heuristic};
+use crate::notation::{parse_slides, slides_to_string};
+use crate::schema::{self, DocumentFormat, ProblemDocument, SchemaVersion};
+use crate::slides::Slide;
+use crate::{
+    resume_search, Algorithm, EightPuzzle, EightPuzzleNode, Moves, Problem, SearchLimits,
+    SearchOutcome, SearchProgress, SearchState, SearchStats, StopReason,
+};
+
+// how many nodes are expanded between checkpoints when nothing else is asked for
+pub const DEFAULT_CHECKPOINT_EVERY: u64 = 100_000;
+
+#[derive(Clone, Debug)]
+pub struct CheckpointOptions {
+    pub path: PathBuf,
+    // how many nodes to expand between checkpoints
+    pub every: u64,
+}
+
+// a search read back from its checkpoint
+pub struct Checkpoint {
+    pub problem: Problem,
+    pub algorithm: Algorithm,
+    pub heuristic: String,
+    pub progress: SearchProgress,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SavedNode {
+    id: u64,
+    parent: Option<u64>,
+    puzzle: EightPuzzle,
+    g: u64,
+    h: u64,
+    depth: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    slide: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SavedStep {
+    id: u64,
+    parent: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    slide: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CheckpointDocument {
+    version: SchemaVersion,
+    problem: ProblemDocument,
+    algorithm: Algorithm,
+    heuristic: String,
+    next_id: u64,
+    stats: SearchStats,
+    queue: Vec<SavedNode>,
+    closed: Vec<EightPuzzle>,
+    expanded: Vec<SavedStep>,
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn slide_to_string(slide: &Option<Slide>) -> Option<String> {
+    slide.map(|slide| slides_to_string(&[slide]))
+}
+
+fn slide_from_string(text: &Option<String>) -> io::Result<Option<Slide>> {
+    let text = match text {
+        Some(text) => text,
+        None => return Ok(None),
+    };
+
+    match parse_slides(text) {
+        Ok(slides) if slides.len() == 1 => Ok(Some(slides[0])),
+        _ => Err(invalid_data(format!("bad slide \"{}\"", text))),
+    }
+}
+
+fn save(
+    path: &Path,
+    problem: &Problem,
+    algorithm: Algorithm,
+    heuristic: &str,
+    progress: &SearchProgress,
+) -> io::Result<()> {
+    // kept in the order they were expanded, so the same search saves the same file
+    let mut expanded: Vec<SavedStep> = progress
+        .expanded
+        .iter()
+        .map(|(id, (parent, slide))| SavedStep {
+            id: *id,
+            parent: *parent,
+            slide: slide_to_string(slide),
+        })
+        .collect();
+    expanded.sort_by_key(|step| step.id);
+    let mut closed: Vec<&EightPuzzle> = progress.closed.iter().collect();
+    closed.sort_by(|a, b| a.tiles.cmp(&b.tiles));
+
+    let document = CheckpointDocument {
+        version: SchemaVersion,
+        problem: ProblemDocument::new(problem),
+        algorithm,
+        heuristic: heuristic.to_string(),
+        next_id: progress.state.next_id,
+        stats: progress.state.stats.clone(),
+        queue: progress
+            .queue
+            .iter()
+            .map(|node| SavedNode {
+                id: node.id,
+                parent: node.parent,
+                puzzle: node.puzzle.clone(),
+                g: node.g,
+                h: node.h,
+                depth: node.depth,
+                slide: slide_to_string(&node.slide),
+            })
+            .collect(),
+        closed: closed.into_iter().cloned().collect(),
+        expanded,
+    };
+
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    fs::write(
+        &partial,
+        schema::to_string(&document, DocumentFormat::from_path(path))?,
+    )?;
+    fs::rename(&partial, path)
+}
+
+pub fn read_checkpoint(path: &Path) -> io::Result<Checkpoint> {
+    let document: CheckpointDocument = schema::read_document(path)?;
+    let problem = document.problem.to_problem().map_err(invalid_data)?;
+    // boards are written without their edges, they're the problem's
+    let topology = problem.initial_state.topology;
+
+    let mut queue = Vec::new();
+    for node in document.queue {
+        let slide = slide_from_string(&node.slide)?;
+        queue.push(EightPuzzleNode {
+            id: node.id,
+            parent: node.parent,
+            puzzle: node.puzzle.with_topology(topology),
+            g: node.g,
+            h: node.h,
+            depth: node.depth,
+            prev_move: slide.map_or(Moves::Nothing, |slide| slide.direction),
+            slide,
+        });
+    }
+    let mut expanded = HashMap::new();
+    for step in document.expanded {
+        expanded.insert(step.id, (step.parent, slide_from_string(&step.slide)?));
+    }
+
+    Ok(Checkpoint {
+        problem,
+        algorithm: document.algorithm,
+        heuristic: document.heuristic,
+        progress: SearchProgress {
+            queue,
+            closed: document
+                .closed
+                .into_iter()
+                .map(|puzzle| puzzle.with_topology(topology))
+                .collect(),
+            expanded,
+            state: SearchState {
+                next_id: document.next_id,
+                stats: document.stats,
+            },
+        },
+    })
+}
+
+// runs the search a stretch of expansions at a time, saving it after each
+fn run(
+    problem: Problem,
+    algorithm: Algorithm,
+    name: &str,
+    heuristic: &dyn Heuristic,
+    mut progress: Option<SearchProgress>,
+    limits: SearchLimits,
+    options: &CheckpointOptions,
+) -> io::Result<(SearchOutcome, SearchStats)> {
+    loop {
+        let expanded = progress
+            .as_ref()
+            .map_or(0, |progress| progress.state.stats.expanded);
+        let next_save = expanded + options.every.max(1);
+        let (max_expansions, limited) = match limits.max_expansions {
+            Some(max) if max <= next_save => (max, true),
+            _ => (next_save, false),
+        };
+        let stretch = SearchLimits {
+            max_expansions: Some(max_expansions),
+            ..limits.clone()
+        };
+
+        let (outcome, next) = resume_search(
+            problem.clone(),
+            heuristic,
+            algorithm,
+            stretch,
+            progress.take(),
+        );
+        save(&options.path, &problem, algorithm, name, &next)?;
+
+        match outcome {
+            SearchOutcome::Stopped(StopReason::ExpansionLimit) if !limited => {
+                progress = Some(next);
+            }
+            // stopped by the caller's limits, the checkpoint is where to carry on from
+            SearchOutcome::Stopped(_) => return Ok((outcome, next.state.stats)),
+            _ => {
+                fs::remove_file(&options.path)?;
+                return Ok((outcome, next.state.stats));
+            }
+        }
+    }
+}
+
+// searches like search_with_heuristic, saving the search every so often. the heuristic is given
+// by its name in heuristic.rs, so the checkpoint can say which it was
+pub fn search_with_checkpoints(
+    problem: Problem,
+    heuristic: &str,
+    algorithm: Algorithm,
+    limits: SearchLimits,
+    options: &CheckpointOptions,
+) -> io::Result<(SearchOutcome, SearchStats)> {
+    let parsed = parse_heuristic(heuristic).map_err(invalid_data)?;
+    run(
+        problem, algorithm, heuristic, &*parsed, None, limits, options,
+    )
+}
+
+// carries on a search read back with read_checkpoint, saving it again to options.path as it goes
+pub fn resume(
+    checkpoint: Checkpoint,
+    limits: SearchLimits,
+    options: &CheckpointOptions,
+) -> io::Result<(SearchOutcome, SearchStats)> {
+    let parsed = parse_heuristic(&checkpoint.heuristic).map_err(invalid_data)?;
+    run(
+        checkpoint.problem,
+        checkpoint.algorithm,
+        &checkpoint.heuristic,
+        &*parsed,
+        Some(checkpoint.progress),
+        limits,
+        options,
+    )
+}
@@ -0,0 +1,987 @@
+// handles running the program with arguments instead of answering the interactive prompts
+//
+//     trace-summary <trace file>
+//     trace-replay <trace file>
+//     solve <puzzle> [--goal <puzzle>] [--heuristic <name>] [--algorithm <name>] [--moves <model>]
+//           [--topology <name>] [--colors <colors>]
+//     solve-file <file> [--goal <puzzle>] [--heuristic <name>] [--algorithm <name>] [--moves <model>]
+//                [--topology <name>] [--colors <colors>]
+//     solve-constructive <puzzle> [--goal <puzzle>]
+//     solve-frontier <puzzle> [--goal <puzzle>] [--heuristic <name>] [--algorithm <name>]
+//                    [--topology <name>]
+//     count-solutions <puzzle> [--goal <puzzle>] [--heuristic <name>] [--moves <model>]
+//                     [--topology <name>] [--colors <colors>] [--costs <cost model>]
+//                     [--limit <count>] [--timeout <milliseconds>]
+//     solve-problem <problem file> [--heuristic <name>] [--algorithm <name>] [--output <result file>]
+//                   [--checkpoint <checkpoint file>] [--checkpoint-every <expansions>]
+//     resume-search <checkpoint file> [--output <result file>] [--checkpoint-every <expansions>]
+//     play-solution <puzzle> <moves> [--goal <puzzle>] [--delay <milliseconds>] [--topology <name>]
+//     check-solution <puzzle> <moves> [--goal <puzzle>] [--topology <name>] [--colors <colors>]
+//     shorten-solution <puzzle> <moves> [--goal <puzzle>] [--topology <name>] [--colors <colors>]
+//                      [--window <moves>]
+//     serve [--address <host:port>] [--max-solves <count>] [--timeout <milliseconds>]
+//     ffi-header
+//     analyze-heuristic [--heuristic <name>] [--rows <count>] [--cols <count>] [--samples <count>]
+//                       [--searches <count>] [--seed <number>]
+//     walking-tables [--rows <count>] [--cols <count>] [--goal <puzzle>]
+//     learn-macros [--rows <count>] [--cols <count>] [--goal <puzzle>] [--output <table file>]
+//     solve-macros <puzzle> [--goal <puzzle>] [--table <table file>] [--algorithm <name>]
+//                  [--heuristic <name>]
+//     external-bfs [--rows <count>] [--cols <count>] [--goal <puzzle>] [--directory <directory>]
+//                  [--buffer <boards>]
+//
+// heuristics are ucs, misplaced, manhattan (the default), linear-conflict, walking,
+// walking-linear-conflict, gaschnig or inversion-distance, or a combination of them written as in
+// heuristic.rs, like "max(walking, linear-conflict)". algorithms are astar (the default), ucs or greedy
+// and move models are single-tile (the default) or multi-tile. topologies are flat (the default) or
+// torus, where the edges wrap around. colors give tiles that can stand in for each other, like
+// "red=1,2 blue=3,4" (see colors.rs)
+//
+// walking distance tables are kept in the tables directory, walking-tables builds them ahead of
+// time and otherwise the first search that needs one does
+//
+// puzzles and moves use the one line notation, like "1 2 3/4 5 6/7 0 8" and "R", with a # for each
+// cell that's a wall and a 0 for each space. answers with more than one space or with multi-tile
+// moves are written as slides, like "3,2R2" (see notation.rs)
+// problem and result files are the JSON or YAML documents described in schema.rs
+//
+// with a --checkpoint, solve-problem saves the search to it every so many expansions (see
+// checkpoint.rs), and resume-search carries on from there after a crash or a stop
+//
+// solve-constructive doesn't search, it puts the tiles in place a row and a column at a time (see
+// constructive.rs). the moves are far from the fewest, but it copes with boards of any size
+//
+// solve-frontier finds the fewest moves keeping far fewer boards than A* (see frontier.rs), with
+// frontier-astar (the default) or bfhs, and says how many boards it kept
+//
+// count-solutions says how many different solutions are the cheapest and prints the first --limit
+// of them, 10 unless told otherwise (see optimal.rs). costs are unit (the default), tile-number or
+// a cost model written as in problem documents, like '{"type": "tile_weights", "weights": {"1": 5}}'
+//
+// learn-macros saves a table of macro operators for a board size and goal, in the tables directory
+// unless told otherwise (see macros.rs). solve-macros follows the table to the goal, or with an
+// --algorithm searches with the table's macros as moves of their own
+//
+// external-bfs counts the boards at every depth from the goal with a breadth first search kept on
+// disk (see external_bfs.rs), in tables/bfs-RxC unless told otherwise. stopping it and running it
+// again with the same directory carries on where it was
+
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use ai_homework::analysis::{self, AnalysisOptions};
+use ai_homework::checkpoint::{self, CheckpointOptions};
+use ai_homework::colors::TileColors;
+use ai_homework::constructive;
+use ai_homework::cost::CostModel;
+use ai_homework::external_bfs::{self, ExternalBfsOptions};
+use ai_homework::ffi_header;
+use ai_homework::frontier::{self, FrontierAlgorithm};
+use ai_homework::heuristic::{parse_heuristic, Heuristic};
+use ai_homework::macros::{self, MacroTable};
+use ai_homework::notation::{self, moves_to_string, parse_moves, slides_to_string};
+use ai_homework::optimal::{self, OptimalOutcome};
+use ai_homework::parse::Spaces;
+use ai_homework::schema::{self, DocumentFormat, ResultDocument};
+use ai_homework::server::{self, ServerConfig};
+use ai_homework::slides::{self, MoveModel};
+use ai_homework::torus::Topology;
+use ai_homework::walking_distance;
+use ai_homework::walls;
+use ai_homework::{
+    manhattan_distance_heuristic, paths, playback, search_with_heuristic, trace, Algorithm,
+    EightPuzzle, Moves, Problem, SearchLimits, PRINT_EXPANDING_NODES_TOGGLE,
+};
+
+fn usage(program: &str) {
+    println!("usage:");
+    println!("\t {} trace-summary <trace file>", program);
+    println!("\t {} trace-replay <trace file>", program);
+    println!(
+        "\t {} solve <puzzle> [--goal <puzzle>] [--heuristic <name>] [--algorithm <name>] [--moves <model>] [--topology <name>] [--colors <colors>]",
+        program
+    );
+    println!(
+        "\t {} solve-file <file> [--goal <puzzle>] [--heuristic <name>] [--algorithm <name>] [--moves <model>] [--topology <name>] [--colors <colors>]",
+        program
+    );
+    println!(
+        "\t {} solve-constructive <puzzle> [--goal <puzzle>]",
+        program
+    );
+    println!(
+        "\t {} solve-frontier <puzzle> [--goal <puzzle>] [--heuristic <name>] [--algorithm <name>] [--topology <name>]",
+        program
+    );
+    println!(
+        "\t {} count-solutions <puzzle> [--goal <puzzle>] [--heuristic <name>] [--moves <model>] [--topology <name>] [--colors <colors>] [--costs <cost model>] [--limit <count>] [--timeout <milliseconds>]",
+        program
+    );
+    println!(
+        "\t {} solve-problem <problem file> [--heuristic <name>] [--algorithm <name>] [--output <result file>] [--checkpoint <checkpoint file>] [--checkpoint-every <expansions>]",
+        program
+    );
+    println!(
+        "\t {} resume-search <checkpoint file> [--output <result file>] [--checkpoint-every <expansions>]",
+        program
+    );
+    println!(
+        "\t {} play-solution <puzzle> <moves> [--goal <puzzle>] [--delay <milliseconds>] [--topology <name>]",
+        program
+    );
+    println!(
+        "\t {} check-solution <puzzle> <moves> [--goal <puzzle>] [--topology <name>] [--colors <colors>]",
+        program
+    );
+    println!(
+        "\t {} shorten-solution <puzzle> <moves> [--goal <puzzle>] [--topology <name>] [--colors <colors>] [--window <moves>]",
+        program
+    );
+    println!(
+        "\t {} serve [--address <host:port>] [--max-solves <count>] [--timeout <milliseconds>]",
+        program
+    );
+    println!("\t {} ffi-header", program);
+    println!(
+        "\t {} analyze-heuristic [--heuristic <name>] [--rows <count>] [--cols <count>] [--samples <count>] [--searches <count>] [--seed <number>]",
+        program
+    );
+    println!(
+        "\t {} walking-tables [--rows <count>] [--cols <count>] [--goal <puzzle>]",
+        program
+    );
+    println!(
+        "\t {} learn-macros [--rows <count>] [--cols <count>] [--goal <puzzle>] [--output <table file>]",
+        program
+    );
+    println!(
+        "\t {} solve-macros <puzzle> [--goal <puzzle>] [--table <table file>] [--algorithm <name>] [--heuristic <name>]",
+        program
+    );
+    println!(
+        "\t {} external-bfs [--rows <count>] [--cols <count>] [--goal <puzzle>] [--directory <directory>] [--buffer <boards>]",
+        program
+    );
+    println!(
+        "heuristics: ucs, misplaced, manhattan, linear-conflict, walking, walking-linear-conflict, \
+         gaschnig, inversion-distance"
+    );
+    println!("combined heuristics: manhattan[<tiles>], max(...), sum(...), scale(<weight>, ...), fallback(...)");
+    println!("algorithms: astar, ucs, greedy, and frontier-astar or bfhs for solve-frontier");
+    println!("move models: single-tile, multi-tile");
+}
+
+// the arguments that aren't options, and the value of every --option
+struct Arguments {
+    positional: Vec<String>,
+    goal: Option<String>,
+    heuristic: Option<String>,
+    delay: Option<String>,
+    output: Option<String>,
+    algorithm: Option<String>,
+    moves: Option<String>,
+    topology: Option<String>,
+    colors: Option<String>,
+    window: Option<String>,
+    address: Option<String>,
+    max_solves: Option<String>,
+    timeout: Option<String>,
+    rows: Option<String>,
+    cols: Option<String>,
+    samples: Option<String>,
+    searches: Option<String>,
+    seed: Option<String>,
+    table: Option<String>,
+    directory: Option<String>,
+    buffer: Option<String>,
+    checkpoint: Option<String>,
+    checkpoint_every: Option<String>,
+    limit: Option<String>,
+    costs: Option<String>,
+}
+
+fn split_arguments(args: &[String]) -> Result<Arguments, String> {
+    let mut arguments = Arguments {
+        positional: Vec::new(),
+        goal: None,
+        heuristic: None,
+        delay: None,
+        output: None,
+        algorithm: None,
+        moves: None,
+        topology: None,
+        colors: None,
+        window: None,
+        address: None,
+        max_solves: None,
+        timeout: None,
+        rows: None,
+        cols: None,
+        samples: None,
+        searches: None,
+        seed: None,
+        table: None,
+        directory: None,
+        buffer: None,
+        checkpoint: None,
+        checkpoint_every: None,
+        limit: None,
+        costs: None,
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let slot = match arg.as_str() {
+            "--goal" => &mut arguments.goal,
+            "--heuristic" => &mut arguments.heuristic,
+            "--delay" => &mut arguments.delay,
+            "--output" => &mut arguments.output,
+            "--algorithm" => &mut arguments.algorithm,
+            "--moves" => &mut arguments.moves,
+            "--topology" => &mut arguments.topology,
+            "--colors" => &mut arguments.colors,
+            "--window" => &mut arguments.window,
+            "--address" => &mut arguments.address,
+            "--max-solves" => &mut arguments.max_solves,
+            "--timeout" => &mut arguments.timeout,
+            "--rows" => &mut arguments.rows,
+            "--cols" => &mut arguments.cols,
+            "--samples" => &mut arguments.samples,
+            "--searches" => &mut arguments.searches,
+            "--seed" => &mut arguments.seed,
+            "--table" => &mut arguments.table,
+            "--directory" => &mut arguments.directory,
+            "--buffer" => &mut arguments.buffer,
+            "--checkpoint" => &mut arguments.checkpoint,
+            "--checkpoint-every" => &mut arguments.checkpoint_every,
+            "--limit" => &mut arguments.limit,
+            "--costs" => &mut arguments.costs,
+            _ => {
+                arguments.positional.push(arg.clone());
+                continue;
+            }
+        };
+
+        match iter.next() {
+            Some(value) => *slot = Some(value.clone()),
+            None => return Err(format!("{} needs a value", arg)),
+        }
+    }
+
+    Ok(arguments)
+}
+
+fn heuristic_from_name(name: Option<&str>) -> Result<Box<dyn Heuristic>, String> {
+    parse_heuristic(name.unwrap_or("manhattan"))
+}
+
+fn algorithm_from_name(name: Option<&str>) -> Result<Algorithm, String> {
+    match name {
+        Some(name) => {
+            Algorithm::from_name(name).ok_or_else(|| format!("unknown algorithm {}", name))
+        }
+        None => Ok(Algorithm::AStar),
+    }
+}
+
+fn move_model_from_name(name: Option<&str>) -> Result<MoveModel, String> {
+    match name {
+        Some(name) => {
+            MoveModel::from_name(name).ok_or_else(|| format!("unknown move model {}", name))
+        }
+        None => Ok(MoveModel::SingleTile),
+    }
+}
+
+fn topology_from_name(name: Option<&str>) -> Result<Topology, String> {
+    match name {
+        Some(name) => Topology::from_name(name).ok_or_else(|| format!("unknown topology {}", name)),
+        None => Ok(Topology::Flat),
+    }
+}
+
+fn colors_from_text(text: Option<&str>) -> Result<TileColors, String> {
+    match text {
+        Some(text) => TileColors::parse(text).map_err(|error| format!("bad colors: {}", error)),
+        None => Ok(TileColors::default()),
+    }
+}
+
+fn cost_model_from_text(text: Option<&str>) -> Result<CostModel, String> {
+    match text {
+        None | Some("unit") => Ok(CostModel::Unit),
+        Some("tile-number") => Ok(CostModel::TileNumber),
+        Some(text) => schema::from_str(text, DocumentFormat::Json)
+            .map_err(|error| format!("bad cost model {}: {}", text, error)),
+    }
+}
+
+fn parse_puzzle(text: &str) -> Result<EightPuzzle, String> {
+    notation::parse_puzzle(text, Spaces::Several)
+        .map_err(|errors| format!("bad puzzle \"{}\": {}", text, errors))
+}
+
+// the goal given with --goal, or the usual goal for a board the shape of the puzzle, with the same
+// edges as the puzzle
+fn goal_for(puzzle: &EightPuzzle, goal: Option<&str>) -> Result<EightPuzzle, String> {
+    let goal = match goal {
+        Some(text) => parse_puzzle(text)?.with_topology(puzzle.topology),
+        None => return Ok(EightPuzzle::solved_like(puzzle)),
+    };
+
+    if goal.rows != puzzle.rows || goal.cols != puzzle.cols {
+        return Err(format!(
+            "the goal is {}x{} but the puzzle is {}x{}",
+            goal.rows, goal.cols, puzzle.rows, puzzle.cols
+        ));
+    }
+    if !walls::same_walls(&goal, puzzle) {
+        return Err("the goal's walls aren't where the puzzle's are".to_string());
+    }
+    if slides::space_count(&goal) != slides::space_count(puzzle) {
+        return Err(format!(
+            "the goal has {} spaces but the puzzle has {}",
+            slides::space_count(&goal),
+            slides::space_count(puzzle)
+        ));
+    }
+
+    Ok(goal)
+}
+
+// the solution as a move string, or as slides when the moves alone don't say enough, or None if
+// the goal can't be reached
+fn solve_one(
+    puzzle: EightPuzzle,
+    goal: Option<&str>,
+    heuristic: &dyn Heuristic,
+    algorithm: Algorithm,
+    move_model: MoveModel,
+    colors: &TileColors,
+) -> Result<Option<String>, String> {
+    let goal_state = goal_for(&puzzle, goal)?;
+    colors.check(&puzzle)?;
+    let problem = Problem {
+        move_model,
+        colors: colors.clone(),
+        ..Problem::new(puzzle, goal_state)
+    };
+    let plain = slides::is_plain(&problem);
+
+    let (outcome, _) =
+        search_with_heuristic(problem, heuristic, algorithm, SearchLimits::default());
+    Ok(outcome.solution().map(|solution| {
+        if plain {
+            moves_to_string(&solution.moves)
+        } else {
+            slides_to_string(&solution.slides)
+        }
+    }))
+}
+
+fn run_trace_command(command: &str, arguments: &Arguments) -> Result<(), String> {
+    if arguments.positional.len() != 1 {
+        return Err(format!("{} takes one trace file", command));
+    }
+
+    let path = Path::new(&arguments.positional[0]);
+    let result = match command {
+        "trace-summary" => trace::print_summary(path),
+        _ => trace::replay(path),
+    };
+
+    result.map_err(|error| format!("could not read the trace {}: {}", path.display(), error))
+}
+
+fn run_solve(arguments: &Arguments) -> Result<(), String> {
+    if arguments.positional.len() != 1 {
+        return Err("solve takes one puzzle".to_string());
+    }
+
+    let heuristic = heuristic_from_name(arguments.heuristic.as_deref())?;
+    let algorithm = algorithm_from_name(arguments.algorithm.as_deref())?;
+    let move_model = move_model_from_name(arguments.moves.as_deref())?;
+    let topology = topology_from_name(arguments.topology.as_deref())?;
+    let colors = colors_from_text(arguments.colors.as_deref())?;
+    let puzzle = parse_puzzle(&arguments.positional[0])?.with_topology(topology);
+
+    match solve_one(
+        puzzle,
+        arguments.goal.as_deref(),
+        &*heuristic,
+        algorithm,
+        move_model,
+        &colors,
+    )? {
+        Some(moves) => println!("{}", moves),
+        None => println!("no answer :("),
+    }
+
+    Ok(())
+}
+
+// solves one puzzle per line, blank lines and lines starting with # are skipped unless they're a
+// board whose first cell is a wall
+// every answer is printed as the puzzle and its moves split by a tab, or a - if there is none
+fn run_solve_file(arguments: &Arguments) -> Result<(), String> {
+    if arguments.positional.len() != 1 {
+        return Err("solve-file takes one file".to_string());
+    }
+
+    let heuristic = heuristic_from_name(arguments.heuristic.as_deref())?;
+    let algorithm = algorithm_from_name(arguments.algorithm.as_deref())?;
+    let move_model = move_model_from_name(arguments.moves.as_deref())?;
+    let topology = topology_from_name(arguments.topology.as_deref())?;
+    let colors = colors_from_text(arguments.colors.as_deref())?;
+    let path = &arguments.positional[0];
+    let contents =
+        fs::read_to_string(path).map_err(|error| format!("could not read {}: {}", path, error))?;
+
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        let is_comment = line.starts_with('#') && parse_puzzle(line).is_err();
+        if line.is_empty() || is_comment {
+            continue;
+        }
+
+        let puzzle =
+            parse_puzzle(line).map_err(|error| format!("line {}: {}", number + 1, error))?;
+        let canonical = puzzle.to_string();
+        let puzzle = puzzle.with_topology(topology);
+
+        match solve_one(
+            puzzle,
+            arguments.goal.as_deref(),
+            &*heuristic,
+            algorithm,
+            move_model,
+            &colors,
+        )? {
+            Some(moves) => println!("{}\t{}", canonical, moves),
+            None => println!("{}\t-", canonical),
+        }
+    }
+
+    Ok(())
+}
+
+fn run_solve_constructive(arguments: &Arguments) -> Result<(), String> {
+    if arguments.positional.len() != 1 {
+        return Err("solve-constructive takes one puzzle".to_string());
+    }
+
+    let puzzle = parse_puzzle(&arguments.positional[0])?;
+    let goal = goal_for(&puzzle, arguments.goal.as_deref())?;
+    let problem = Problem::new(puzzle, goal);
+
+    let moves = constructive::solve(&problem)?;
+    println!("{}", moves_to_string(&moves));
+    Ok(())
+}
+
+fn run_solve_frontier(arguments: &Arguments) -> Result<(), String> {
+    if arguments.positional.len() != 1 {
+        return Err("solve-frontier takes one puzzle".to_string());
+    }
+
+    let heuristic = heuristic_from_name(arguments.heuristic.as_deref())?;
+    let algorithm = match arguments.algorithm.as_deref() {
+        Some(name) => FrontierAlgorithm::from_name(name)
+            .ok_or_else(|| format!("unknown algorithm {}, use frontier-astar or bfhs", name))?,
+        None => FrontierAlgorithm::FrontierAStar,
+    };
+    let topology = topology_from_name(arguments.topology.as_deref())?;
+    let puzzle = parse_puzzle(&arguments.positional[0])?.with_topology(topology);
+    let goal = goal_for(&puzzle, arguments.goal.as_deref())?;
+    let problem = Problem::new(puzzle, goal);
+
+    let (outcome, stats) =
+        frontier::search(&problem, &*heuristic, algorithm, SearchLimits::default())?;
+    match outcome.solution() {
+        Some(solution) => println!("{}", moves_to_string(&solution.moves)),
+        None => println!("no answer :("),
+    }
+    println!(
+        "{} kept at most {} boards while its first search expanded {}",
+        algorithm.name(),
+        stats.max_stored,
+        stats.closed_boards
+    );
+    Ok(())
+}
+
+// prints how many solutions are the cheapest, then the first few of them
+fn run_count_solutions(arguments: &Arguments) -> Result<(), String> {
+    if arguments.positional.len() != 1 {
+        return Err("count-solutions takes one puzzle".to_string());
+    }
+
+    let heuristic = heuristic_from_name(arguments.heuristic.as_deref())?;
+    let move_model = move_model_from_name(arguments.moves.as_deref())?;
+    let topology = topology_from_name(arguments.topology.as_deref())?;
+    let colors = colors_from_text(arguments.colors.as_deref())?;
+    let cost_model = cost_model_from_text(arguments.costs.as_deref())?;
+    let limit = number_option(&arguments.limit, "limit", 10)?;
+    let limits = SearchLimits {
+        max_expansions: None,
+        deadline: match &arguments.timeout {
+            Some(_) => {
+                let timeout = number_option(&arguments.timeout, "timeout", 0)?;
+                Some(Instant::now() + Duration::from_millis(timeout))
+            }
+            None => None,
+        },
+        ..SearchLimits::default()
+    };
+    let puzzle = parse_puzzle(&arguments.positional[0])?.with_topology(topology);
+    let goal = goal_for(&puzzle, arguments.goal.as_deref())?;
+    colors.check(&puzzle)?;
+    let problem = Problem {
+        initial_state: puzzle,
+        goal_state: goal,
+        cost_model,
+        move_model,
+        colors,
+    };
+    let plain = slides::is_plain(&problem);
+
+    let (outcome, stats) = optimal::optimal_paths(&problem, &*heuristic, limits)?;
+    let graph = match outcome {
+        OptimalOutcome::Found(graph) => graph,
+        OptimalOutcome::Unsolvable => {
+            println!("no answer :(");
+            return Ok(());
+        }
+        OptimalOutcome::Stopped(_) => {
+            println!(
+                "ran out of time after expanding {} boards, the goal may still be reachable",
+                stats.expanded
+            );
+            return Ok(());
+        }
+    };
+
+    for path in graph.paths().take(limit) {
+        if plain {
+            let moves: Vec<Moves> = path.iter().map(|slide| slide.direction).collect();
+            println!("{}", moves_to_string(&moves));
+        } else {
+            println!("{}", slides_to_string(&path));
+        }
+    }
+    println!(
+        "{} solutions costing {}, through {} boards",
+        graph.count(),
+        graph.cost,
+        graph.boards()
+    );
+    Ok(())
+}
+
+// solves a problem document and writes the result document to --output, or prints it as JSON
+fn run_solve_problem(arguments: &Arguments) -> Result<(), String> {
+    if arguments.positional.len() != 1 {
+        return Err("solve-problem takes one problem file".to_string());
+    }
+
+    let heuristic_name = arguments.heuristic.as_deref().unwrap_or("manhattan");
+    let heuristic = heuristic_from_name(Some(heuristic_name))?;
+    let algorithm = algorithm_from_name(arguments.algorithm.as_deref())?;
+    let path = Path::new(&arguments.positional[0]);
+    let problem = schema::read_problem(path)
+        .map_err(|error| format!("could not read the problem {}: {}", path.display(), error))?;
+
+    let (outcome, stats) = match &arguments.checkpoint {
+        Some(checkpoint) => checkpoint::search_with_checkpoints(
+            problem.clone(),
+            heuristic_name,
+            algorithm,
+            SearchLimits::default(),
+            &checkpoint_options(checkpoint, arguments)?,
+        )
+        .map_err(|error| format!("could not save the checkpoint {}: {}", checkpoint, error))?,
+        None => search_with_heuristic(
+            problem.clone(),
+            &*heuristic,
+            algorithm,
+            SearchLimits::default(),
+        ),
+    };
+    let result = ResultDocument::new(&problem, algorithm, heuristic_name, &outcome, stats);
+    write_result(arguments, &result)
+}
+
+fn checkpoint_options(path: &str, arguments: &Arguments) -> Result<CheckpointOptions, String> {
+    Ok(CheckpointOptions {
+        path: path.into(),
+        every: number_option(
+            &arguments.checkpoint_every,
+            "checkpoint interval",
+            checkpoint::DEFAULT_CHECKPOINT_EVERY,
+        )?,
+    })
+}
+
+// writes a result document to --output, or prints it as JSON
+fn write_result(arguments: &Arguments, result: &ResultDocument) -> Result<(), String> {
+    match &arguments.output {
+        Some(output) => schema::write_document(Path::new(output), result)
+            .map_err(|error| format!("could not write the result {}: {}", output, error)),
+        None => {
+            let text = schema::to_string(result, DocumentFormat::Json)
+                .map_err(|error| format!("could not write the result: {}", error))?;
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+// carries on the search saved in a checkpoint and writes its result like solve-problem
+fn run_resume_search(arguments: &Arguments) -> Result<(), String> {
+    if arguments.positional.len() != 1 {
+        return Err("resume-search takes one checkpoint file".to_string());
+    }
+
+    let path = &arguments.positional[0];
+    let saved = checkpoint::read_checkpoint(Path::new(path))
+        .map_err(|error| format!("could not read the checkpoint {}: {}", path, error))?;
+    let (problem, algorithm, heuristic) = (
+        saved.problem.clone(),
+        saved.algorithm,
+        saved.heuristic.clone(),
+    );
+    let (outcome, stats) = checkpoint::resume(
+        saved,
+        SearchLimits::default(),
+        &checkpoint_options(path, arguments)?,
+    )
+    .map_err(|error| format!("could not carry on from {}: {}", path, error))?;
+
+    let result = ResultDocument::new(&problem, algorithm, &heuristic, &outcome, stats);
+    write_result(arguments, &result)
+}
+
+fn run_play_solution(arguments: &Arguments) -> Result<(), String> {
+    if arguments.positional.len() != 2 {
+        return Err("play-solution takes a puzzle and its moves".to_string());
+    }
+
+    let topology = topology_from_name(arguments.topology.as_deref())?;
+    let puzzle = parse_puzzle(&arguments.positional[0])?.with_topology(topology);
+    let goal = goal_for(&puzzle, arguments.goal.as_deref())?;
+    let moves = parse_moves(&arguments.positional[1])
+        .map_err(|error| format!("bad moves \"{}\": {}", arguments.positional[1], error))?;
+
+    let delay_ms = match &arguments.delay {
+        Some(delay) => delay
+            .parse::<u64>()
+            .map_err(|_| format!("bad delay {}", delay))?,
+        None => playback::DEFAULT_DELAY_MS,
+    };
+
+    playback::play(
+        puzzle,
+        goal,
+        &moves,
+        &manhattan_distance_heuristic,
+        delay_ms,
+    )
+    .map_err(|error| format!("could not play back the solution: {}", error))
+}
+
+// the puzzle, goal and moves check-solution and shorten-solution are given
+fn path_problem(command: &str, arguments: &Arguments) -> Result<(Problem, Vec<Moves>), String> {
+    if arguments.positional.len() != 2 {
+        return Err(format!("{} takes a puzzle and its moves", command));
+    }
+
+    let topology = topology_from_name(arguments.topology.as_deref())?;
+    let colors = colors_from_text(arguments.colors.as_deref())?;
+    let puzzle = parse_puzzle(&arguments.positional[0])?.with_topology(topology);
+    let goal = goal_for(&puzzle, arguments.goal.as_deref())?;
+    colors.check(&puzzle)?;
+    let moves = parse_moves(&arguments.positional[1])
+        .map_err(|error| format!("bad moves \"{}\": {}", arguments.positional[1], error))?;
+
+    let problem = Problem {
+        colors,
+        ..Problem::new(puzzle, goal)
+    };
+    Ok((problem, moves))
+}
+
+fn run_check_solution(arguments: &Arguments) -> Result<(), String> {
+    let (problem, moves) = path_problem("check-solution", arguments)?;
+    paths::check_path(&problem, &moves).map_err(|error| error.to_string())?;
+
+    println!("Reaches the goal in {} moves", moves.len());
+    Ok(())
+}
+
+// prints the shorter moves, the same moves if nothing could come off
+fn run_shorten_solution(arguments: &Arguments) -> Result<(), String> {
+    let (problem, moves) = path_problem("shorten-solution", arguments)?;
+    let window = number_option(&arguments.window, "window", paths::DEFAULT_WINDOW)?;
+    let shorter =
+        paths::shorten_path(&problem, &moves, window).map_err(|error| error.to_string())?;
+
+    println!("{}", moves_to_string(&shorter));
+    Ok(())
+}
+
+fn run_serve(arguments: &Arguments) -> Result<(), String> {
+    if !arguments.positional.is_empty() {
+        return Err("serve doesn't take a puzzle".to_string());
+    }
+
+    let mut config = ServerConfig::default();
+    if let Some(address) = &arguments.address {
+        config.address = address.clone();
+    }
+    if let Some(max_solves) = &arguments.max_solves {
+        config.max_solves = match max_solves.parse::<usize>() {
+            Ok(count) if count > 0 => count,
+            _ => return Err(format!("bad solve limit {}", max_solves)),
+        };
+    }
+    if let Some(timeout) = &arguments.timeout {
+        config.max_timeout_ms = timeout
+            .parse::<u64>()
+            .map_err(|_| format!("bad timeout {}", timeout))?;
+    }
+
+    let handle =
+        server::start(config).map_err(|error| format!("could not start the server: {}", error))?;
+    println!("Serving puzzles on http://{}", handle.address());
+    handle.wait();
+
+    Ok(())
+}
+
+// the value of a numeric option, or the default when it wasn't given
+fn number_option<T: std::str::FromStr>(
+    value: &Option<String>,
+    what: &str,
+    default: T,
+) -> Result<T, String> {
+    match value {
+        Some(text) => text
+            .parse::<T>()
+            .map_err(|_| format!("bad {} {}", what, text)),
+        None => Ok(default),
+    }
+}
+
+fn run_analyze_heuristic(arguments: &Arguments) -> Result<(), String> {
+    if !arguments.positional.is_empty() {
+        return Err("analyze-heuristic doesn't take a puzzle".to_string());
+    }
+
+    let name = arguments.heuristic.as_deref().unwrap_or("manhattan");
+    let heuristic = heuristic_from_name(Some(name))?;
+
+    let defaults = AnalysisOptions::default();
+    let options = AnalysisOptions {
+        rows: number_option(&arguments.rows, "row count", defaults.rows)?,
+        cols: number_option(&arguments.cols, "column count", defaults.cols)?,
+        samples: number_option(&arguments.samples, "sample count", defaults.samples)?,
+        searches: number_option(&arguments.searches, "search count", defaults.searches)?,
+        seed: number_option(&arguments.seed, "seed", defaults.seed)?,
+        ..defaults
+    };
+    if options.rows < 2 || options.cols < 2 {
+        return Err("boards need at least 2 rows and 2 columns".to_string());
+    }
+
+    let report = analysis::analyze(&*heuristic, &options);
+    analysis::print_report(name, &report);
+
+    Ok(())
+}
+
+fn run_walking_tables(arguments: &Arguments) -> Result<(), String> {
+    if !arguments.positional.is_empty() {
+        return Err("walking-tables doesn't take a puzzle".to_string());
+    }
+
+    let rows = number_option(&arguments.rows, "row count", 4)?;
+    let cols = number_option(&arguments.cols, "column count", rows)?;
+    if rows < 2 || cols < 2 {
+        return Err("boards need at least 2 rows and 2 columns".to_string());
+    }
+    let goal = goal_for(&EightPuzzle::solved(rows, cols), arguments.goal.as_deref())?;
+
+    let directory = Path::new(walking_distance::DEFAULT_TABLE_DIRECTORY);
+    let paths = walking_distance::save_tables(directory, &goal)
+        .map_err(|error| format!("could not save the tables: {}", error))?;
+
+    if paths.is_empty() {
+        println!(
+            "A {}x{} board is too big for walking distance tables, manhattan distance is used instead",
+            rows, cols
+        );
+    }
+    for path in paths {
+        println!("Saved {}", path.display());
+    }
+
+    Ok(())
+}
+
+fn run_learn_macros(arguments: &Arguments) -> Result<(), String> {
+    if !arguments.positional.is_empty() {
+        return Err("learn-macros doesn't take a puzzle".to_string());
+    }
+
+    let rows = number_option(&arguments.rows, "row count", 3)?;
+    let cols = number_option(&arguments.cols, "column count", rows)?;
+    if rows < 2 || cols < 2 {
+        return Err("boards need at least 2 rows and 2 columns".to_string());
+    }
+    let goal = goal_for(&EightPuzzle::solved(rows, cols), arguments.goal.as_deref())?;
+
+    let table = macros::learn(&goal, macros::DEFAULT_MAX_BOARDS)?;
+    let path = match &arguments.output {
+        Some(output) => output.into(),
+        None => macros::default_path(goal.rows, goal.cols),
+    };
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)
+            .map_err(|error| format!("could not make {}: {}", directory.display(), error))?;
+    }
+    schema::write_document(&path, &table)
+        .map_err(|error| format!("could not write the table {}: {}", path.display(), error))?;
+
+    println!(
+        "Saved {}, it solves any board in at most {} moves",
+        path.display(),
+        table.longest()
+    );
+    Ok(())
+}
+
+fn run_solve_macros(arguments: &Arguments) -> Result<(), String> {
+    if arguments.positional.len() != 1 {
+        return Err("solve-macros takes one puzzle".to_string());
+    }
+
+    let puzzle = parse_puzzle(&arguments.positional[0])?;
+    let goal = goal_for(&puzzle, arguments.goal.as_deref())?;
+    let path = match &arguments.table {
+        Some(table) => table.into(),
+        None => macros::default_path(puzzle.rows, puzzle.cols),
+    };
+    let table: MacroTable = schema::read_document(&path)
+        .map_err(|error| format!("could not read the table {}: {}", path.display(), error))?;
+    let problem = Problem::new(puzzle, goal);
+
+    if arguments.algorithm.is_none() {
+        let moves = macros::solve(&table, &problem)?;
+        println!("{}", moves_to_string(&moves));
+        return Ok(());
+    }
+
+    let heuristic = heuristic_from_name(arguments.heuristic.as_deref())?;
+    let algorithm = algorithm_from_name(arguments.algorithm.as_deref())?;
+    let (outcome, _) = macros::search_with_macros(
+        problem,
+        &*heuristic,
+        &table,
+        algorithm,
+        SearchLimits::default(),
+    )?;
+    match outcome.solution() {
+        Some(solution) => println!("{}", moves_to_string(&solution.moves)),
+        None => println!("no answer :("),
+    }
+    Ok(())
+}
+
+fn run_external_bfs(arguments: &Arguments) -> Result<(), String> {
+    if !arguments.positional.is_empty() {
+        return Err("external-bfs doesn't take a puzzle".to_string());
+    }
+
+    let rows = number_option(&arguments.rows, "row count", 3)?;
+    let cols = number_option(&arguments.cols, "column count", rows)?;
+    if rows < 2 || cols < 2 {
+        return Err("boards need at least 2 rows and 2 columns".to_string());
+    }
+    let goal = goal_for(&EightPuzzle::solved(rows, cols), arguments.goal.as_deref())?;
+
+    let defaults = ExternalBfsOptions::default();
+    let options = ExternalBfsOptions {
+        directory: match &arguments.directory {
+            Some(directory) => directory.into(),
+            None => Path::new(walking_distance::DEFAULT_TABLE_DIRECTORY)
+                .join(format!("bfs-{}x{}", goal.rows, goal.cols)),
+        },
+        buffer_boards: number_option(&arguments.buffer, "buffer size", defaults.buffer_boards)?,
+        ..defaults
+    };
+
+    let progress = external_bfs::run(&goal, &options).map_err(|error| {
+        format!(
+            "the search in {} stopped: {}",
+            options.directory.display(),
+            error
+        )
+    })?;
+    for (depth, boards) in progress.layers.iter().enumerate() {
+        println!("{}\t{}", depth, boards);
+    }
+    println!(
+        "{} boards, the farthest {} moves from the goal",
+        progress.boards(),
+        progress.layers.len() - 1
+    );
+    Ok(())
+}
+
+pub fn run(args: &[String]) {
+    let arguments = match split_arguments(&args[2..]) {
+        Ok(arguments) => arguments,
+        Err(error) => {
+            println!("Woops! {}", error);
+            return;
+        }
+    };
+
+    // answers are meant to be read by other programs, so the search stays quiet
+    PRINT_EXPANDING_NODES_TOGGLE.store(false, Ordering::Relaxed);
+    walking_distance::set_table_directory(Some(walking_distance::DEFAULT_TABLE_DIRECTORY.into()));
+
+    let result = match args[1].as_str() {
+        "trace-summary" | "trace-replay" => run_trace_command(&args[1], &arguments),
+        "solve" => run_solve(&arguments),
+        "solve-file" => run_solve_file(&arguments),
+        "solve-constructive" => run_solve_constructive(&arguments),
+        "solve-frontier" => run_solve_frontier(&arguments),
+        "count-solutions" => run_count_solutions(&arguments),
+        "solve-problem" => run_solve_problem(&arguments),
+        "resume-search" => run_resume_search(&arguments),
+        "play-solution" => run_play_solution(&arguments),
+        "check-solution" => run_check_solution(&arguments),
+        "shorten-solution" => run_shorten_solution(&arguments),
+        "serve" => run_serve(&arguments),
+        "analyze-heuristic" => run_analyze_heuristic(&arguments),
+        "walking-tables" => run_walking_tables(&arguments),
+        "learn-macros" => run_learn_macros(&arguments),
+        "solve-macros" => run_solve_macros(&arguments),
+        "external-bfs" => run_external_bfs(&arguments),
+        "ffi-header" => {
+            print!("{}", ffi_header::generate());
+            Ok(())
+        }
+        _ => {
+            usage(&args[0]);
+            return;
+        }
+    };
+
+    if let Err(error) = result {
+        println!("Woops! {}", error);
+    }
+}
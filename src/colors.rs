@@ -0,0 +1,317 @@
+// puzzles where some tiles are painted the same colour and any of them can go where another should
+//
+// the colours are kept with the problem, as a colour for some of the tiles, and a tile without one
+// is only ever itself. the goal is still a whole board, but only the colours on it have to be
+// matched, so two boards that differ only in which red tile is where are the same board to the
+// search. it tells them apart by their canonical form, every tile swapped for the smallest tile of
+// its colour.
+//
+// in documents the colours look like
+//     { "1": "red", "2": "red", "3": "blue", "4": "blue" }
+// and on the command line like "red=1,2 blue=3,4".
+//
+// heuristics are given canonical boards, where a number can turn up more than once. manhattan
+// distance gives the tiles of each colour the goal cells that are cheapest to get them to all
+// together, by the hungarian method. the heuristics that follow each tile to its own spot can't,
+// and give manhattan distance instead.
+//
+// the parity of a board doesn't matter once two tiles can trade places without anyone noticing,
+// so every arrangement with the right colours in it can be solved unless the tiles can't pass each
+// other at all, as on a single row or a ring.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::heuristic::{ComponentCount, Heuristic};
+use crate::{torus, walls, EightPuzzle, BLANK, WALL};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TileColors {
+    #[serde(deserialize_with = "crate::cost::tile_keyed")]
+    pub colors: BTreeMap<u16, String>,
+}
+
+impl TileColors {
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    // reads "red=1,2 blue=3,4"
+    pub fn parse(text: &str) -> Result<TileColors, String> {
+        let mut colors = BTreeMap::new();
+
+        for group in text.split_whitespace() {
+            let (name, tiles) = match group.find('=') {
+                Some(equals) => (&group[..equals], &group[equals + 1..]),
+                None => return Err(format!("{} needs a colour, like red=1,2", group)),
+            };
+            if name.is_empty() {
+                return Err(format!("{} needs a colour, like red=1,2", group));
+            }
+
+            for tile in tiles.split(',').filter(|tile| !tile.is_empty()) {
+                let tile = tile
+                    .parse::<u16>()
+                    .map_err(|_| format!("bad tile {}", tile))?;
+                if colors.insert(tile, name.to_string()).is_some() {
+                    return Err(format!("tile {} has two colours", tile));
+                }
+            }
+        }
+
+        Ok(TileColors { colors })
+    }
+
+    // every coloured tile has to be a tile of the board
+    pub fn check(&self, puzzle: &EightPuzzle) -> Result<(), String> {
+        for tile in self.colors.keys() {
+            if *tile == BLANK || !puzzle.tiles.contains(tile) {
+                return Err(format!("tile {} has a colour but isn't on the board", tile));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn classes(&self) -> TileClasses {
+        let largest = self.colors.keys().cloned().max().unwrap_or(0);
+        let mut table: Vec<u16> = (0..=largest).collect();
+        let mut smallest: BTreeMap<&str, u16> = BTreeMap::new();
+
+        // the keys come in order, so the first tile seen of each colour is its smallest
+        for (tile, color) in self.colors.iter() {
+            table[*tile as usize] = *smallest.entry(color.as_str()).or_insert(*tile);
+        }
+
+        TileClasses { table }
+    }
+
+    pub fn canonical(&self, puzzle: &EightPuzzle) -> EightPuzzle {
+        self.classes().canonical(puzzle)
+    }
+
+    // whether the boards have the same colours in the same cells
+    pub fn matches(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> bool {
+        if self.is_empty() {
+            return puzzle == goal;
+        }
+        let classes = self.classes();
+        classes.canonical(puzzle) == classes.canonical(goal)
+    }
+}
+
+impl fmt::Display for TileColors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut groups: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for (tile, color) in self.colors.iter() {
+            groups.entry(color).or_default().push(tile.to_string());
+        }
+
+        let groups: Vec<String> = groups
+            .iter()
+            .map(|(color, tiles)| format!("{}={}", color, tiles.join(",")))
+            .collect();
+        write!(f, "{}", groups.join(" "))
+    }
+}
+
+// which tile stands for each tile's colour, worked out once for a search
+pub struct TileClasses {
+    table: Vec<u16>,
+}
+
+impl TileClasses {
+    pub fn class(&self, tile: u16) -> u16 {
+        self.table.get(tile as usize).cloned().unwrap_or(tile)
+    }
+
+    pub fn canonical(&self, puzzle: &EightPuzzle) -> EightPuzzle {
+        let mut canonical = puzzle.clone();
+        for tile in canonical.tiles.iter_mut() {
+            *tile = self.class(*tile);
+        }
+        canonical
+    }
+}
+
+// whether some tile turns up more than once, which only happens on a canonical board
+pub fn has_interchangeable_tiles(puzzle: &EightPuzzle) -> bool {
+    let mut seen = vec![false; puzzle.tiles.len()];
+
+    for tile in puzzle.tiles.iter() {
+        if *tile == BLANK || *tile == WALL {
+            continue;
+        }
+        match seen.get_mut(*tile as usize) {
+            Some(seen) if *seen => return true,
+            Some(seen) => *seen = true,
+            None => {}
+        }
+    }
+
+    false
+}
+
+// the least total cost of giving every row its own column, by the hungarian method with
+// potentials, for a square table
+fn cheapest_assignment(costs: &[Vec<u64>]) -> u64 {
+    let size = costs.len();
+    // counted from 1, 0 is a column nothing is assigned to yet
+    let mut row_potential = vec![0i64; size + 1];
+    let mut col_potential = vec![0i64; size + 1];
+    let mut row_of = vec![0usize; size + 1];
+    let mut way = vec![0usize; size + 1];
+
+    for row in 1..=size {
+        row_of[0] = row;
+        let mut col = 0;
+        let mut least = vec![i64::MAX; size + 1];
+        let mut used = vec![false; size + 1];
+
+        loop {
+            used[col] = true;
+            let current = row_of[col];
+            let mut delta = i64::MAX;
+            let mut next = 0;
+
+            for other in 1..=size {
+                if used[other] {
+                    continue;
+                }
+                let reduced = costs[current - 1][other - 1] as i64
+                    - row_potential[current]
+                    - col_potential[other];
+                if reduced < least[other] {
+                    least[other] = reduced;
+                    way[other] = col;
+                }
+                if least[other] < delta {
+                    delta = least[other];
+                    next = other;
+                }
+            }
+
+            for other in 0..=size {
+                if used[other] {
+                    row_potential[row_of[other]] += delta;
+                    col_potential[other] -= delta;
+                } else {
+                    least[other] -= delta;
+                }
+            }
+
+            col = next;
+            if row_of[col] == 0 {
+                break;
+            }
+        }
+
+        // flips the assignments along the path that was found
+        loop {
+            let previous = way[col];
+            row_of[col] = row_of[previous];
+            col = previous;
+            if col == 0 {
+                break;
+            }
+        }
+    }
+
+    (1..=size).map(|col| costs[row_of[col] - 1][col - 1]).sum()
+}
+
+// manhattan distance on a canonical board, with the tiles of each colour sent to the goal cells
+// of that colour in the way that needs the fewest moves. around walls and on a torus the
+// distances are the ones manhattan distance uses there
+pub fn matched_manhattan(puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+    let around_walls = if walls::has_walls(puzzle) {
+        Some(walls::cell_distances(puzzle))
+    } else {
+        None
+    };
+    let wraps = torus::wraps(puzzle);
+    let (rows, cols) = (puzzle.rows, puzzle.cols);
+    let distance = |from: usize, to: usize| -> u64 {
+        match &around_walls {
+            // cells walls cut off from each other count as far as they can be
+            Some(distances) => distances.between(from, to).min(u32::MAX as u64),
+            None => {
+                torus::axis_distance(from / cols, to / cols, rows, wraps)
+                    + torus::axis_distance(from % cols, to % cols, cols, wraps)
+            }
+        }
+    };
+
+    // where the tiles of each class are now and where they go
+    let mut cells: BTreeMap<u16, (Vec<usize>, Vec<usize>)> = BTreeMap::new();
+    for (index, tile) in puzzle.tiles.iter().enumerate() {
+        if *tile != BLANK && *tile != WALL {
+            cells.entry(*tile).or_default().0.push(index);
+        }
+    }
+    for (index, tile) in goal.tiles.iter().enumerate() {
+        if *tile != BLANK && *tile != WALL {
+            cells.entry(*tile).or_default().1.push(index);
+        }
+    }
+
+    cells
+        .values()
+        // a board with the wrong number of some colour can't be solved, whatever this says
+        .filter(|(from, to)| from.len() == to.len())
+        .map(|(from, to)| {
+            if from.len() == 1 {
+                return distance(from[0], to[0]);
+            }
+            let costs: Vec<Vec<u64>> = from
+                .iter()
+                .map(|from| to.iter().map(|to| distance(*from, *to)).collect())
+                .collect();
+            cheapest_assignment(&costs)
+        })
+        .sum()
+}
+
+// runs a heuristic on the canonical boards, the search puts this around the one it was given when
+// the problem has colours
+pub struct ColoredHeuristic<'a> {
+    inner: &'a dyn Heuristic,
+    classes: TileClasses,
+}
+
+impl<'a> ColoredHeuristic<'a> {
+    pub fn new(inner: &'a dyn Heuristic, colors: &TileColors) -> ColoredHeuristic<'a> {
+        ColoredHeuristic {
+            inner,
+            classes: colors.classes(),
+        }
+    }
+}
+
+// under a cost model the estimate is scaled by the cheapest move there is, the costs of each tile
+// don't carry over to the others of its colour
+impl<'a> Heuristic for ColoredHeuristic<'a> {
+    fn estimate(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+        self.inner.estimate(
+            &self.classes.canonical(puzzle),
+            &self.classes.canonical(goal),
+        )
+    }
+
+    fn tiles(&self) -> Option<Vec<u16>> {
+        self.inner.tiles()
+    }
+
+    fn supports(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> bool {
+        self.inner.supports(
+            &self.classes.canonical(puzzle),
+            &self.classes.canonical(goal),
+        )
+    }
+
+    fn take_max_counts(&self) -> Vec<ComponentCount> {
+        self.inner.take_max_counts()
+    }
+}
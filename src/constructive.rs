@@ -0,0 +1,529 @@
+// solving boards far too big to search, the way people do it by hand
+//
+// the top row is put in place, then the left column, then the next row and so on, each time
+// leaving a board one smaller to finish, until only a 2x3 block (or 3x2, or 2x2) is left in the
+// bottom right corner. that block is finished from a table of the fewest moves from every
+// arrangement of it, worked out by a breadth first search back from its goal.
+//
+// a column is solved by the same code as a row with the rows and columns traded, so everything
+// below works in a frame that is the board itself while a row is solved and the board turned on
+// its side while a column is.
+//
+// all but the last two tiles of a line go straight to their cells, one step at a time with the
+// space brought round in front of them. the last two can't go in that way without knocking the
+// one before out again, so they're brought into the corner of the board around their cells and
+// put in place together by a search over where those two tiles and the space are in it, the other
+// tiles there left to go wherever.
+//
+// the answer is nowhere near the fewest moves, but it comes out in time that grows with the cube
+// of the side of the board rather than exponentially. only flat boards with one space and no
+// walls or colours can be solved like this.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{colors, slides, torus, walls, EightPuzzle, Moves, Problem, BLANK};
+
+// a cell as (row, column) in the frame
+type Cell = (usize, usize);
+
+const DIRECTIONS: [Moves; 4] = [Moves::Up, Moves::Down, Moves::Left, Moves::Right];
+
+struct Builder {
+    rows: usize,
+    cols: usize,
+    tiles: Vec<u16>,
+    goal: Vec<u16>,
+    // cells already solved, which nothing moves through again
+    locked: Vec<bool>,
+    blank: usize,
+    moves: Vec<Moves>,
+    // the top left corner of what's left to solve, on the board
+    top: usize,
+    left: usize,
+    // whether rows and columns are traded, while a column is solved
+    transposed: bool,
+    // for route, by the cell's index on the board: the stamp of the last search that got there and
+    // where it came from
+    visited: Vec<u32>,
+    came_from: Vec<(Cell, Moves)>,
+    stamp: u32,
+}
+
+impl Builder {
+    fn size(&self) -> (usize, usize) {
+        if self.transposed {
+            (self.cols, self.rows)
+        } else {
+            (self.rows, self.cols)
+        }
+    }
+
+    fn corner(&self) -> Cell {
+        if self.transposed {
+            (self.left, self.top)
+        } else {
+            (self.top, self.left)
+        }
+    }
+
+    fn index(&self, (row, col): Cell) -> usize {
+        if self.transposed {
+            col * self.cols + row
+        } else {
+            row * self.cols + col
+        }
+    }
+
+    fn cell(&self, index: usize) -> Cell {
+        let (row, col) = (index / self.cols, index % self.cols);
+        if self.transposed {
+            (col, row)
+        } else {
+            (row, col)
+        }
+    }
+
+    // a move in the frame as a move on the board
+    fn on_board(&self, direction: Moves) -> Moves {
+        if !self.transposed {
+            return direction;
+        }
+        match direction {
+            Moves::Up => Moves::Left,
+            Moves::Down => Moves::Right,
+            Moves::Left => Moves::Up,
+            Moves::Right => Moves::Down,
+            Moves::Nothing => Moves::Nothing,
+        }
+    }
+
+    // the cell next to this one in the frame, None off the edge
+    fn step(&self, (row, col): Cell, direction: Moves) -> Option<Cell> {
+        let (rows, cols) = self.size();
+        match direction {
+            Moves::Up if row > 0 => Some((row - 1, col)),
+            Moves::Down if row + 1 < rows => Some((row + 1, col)),
+            Moves::Left if col > 0 => Some((row, col - 1)),
+            Moves::Right if col + 1 < cols => Some((row, col + 1)),
+            _ => None,
+        }
+    }
+
+    // in what's left to solve and not locked
+    fn is_free(&self, cell: Cell) -> bool {
+        let (rows, cols) = self.size();
+        let (top, left) = self.corner();
+        cell.0 >= top
+            && cell.1 >= left
+            && cell.0 < rows
+            && cell.1 < cols
+            && !self.locked[self.index(cell)]
+    }
+
+    fn find(&self, tile: u16) -> Cell {
+        self.cell(self.tiles.iter().position(|other| *other == tile).unwrap())
+    }
+
+    // moves the space one cell in the frame
+    fn slide(&mut self, direction: Moves) {
+        let to = self.step(self.cell(self.blank), direction).unwrap();
+        let to = self.index(to);
+        self.tiles.swap(self.blank, to);
+        self.blank = to;
+        self.moves.push(self.on_board(direction));
+    }
+
+    // the fewest moves taking something from one cell to a cell that's done, through free cells
+    // that aren't avoided and that lie between the given corners
+    fn route(
+        &mut self,
+        from: Cell,
+        done: &dyn Fn(Cell) -> bool,
+        avoid: &[Cell],
+        (low, high): (Cell, Cell),
+    ) -> Option<Vec<Moves>> {
+        // a new stamp marks every cell unvisited without clearing anything
+        self.stamp += 1;
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+        let index = self.index(from);
+        self.visited[index] = self.stamp;
+
+        while let Some(cell) = queue.pop_front() {
+            if done(cell) {
+                let mut path = Vec::new();
+                let mut at = cell;
+                while at != from {
+                    let (previous, direction) = self.came_from[self.index(at)];
+                    path.push(direction);
+                    at = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for direction in DIRECTIONS.iter() {
+                let next = match self.step(cell, *direction) {
+                    Some(next) => next,
+                    None => continue,
+                };
+                let inside =
+                    next.0 >= low.0 && next.1 >= low.1 && next.0 <= high.0 && next.1 <= high.1;
+                let index = self.index(next);
+                if inside
+                    && self.visited[index] != self.stamp
+                    && self.is_free(next)
+                    && !avoid.contains(&next)
+                {
+                    self.visited[index] = self.stamp;
+                    self.came_from[index] = (cell, *direction);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+
+    // everything still to solve, for route
+    fn everywhere(&self) -> (Cell, Cell) {
+        let (rows, cols) = self.size();
+        (self.corner(), (rows - 1, cols - 1))
+    }
+
+    // takes the space to a cell without going through the avoided ones. it looks close by first,
+    // the box around the space and the cell, before searching everything that's left
+    fn move_blank(&mut self, to: Cell, avoid: &[Cell]) -> Result<(), String> {
+        let from = self.cell(self.blank);
+        let (rows, cols) = self.size();
+        let nearby = (
+            (
+                from.0.min(to.0).saturating_sub(1),
+                from.1.min(to.1).saturating_sub(1),
+            ),
+            (
+                (from.0.max(to.0) + 1).min(rows - 1),
+                (from.1.max(to.1) + 1).min(cols - 1),
+            ),
+        );
+
+        let done = |cell: Cell| cell == to;
+        let everywhere = self.everywhere();
+        let path = match self.route(from, &done, avoid, nearby) {
+            Some(path) => path,
+            None => self
+                .route(from, &done, avoid, everywhere)
+                .ok_or_else(|| "the space got shut in".to_string())?,
+        };
+
+        for direction in path {
+            self.slide(direction);
+        }
+        Ok(())
+    }
+
+    // moves a tile a cell at a time until it's somewhere done, bringing the space round in front
+    // of it for every step, and gives back where it ended up
+    fn move_tile(
+        &mut self,
+        tile: u16,
+        done: &dyn Fn(Cell) -> bool,
+        avoid: &[Cell],
+    ) -> Result<Cell, String> {
+        let mut at = self.find(tile);
+        let everywhere = self.everywhere();
+        let path = self
+            .route(at, done, avoid, everywhere)
+            .ok_or_else(|| format!("tile {} can't get to its place", tile))?;
+
+        for direction in path {
+            let next = self.step(at, direction).unwrap();
+            let mut keep_clear = avoid.to_vec();
+            keep_clear.push(at);
+            self.move_blank(next, &keep_clear)?;
+            self.slide(direction.opposite());
+            at = next;
+        }
+
+        Ok(at)
+    }
+
+    // puts the tiles of the top row of the frame in place and locks them
+    fn solve_line(&mut self) -> Result<(), String> {
+        let (_, cols) = self.size();
+        let (top, left) = self.corner();
+
+        for col in left..cols - 2 {
+            let cell = (top, col);
+            let tile = self.goal[self.index(cell)];
+            self.move_tile(tile, &|at| at == cell, &[])?;
+            self.lock(&[cell]);
+        }
+
+        self.solve_last_two()?;
+
+        if self.transposed {
+            self.left += 1;
+        } else {
+            self.top += 1;
+        }
+        Ok(())
+    }
+
+    // the last two cells of the top row, solved together in the corner around them
+    fn solve_last_two(&mut self) -> Result<(), String> {
+        let (rows, cols) = self.size();
+        let (top, left) = self.corner();
+        let first = (top, cols - 2);
+        let second = (top, cols - 1);
+        let first_tile = self.goal[self.index(first)];
+        let second_tile = self.goal[self.index(second)];
+
+        if self.tiles[self.index(first)] == first_tile
+            && self.tiles[self.index(second)] == second_tile
+        {
+            self.lock(&[first, second]);
+            return Ok(());
+        }
+
+        // the tiles are brought into the corner of three rows and columns around their cells, and
+        // put in place by a search over a corner one bigger each way, which also holds the cells
+        // the space can get shut in while the second tile comes past the first
+        let inner = ((top, left.max(cols.saturating_sub(3))), (top + 2, cols - 1));
+        let outer = (
+            (top, left.max(cols.saturating_sub(4))),
+            ((top + 3).min(rows - 1), cols - 1),
+        );
+        let within = |(low, high): (Cell, Cell)| {
+            move |cell: Cell| {
+                cell.0 >= low.0 && cell.1 >= low.1 && cell.0 <= high.0 && cell.1 <= high.1
+            }
+        };
+
+        let first_at = self.move_tile(first_tile, &within(inner), &[])?;
+        // the first tile is held where it is while the second comes in, if the space gets shut in
+        // on the way the second tile is next to it and the search takes over from there
+        let _ = self.move_tile(second_tile, &within(inner), &[first_at]);
+        let second_at = self.find(second_tile);
+
+        if !within(outer)(self.cell(self.blank)) {
+            let (low, high) = outer;
+            let spots: Vec<Cell> = (low.0..=high.0)
+                .flat_map(|row| (low.1..=high.1).map(move |col| (row, col)))
+                .filter(|cell| self.is_free(*cell) && *cell != first_at && *cell != second_at)
+                .collect();
+            let avoid = [first_at, second_at];
+            if !spots
+                .iter()
+                .any(|spot| self.move_blank(*spot, &avoid).is_ok())
+            {
+                return Err("the space can't get into the corner".to_string());
+            }
+        }
+
+        let path = self
+            .pair_moves(first_at, second_at, (first, second), outer)
+            .ok_or_else(|| {
+                format!(
+                    "tiles {} and {} can't be put in place",
+                    first_tile, second_tile
+                )
+            })?;
+        for direction in path {
+            self.slide(direction);
+        }
+
+        self.lock(&[first, second]);
+        Ok(())
+    }
+
+    fn lock(&mut self, cells: &[Cell]) {
+        for cell in cells {
+            let index = self.index(*cell);
+            self.locked[index] = true;
+        }
+    }
+
+    // the fewest moves of the space inside the corner taking two tiles to their cells
+    fn pair_moves(
+        &self,
+        first: Cell,
+        second: Cell,
+        targets: (Cell, Cell),
+        (low, high): (Cell, Cell),
+    ) -> Option<Vec<Moves>> {
+        type State = (Cell, Cell, Cell);
+        let start: State = (first, second, self.cell(self.blank));
+        let inside =
+            |cell: Cell| cell.0 >= low.0 && cell.1 >= low.1 && cell.0 <= high.0 && cell.1 <= high.1;
+        if !inside(first) || !inside(second) || !inside(start.2) {
+            return None;
+        }
+        // the corner is at most 4x4, so a state numbers as three cells counted from 0 to 15
+        let cell_number = |cell: Cell| (cell.0 - low.0) * 4 + cell.1 - low.1;
+        let number = |(first, second, blank): State| {
+            (cell_number(first) * 16 + cell_number(second)) * 16 + cell_number(blank)
+        };
+        let mut came_from: Vec<Option<(State, Moves)>> = vec![None; 16 * 16 * 16];
+        let mut queue = VecDeque::new();
+        came_from[number(start)] = Some((start, Moves::Nothing));
+        queue.push_back(start);
+
+        while let Some(state) = queue.pop_front() {
+            let (first, second, blank) = state;
+            if (first, second) == targets {
+                let mut path = Vec::new();
+                let mut at = state;
+                while at != start {
+                    let (previous, direction) = came_from[number(at)].unwrap();
+                    path.push(direction);
+                    at = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for direction in DIRECTIONS.iter() {
+                let next = match self.step(blank, *direction) {
+                    Some(next) => next,
+                    None => continue,
+                };
+                if !inside(next) || !self.is_free(next) {
+                    continue;
+                }
+
+                let moved = |tile: Cell| if tile == next { blank } else { tile };
+                let following = (moved(first), moved(second), next);
+                if came_from[number(following)].is_none() {
+                    came_from[number(following)] = Some((state, *direction));
+                    queue.push_back(following);
+                }
+            }
+        }
+
+        None
+    }
+
+    // the block left in the bottom right corner, from a table of the way to its goal from every
+    // arrangement of it
+    fn solve_block(&mut self) -> Result<(), String> {
+        self.transposed = false;
+        let block = |tiles: &[u16]| -> EightPuzzle {
+            let mut block = EightPuzzle::solved(self.rows - self.top, self.cols - self.left);
+            let mut cells = block.tiles.iter_mut();
+            for row in self.top..self.rows {
+                for col in self.left..self.cols {
+                    *cells.next().unwrap() = tiles[row * self.cols + col];
+                }
+            }
+            block
+        };
+
+        let goal = block(&self.goal);
+        let table = block_table(&goal);
+        let mut puzzle = block(&self.tiles);
+
+        while puzzle != goal {
+            let direction = *table
+                .get(&puzzle)
+                .ok_or_else(|| "the last corner can't be finished".to_string())?;
+            puzzle = puzzle.apply_move(direction);
+            self.moves.push(direction);
+        }
+
+        Ok(())
+    }
+}
+
+// for every arrangement of a small block that can reach its goal, the first move on the way there
+fn block_table(goal: &EightPuzzle) -> HashMap<EightPuzzle, Moves> {
+    let mut table = HashMap::new();
+    let mut queue = VecDeque::new();
+    table.insert(goal.clone(), Moves::Nothing);
+    queue.push_back(goal.clone());
+
+    while let Some(puzzle) = queue.pop_front() {
+        for direction in DIRECTIONS.iter() {
+            let next = puzzle.apply_move(*direction);
+            if next != puzzle && !table.contains_key(&next) {
+                table.insert(next.clone(), direction.opposite());
+                queue.push_back(next);
+            }
+        }
+    }
+
+    table
+}
+
+// moves that take the board to the goal, found without searching. the goal can be any
+// arrangement of the same tiles
+pub fn solve(problem: &Problem) -> Result<Vec<Moves>, String> {
+    let (initial, goal) = (&problem.initial_state, &problem.goal_state);
+
+    if initial.rows < 2 || initial.cols < 2 {
+        return Err("the board needs at least two rows and two columns".to_string());
+    }
+    if goal.rows != initial.rows || goal.cols != initial.cols {
+        return Err("the goal isn't the shape of the board".to_string());
+    }
+    if walls::has_walls(initial) || torus::wraps(initial) || !problem.colors.is_empty() {
+        return Err("only flat boards without walls or colours can be solved this way".to_string());
+    }
+    if slides::space_count(initial) != 1 || slides::space_count(goal) != 1 {
+        return Err("only boards with one space can be solved this way".to_string());
+    }
+    let mut tiles = initial.tiles.clone();
+    let mut goal_tiles = goal.tiles.clone();
+    tiles.sort_unstable();
+    goal_tiles.sort_unstable();
+    if tiles != goal_tiles || colors::has_interchangeable_tiles(initial) {
+        return Err("the goal doesn't have the same tiles as the board".to_string());
+    }
+    if !problem.is_solvable() {
+        return Err("the goal can't be reached from this board".to_string());
+    }
+
+    // the block at the end has to hold the goal's space, so the goal is solved with its space
+    // taken to the bottom right corner and then the space is walked back
+    let goal_blank = goal.blank_position();
+    let mut walk = vec![Moves::Right; initial.cols - 1 - goal_blank.col];
+    walk.extend(vec![Moves::Down; initial.rows - 1 - goal_blank.row]);
+    let mut cornered = goal.clone();
+    for direction in walk.iter() {
+        cornered = cornered.apply_move(*direction);
+    }
+
+    let mut builder = Builder {
+        rows: initial.rows,
+        cols: initial.cols,
+        tiles: initial.tiles.clone(),
+        goal: cornered.tiles,
+        locked: vec![false; initial.tiles.len()],
+        blank: initial
+            .tiles
+            .iter()
+            .position(|tile| *tile == BLANK)
+            .unwrap(),
+        moves: Vec::new(),
+        top: 0,
+        left: 0,
+        transposed: false,
+        visited: vec![0; initial.tiles.len()],
+        came_from: vec![((0, 0), Moves::Nothing); initial.tiles.len()],
+        stamp: 0,
+    };
+
+    loop {
+        let (rows, cols) = (builder.rows - builder.top, builder.cols - builder.left);
+        if (rows <= 2 && cols <= 3) || (rows <= 3 && cols <= 2) {
+            break;
+        }
+        builder.transposed = cols > rows;
+        builder.solve_line()?;
+    }
+    builder.solve_block()?;
+
+    let mut moves = builder.moves;
+    moves.extend(walk.iter().rev().map(|direction| direction.opposite()));
+    Ok(moves)
+}
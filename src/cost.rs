@@ -0,0 +1,250 @@
+// what moves cost, for puzzles where some tiles are harder to slide than others
+//
+// a move of the space slides one tile the other way, and the cost model prices that move from the
+// tile and the direction the space went:
+// * unit, every move costs one, the usual puzzle
+// * tile_number, sliding a tile costs its number
+// * tile_weights, a cost for some of the tiles, the rest cost one
+// * directions, a cost for each direction the space moves in
+// * custom, any function of the tile and the direction, only from code since it can't be written
+//   into a document
+//
+// in documents they look like
+//     { "type": "tile_weights", "weights": { "1": 5, "8": 2 } }
+//     { "type": "directions", "up": 2, "down": 2, "left": 1, "right": 1 }
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize};
+
+use crate::heuristic::{ComponentCount, Heuristic};
+use crate::slides::{self, Slide};
+use crate::{EightPuzzle, Moves, BLANK, WALL};
+
+const ALL_MOVES: [Moves; 4] = [Moves::Up, Moves::Down, Moves::Left, Moves::Right];
+
+// the function behind a custom cost model, compared by which function it is
+#[derive(Clone)]
+pub struct CostFunction(pub Arc<dyn Fn(u16, Moves) -> u64 + Send + Sync>);
+
+impl fmt::Debug for CostFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CostFunction")
+    }
+}
+
+impl PartialEq for CostFunction {
+    fn eq(&self, other: &CostFunction) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum CostModel {
+    #[default]
+    Unit,
+    TileNumber,
+    TileWeights {
+        #[serde(deserialize_with = "tile_keyed")]
+        weights: BTreeMap<u16, u64>,
+    },
+    // by the direction the space moves, so the tile slides the opposite way
+    Directions {
+        up: u64,
+        down: u64,
+        left: u64,
+        right: u64,
+    },
+    #[serde(skip)]
+    Custom(CostFunction),
+}
+
+// JSON can only have text for keys and YAML writes them as numbers, either is read as a tile
+#[derive(Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(untagged)]
+enum TileKey {
+    Number(u16),
+    Text(String),
+}
+
+pub(crate) fn tile_keyed<'de, D, V>(deserializer: D) -> Result<BTreeMap<u16, V>, D::Error>
+where
+    D: Deserializer<'de>,
+    V: Deserialize<'de>,
+{
+    let mut values = BTreeMap::new();
+
+    for (key, value) in BTreeMap::<TileKey, V>::deserialize(deserializer)? {
+        let tile = match key {
+            TileKey::Number(tile) => tile,
+            TileKey::Text(text) => text
+                .parse()
+                .map_err(|_| de::Error::custom(format!("{} is not a tile", text)))?,
+        };
+        values.insert(tile, value);
+    }
+
+    Ok(values)
+}
+
+impl CostModel {
+    pub fn custom<F>(function: F) -> CostModel
+    where
+        F: Fn(u16, Moves) -> u64 + Send + Sync + 'static,
+    {
+        CostModel::Custom(CostFunction(Arc::new(function)))
+    }
+
+    pub fn is_unit(&self) -> bool {
+        *self == CostModel::Unit
+    }
+
+    // what sliding the tile costs when the space makes the move
+    pub fn move_cost(&self, tile: u16, space_move: Moves) -> u64 {
+        match self {
+            CostModel::Unit => 1,
+            CostModel::TileNumber => tile as u64,
+            CostModel::TileWeights { weights } => *weights.get(&tile).unwrap_or(&1),
+            CostModel::Directions {
+                up,
+                down,
+                left,
+                right,
+            } => match space_move {
+                Moves::Up => *up,
+                Moves::Down => *down,
+                Moves::Left => *left,
+                Moves::Right => *right,
+                Moves::Nothing => 0,
+            },
+            CostModel::Custom(function) => (function.0)(tile, space_move),
+        }
+    }
+
+    // what a slide pushing these tiles costs, the dearest of them since they all go at once
+    pub fn slide_cost(&self, tiles: &[u16], space_move: Moves) -> u64 {
+        tiles
+            .iter()
+            .map(|tile| self.move_cost(*tile, space_move))
+            .max()
+            .unwrap_or(0)
+    }
+
+    // what a path costs from the board it starts on, moves into a wall are free since nothing
+    // slides
+    pub fn path_cost(&self, start: &EightPuzzle, moves: &[Moves]) -> u64 {
+        let mut puzzle = start.clone();
+        let mut cost = 0;
+
+        for space_move in moves {
+            let blank = puzzle.blank_position();
+            let next = puzzle.apply_move(*space_move);
+            if next != puzzle {
+                cost += self.move_cost(next.get(blank.row, blank.col), *space_move);
+            }
+            puzzle = next;
+        }
+
+        cost
+    }
+
+    // the same for a path of slides (see slides.rs), None if one of them can't be made
+    pub fn slides_cost(&self, start: &EightPuzzle, slides: &[Slide]) -> Option<u64> {
+        let mut puzzle = start.clone();
+        let mut cost = 0;
+
+        for slide in slides {
+            cost += self.slide_cost(&slides::slid_tiles(&puzzle, slide), slide.direction);
+            puzzle = slides::apply_slide(&puzzle, slide)?;
+        }
+
+        Some(cost)
+    }
+
+    // the cheapest way each tile of the board can move, which heuristics scale their counts by
+    pub fn cheapest_moves(&self, puzzle: &EightPuzzle) -> CheapestMoves {
+        let tiles: Vec<u16> = puzzle
+            .tiles
+            .iter()
+            .cloned()
+            .filter(|tile| *tile != BLANK && *tile != WALL)
+            .collect();
+        let size = tiles.iter().map(|tile| *tile as usize + 1).max();
+        let mut per_tile = vec![0; size.unwrap_or(0)];
+
+        for tile in tiles.iter() {
+            per_tile[*tile as usize] = ALL_MOVES
+                .iter()
+                .map(|space_move| self.move_cost(*tile, *space_move))
+                .min()
+                .unwrap_or(0);
+        }
+
+        let overall = tiles
+            .iter()
+            .map(|tile| per_tile[*tile as usize])
+            .min()
+            .unwrap_or(0);
+
+        CheapestMoves { per_tile, overall }
+    }
+}
+
+pub struct CheapestMoves {
+    per_tile: Vec<u64>,
+    overall: u64,
+}
+
+impl CheapestMoves {
+    // the least one move of this tile can cost
+    pub fn tile(&self, tile: u16) -> u64 {
+        self.per_tile.get(tile as usize).cloned().unwrap_or(0)
+    }
+
+    // the least any move can cost
+    pub fn overall(&self) -> u64 {
+        self.overall
+    }
+}
+
+// runs a heuristic under a cost model, the search puts this around the one it was given whenever
+// the moves don't all cost one
+pub struct CostWeightedHeuristic<'a> {
+    inner: &'a dyn Heuristic,
+    cheapest: CheapestMoves,
+}
+
+impl<'a> CostWeightedHeuristic<'a> {
+    pub fn new(
+        inner: &'a dyn Heuristic,
+        costs: &CostModel,
+        puzzle: &EightPuzzle,
+    ) -> CostWeightedHeuristic<'a> {
+        CostWeightedHeuristic {
+            inner,
+            cheapest: costs.cheapest_moves(puzzle),
+        }
+    }
+}
+
+impl<'a> Heuristic for CostWeightedHeuristic<'a> {
+    fn estimate(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+        self.inner.weighted_estimate(puzzle, goal, &self.cheapest)
+    }
+
+    fn tiles(&self) -> Option<Vec<u16>> {
+        self.inner.tiles()
+    }
+
+    fn supports(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> bool {
+        self.inner.supports(puzzle, goal)
+    }
+
+    fn take_max_counts(&self) -> Vec<ComponentCount> {
+        self.inner.take_max_counts()
+    }
+}
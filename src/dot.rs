@@ -0,0 +1,253 @@
+// collects the tree a search builds and writes it out as a Graphviz DOT graph
+//
+// every node the search creates is remembered up to the depth cap, and when the graph is written
+// the lowest numbered nodes are kept up to the node cap, so the picture shows the start of the
+// search. nodes on the solution path within the depth cap are always kept and drawn in red, even
+// when the goal itself is deeper than the cap.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::io::BufWriter;
+use std::path::Path;
+
+use crate::colors::{TileClasses, TileColors};
+use crate::trace::{TraceEvent, TraceEventKind};
+use crate::{EightPuzzle, Moves, BLANK, WALL};
+
+pub const DEFAULT_MAX_NODES: usize = 200;
+pub const DEFAULT_MAX_DEPTH: u64 = 10;
+
+#[derive(Clone, Copy, PartialEq)]
+enum TreeNodeState {
+    // still waiting in the queue when the search stopped
+    Generated,
+    // had its children generated
+    Expanded,
+    // thrown away because its board was already expanded
+    Pruned,
+}
+
+struct TreeNode {
+    parent: Option<u64>,
+    puzzle: EightPuzzle,
+    g: u64,
+    h: u64,
+    prev_move: Moves,
+    state: TreeNodeState,
+}
+
+// how many of the nodes the search made aren't in the graph
+pub struct LeftOut {
+    // within the depth cap but past the node cap
+    pub omitted: usize,
+    // below the depth cap
+    pub deeper: usize,
+}
+
+pub struct SearchTreeExport {
+    // the goal in colours, boards are matched against it the way the search does
+    goal: EightPuzzle,
+    classes: TileClasses,
+    max_nodes: usize,
+    max_depth: u64,
+    nodes: BTreeMap<u64, TreeNode>,
+    // the parents of the nodes below the depth cap, to walk back from a goal down there
+    deeper: HashMap<u64, Option<u64>>,
+    solution: Option<u64>,
+}
+
+impl SearchTreeExport {
+    pub fn new(goal: EightPuzzle, max_nodes: usize, max_depth: u64) -> SearchTreeExport {
+        SearchTreeExport {
+            goal,
+            classes: TileColors::default().classes(),
+            max_nodes,
+            max_depth,
+            nodes: BTreeMap::new(),
+            deeper: HashMap::new(),
+            solution: None,
+        }
+    }
+
+    // the same export for a search whose goal only constrains the colours of the tiles
+    pub fn with_colors(mut self, colors: &TileColors) -> SearchTreeExport {
+        self.classes = colors.classes();
+        self.goal = self.classes.canonical(&self.goal);
+        self
+    }
+
+    pub fn record(&mut self, event: &TraceEvent) {
+        // the goal can be deeper than the cap, the part of its path above the cap is still drawn
+        if event.kind == TraceEventKind::Pop
+            && self.solution.is_none()
+            && self.classes.canonical(&event.puzzle) == self.goal
+        {
+            self.solution = Some(event.id);
+        }
+
+        if event.depth > self.max_depth {
+            if event.kind == TraceEventKind::Generate || event.kind == TraceEventKind::Prune {
+                self.deeper.insert(event.id, event.parent);
+            }
+            return;
+        }
+
+        match event.kind {
+            TraceEventKind::Generate => {
+                self.nodes.insert(
+                    event.id,
+                    TreeNode {
+                        parent: event.parent,
+                        puzzle: event.puzzle.clone(),
+                        g: event.g,
+                        h: event.h,
+                        prev_move: event.prev_move,
+                        state: TreeNodeState::Generated,
+                    },
+                );
+            }
+            TraceEventKind::Expand => {
+                if let Some(node) = self.nodes.get_mut(&event.id) {
+                    node.state = TreeNodeState::Expanded;
+                }
+            }
+            TraceEventKind::Prune => {
+                // a child pruned on creation was never generated, so it's added here
+                let node = self.nodes.entry(event.id).or_insert(TreeNode {
+                    parent: event.parent,
+                    puzzle: event.puzzle.clone(),
+                    g: event.g,
+                    h: event.h,
+                    prev_move: event.prev_move,
+                    state: TreeNodeState::Pruned,
+                });
+                node.state = TreeNodeState::Pruned;
+            }
+            TraceEventKind::Pop => {}
+        }
+    }
+
+    // walks the parent links back from the goal node, keeping the nodes within the depth cap
+    fn solution_path(&self) -> HashSet<u64> {
+        let mut path = HashSet::new();
+        let mut current = self.solution;
+
+        while let Some(id) = current {
+            current = match self.nodes.get(&id) {
+                Some(node) => {
+                    path.insert(id);
+                    node.parent
+                }
+                None => self.deeper.get(&id).cloned().flatten(),
+            };
+        }
+
+        path
+    }
+
+    // writes the graph and gives back how many nodes had to be left out of it
+    pub fn write(&self, path: &Path) -> io::Result<LeftOut> {
+        let solution_path = self.solution_path();
+
+        let mut kept: Vec<u64> = self
+            .nodes
+            .keys()
+            .filter(|id| !solution_path.contains(id))
+            .take(self.max_nodes.saturating_sub(solution_path.len()))
+            .cloned()
+            .collect();
+        kept.extend(solution_path.iter().cloned());
+        kept.sort_unstable();
+        let kept_set: HashSet<u64> = kept.iter().cloned().collect();
+
+        let mut out = BufWriter::new(File::create(path)?);
+
+        writeln!(out, "digraph search {{")?;
+        writeln!(out, "\tnode [fontname=\"monospace\"];")?;
+
+        for id in kept.iter() {
+            let node = &self.nodes[id];
+
+            let style = match node.state {
+                TreeNodeState::Expanded => "shape=box",
+                TreeNodeState::Generated => "shape=box, style=dashed",
+                TreeNodeState::Pruned => "shape=box, style=dotted, fontcolor=gray",
+            };
+            let highlight = if solution_path.contains(id) {
+                ", color=red, penwidth=2"
+            } else {
+                ""
+            };
+
+            writeln!(
+                out,
+                "\tn{} [label=\"{}\\ng = {}, h = {}\", {}{}];",
+                id,
+                board_label(&node.puzzle),
+                node.g,
+                node.h,
+                style,
+                highlight
+            )?;
+        }
+
+        for id in kept.iter() {
+            let node = &self.nodes[id];
+
+            if let Some(parent) = node.parent {
+                if !kept_set.contains(&parent) {
+                    continue;
+                }
+
+                let highlight = if solution_path.contains(id) {
+                    ", color=red, penwidth=2"
+                } else {
+                    ""
+                };
+                writeln!(
+                    out,
+                    "\tn{} -> n{} [label=\"{}\"{}];",
+                    parent,
+                    id,
+                    node.prev_move.name(),
+                    highlight
+                )?;
+            }
+        }
+
+        writeln!(out, "}}")?;
+        out.flush()?;
+
+        Ok(LeftOut {
+            omitted: self.nodes.len() - kept.len(),
+            deeper: self.deeper.len(),
+        })
+    }
+}
+
+// the board one line per row, with a * for the space like print_puzzle
+fn board_label(puzzle: &EightPuzzle) -> String {
+    let rows: Vec<String> = puzzle
+        .tiles
+        .chunks(puzzle.cols)
+        .map(|row| {
+            let tiles: Vec<String> = row
+                .iter()
+                .map(|tile| {
+                    if *tile == BLANK {
+                        "*".to_string()
+                    } else if *tile == WALL {
+                        "#".to_string()
+                    } else {
+                        tile.to_string()
+                    }
+                })
+                .collect();
+            tiles.join(" ")
+        })
+        .collect();
+
+    rows.join("\\n")
+}
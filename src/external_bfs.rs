@@ -0,0 +1,397 @@
+// a breadth first search over every board that can reach a goal, kept on disk instead of in memory
+//
+// the search goes a layer at a time, a layer being every board at one depth from the goal. each
+// layer is a file of the boards packed into a u64 each, four bits a cell, sorted and without
+// repeats. the next layer is made by expanding the last one into runs, each sorted in memory up to
+// a buffer's worth of boards and written out, then merging the runs and dropping the boards that
+// are already in the last two layers on the way. duplicates are only found once the whole layer is
+// made (delayed duplicate detection), so memory holds the buffer and not the boards seen. a move
+// undone only leads back one layer, so the two layers before are all it needs to look at, and the
+// older ones are deleted unless they're asked to be kept.
+//
+// after every layer the number of boards in each is saved in progress.json next to the layers:
+//     {
+//       "version": 1,
+//       "goal": [[1, 2, 3], [4, 5, 6], [7, 8, 0]],
+//       "layers": [1, 2, 4, 8, 16, ...],
+//       "finished": false
+//     }
+// running again in the same directory carries on from the last layer saved, so a search that's
+// stopped, or told to stop after some layers, loses at most the layer it was on. boards can have
+// at most 16 cells and tiles up to 15, enough for the 15-puzzle, 3x4 and 2x6, as long as the disk
+// is big enough.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io;
+use std::io::prelude::*;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{self, DocumentFormat, SchemaVersion};
+use crate::{slides, torus, walls, EightPuzzle, BLANK};
+
+const PROGRESS_FILE: &str = "progress.json";
+
+#[derive(Clone, Debug)]
+pub struct ExternalBfsOptions {
+    pub directory: PathBuf,
+    // how many boards are sorted in memory before they're written out as a run
+    pub buffer_boards: usize,
+    // how many layers to make before stopping, None to go on until the search is over
+    pub max_layers: Option<usize>,
+    // keeps every layer file instead of just the last two
+    pub keep_layers: bool,
+}
+
+impl Default for ExternalBfsOptions {
+    fn default() -> ExternalBfsOptions {
+        ExternalBfsOptions {
+            directory: PathBuf::from("bfs"),
+            buffer_boards: 1 << 22,
+            max_layers: None,
+            keep_layers: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BfsProgress {
+    pub version: SchemaVersion,
+    pub goal: EightPuzzle,
+    // how many boards are at each depth, for every layer made so far
+    pub layers: Vec<u64>,
+    // whether the last layer was expanded and nothing new came of it
+    pub finished: bool,
+}
+
+impl BfsProgress {
+    pub fn boards(&self) -> u64 {
+        self.layers.iter().sum()
+    }
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn check_board(goal: &EightPuzzle) -> io::Result<()> {
+    if walls::has_walls(goal) || torus::wraps(goal) {
+        return Err(invalid_input(
+            "only flat boards without walls can be searched on disk".to_string(),
+        ));
+    }
+    if slides::space_count(goal) != 1 {
+        return Err(invalid_input(
+            "only boards with one space can be searched on disk".to_string(),
+        ));
+    }
+    if goal.tiles.len() > 16 || goal.tiles.iter().any(|tile| *tile > 15) {
+        return Err(invalid_input(
+            "boards searched on disk can have at most 16 cells and tiles up to 15".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn pack(tiles: &[u16]) -> u64 {
+    tiles.iter().enumerate().fold(0, |packed, (cell, tile)| {
+        packed | (*tile as u64) << (4 * cell)
+    })
+}
+
+fn tile_at(packed: u64, cell: usize) -> u64 {
+    (packed >> (4 * cell)) & 0xf
+}
+
+// every board one move away, written into children
+fn expand(packed: u64, rows: usize, cols: usize, children: &mut Vec<u64>) {
+    let blank = (0..rows * cols)
+        .find(|cell| tile_at(packed, *cell) == BLANK as u64)
+        .expect("every board has a space");
+    let (row, col) = (blank / cols, blank % cols);
+
+    let mut slide_from = |cell: usize| {
+        let tile = tile_at(packed, cell);
+        children.push(packed & !(0xf << (4 * cell)) | tile << (4 * blank));
+    };
+    if row > 0 {
+        slide_from(blank - cols);
+    }
+    if row + 1 < rows {
+        slide_from(blank + cols);
+    }
+    if col > 0 {
+        slide_from(blank - 1);
+    }
+    if col + 1 < cols {
+        slide_from(blank + 1);
+    }
+}
+
+// the boards of a layer or run file, in the order they were written
+struct BoardReader {
+    input: BufReader<File>,
+}
+
+impl BoardReader {
+    fn open(path: &Path) -> io::Result<BoardReader> {
+        Ok(BoardReader {
+            input: BufReader::new(File::open(path)?),
+        })
+    }
+
+    // like read_exact, but the end of the file between boards is None instead of an error
+    fn next_board(&mut self) -> io::Result<Option<u64>> {
+        let mut bytes = [0; 8];
+        let mut read = 0;
+        while read < bytes.len() {
+            match self.input.read(&mut bytes[read..])? {
+                0 if read == 0 => return Ok(None),
+                0 => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+                count => read += count,
+            }
+        }
+        Ok(Some(u64::from_le_bytes(bytes)))
+    }
+}
+
+// a layer that may not exist, which reads as having no boards
+struct SortedBoards {
+    reader: Option<BoardReader>,
+    next: Option<u64>,
+}
+
+impl SortedBoards {
+    fn open(path: Option<PathBuf>) -> io::Result<SortedBoards> {
+        let mut reader = match path {
+            Some(path) => Some(BoardReader::open(&path)?),
+            None => None,
+        };
+        let next = match &mut reader {
+            Some(reader) => reader.next_board()?,
+            None => None,
+        };
+        Ok(SortedBoards { reader, next })
+    }
+
+    // whether the board is in the file, the boards asked about have to come in order
+    fn contains(&mut self, board: u64) -> io::Result<bool> {
+        while let Some(next) = self.next {
+            if next >= board {
+                return Ok(next == board);
+            }
+            self.next = match &mut self.reader {
+                Some(reader) => reader.next_board()?,
+                None => None,
+            };
+        }
+        Ok(false)
+    }
+}
+
+fn write_boards(path: &Path, boards: &[u64]) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    for board in boards {
+        out.write_all(&board.to_le_bytes())?;
+    }
+    out.flush()
+}
+
+fn layer_path(directory: &Path, depth: usize) -> PathBuf {
+    directory.join(format!("layer-{}.bin", depth))
+}
+
+fn run_path(directory: &Path, run: usize) -> PathBuf {
+    directory.join(format!("run-{}.bin", run))
+}
+
+// written next to the old file and renamed over it, so a stop halfway never leaves half a file
+fn save_progress(directory: &Path, progress: &BfsProgress) -> io::Result<()> {
+    let path = directory.join(PROGRESS_FILE);
+    let partial = directory.join(format!("{}.partial", PROGRESS_FILE));
+    fs::write(&partial, schema::to_string(progress, DocumentFormat::Json)?)?;
+    fs::rename(&partial, &path)
+}
+
+// the progress saved in a directory, None when nothing was started there
+pub fn read_progress(directory: &Path) -> io::Result<Option<BfsProgress>> {
+    let path = directory.join(PROGRESS_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    schema::read_document(&path).map(Some)
+}
+
+// what a stop left behind, the runs and half written layers, and the layers no longer needed
+fn clean_up(directory: &Path, depth: usize, keep_layers: bool) -> io::Result<()> {
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+
+        let layer = name
+            .strip_prefix("layer-")
+            .and_then(|rest| rest.strip_suffix(".bin"))
+            .and_then(|number| number.parse::<usize>().ok());
+        let stale = match layer {
+            Some(layer) => layer > depth || (!keep_layers && layer + 1 < depth),
+            None => name.starts_with("run-") || name.ends_with(".partial"),
+        };
+        if stale {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+// makes the layer after depth from the two before it, giving back how many boards it has
+fn next_layer(
+    directory: &Path,
+    goal: &EightPuzzle,
+    depth: usize,
+    buffer_boards: usize,
+) -> io::Result<u64> {
+    // the children of the layer, in sorted runs
+    let mut runs = 0;
+    let mut buffer = Vec::with_capacity(buffer_boards + 4);
+    let flush = |buffer: &mut Vec<u64>, runs: &mut usize| -> io::Result<()> {
+        buffer.sort_unstable();
+        buffer.dedup();
+        write_boards(&run_path(directory, *runs), buffer)?;
+        buffer.clear();
+        *runs += 1;
+        Ok(())
+    };
+
+    let mut layer = BoardReader::open(&layer_path(directory, depth))?;
+    while let Some(board) = layer.next_board()? {
+        expand(board, goal.rows, goal.cols, &mut buffer);
+        if buffer.len() >= buffer_boards {
+            flush(&mut buffer, &mut runs)?;
+        }
+    }
+    if !buffer.is_empty() {
+        flush(&mut buffer, &mut runs)?;
+    }
+    drop(buffer);
+
+    // merged in order, skipping repeats and the boards of the layer itself and the one before
+    let mut readers = Vec::new();
+    let mut heap = BinaryHeap::new();
+    for run in 0..runs {
+        let mut reader = BoardReader::open(&run_path(directory, run))?;
+        if let Some(board) = reader.next_board()? {
+            heap.push(Reverse((board, run)));
+        }
+        readers.push(reader);
+    }
+    let mut current = SortedBoards::open(Some(layer_path(directory, depth)))?;
+    let mut previous = SortedBoards::open(
+        depth
+            .checked_sub(1)
+            .map(|depth| layer_path(directory, depth)),
+    )?;
+
+    let path = layer_path(directory, depth + 1);
+    let partial = path.with_extension("bin.partial");
+    let mut out = BufWriter::new(File::create(&partial)?);
+    let mut last = None;
+    let mut count = 0;
+    while let Some(Reverse((board, run))) = heap.pop() {
+        if let Some(next) = readers[run].next_board()? {
+            heap.push(Reverse((next, run)));
+        }
+        if last == Some(board) {
+            continue;
+        }
+        last = Some(board);
+
+        if !current.contains(board)? && !previous.contains(board)? {
+            out.write_all(&board.to_le_bytes())?;
+            count += 1;
+        }
+    }
+    out.flush()?;
+    drop(out);
+
+    fs::rename(&partial, &path)?;
+    for run in 0..runs {
+        fs::remove_file(run_path(directory, run))?;
+    }
+    Ok(count)
+}
+
+// searches out from the goal, or carries on with the search saved in the directory
+pub fn run(goal: &EightPuzzle, options: &ExternalBfsOptions) -> io::Result<BfsProgress> {
+    check_board(goal)?;
+    let directory = &options.directory;
+    fs::create_dir_all(directory)?;
+
+    let mut progress = match read_progress(directory)? {
+        Some(progress) if progress.goal != *goal => {
+            return Err(invalid_input(format!(
+                "{} holds a search from {}, not {}",
+                directory.display(),
+                progress.goal,
+                goal
+            )))
+        }
+        Some(progress) => progress,
+        None => {
+            clean_up(directory, 0, options.keep_layers)?;
+            write_boards(&layer_path(directory, 0), &[pack(&goal.tiles)])?;
+            let progress = BfsProgress {
+                version: SchemaVersion,
+                goal: goal.clone(),
+                layers: vec![1],
+                finished: false,
+            };
+            save_progress(directory, &progress)?;
+            progress
+        }
+    };
+
+    let mut made = 0;
+    while !progress.finished && options.max_layers.is_none_or(|max| made < max) {
+        let depth = progress.layers.len() - 1;
+        clean_up(directory, depth, options.keep_layers)?;
+
+        let count = next_layer(directory, goal, depth, options.buffer_boards.max(1))?;
+        if count == 0 {
+            fs::remove_file(layer_path(directory, depth + 1))?;
+            progress.finished = true;
+        } else {
+            progress.layers.push(count);
+        }
+        save_progress(directory, &progress)?;
+        made += 1;
+    }
+
+    clean_up(directory, progress.layers.len() - 1, options.keep_layers)?;
+    Ok(progress)
+}
+
+// the boards of a layer that's still on disk, for looking at the farthest ones
+pub fn layer_boards(
+    goal: &EightPuzzle,
+    directory: &Path,
+    depth: usize,
+) -> io::Result<Vec<EightPuzzle>> {
+    let mut reader = BoardReader::open(&layer_path(directory, depth))?;
+    let mut boards = Vec::new();
+    while let Some(packed) = reader.next_board()? {
+        let mut board = goal.clone();
+        for (cell, tile) in board.tiles.iter_mut().enumerate() {
+            *tile = tile_at(packed, cell) as u16;
+        }
+        boards.push(board);
+    }
+    Ok(boards)
+}
@@ -0,0 +1,362 @@
+// the C interface to the solver, built into the cdylib
+//
+// the header in include/ai_homework.h is generated from this file by ffi_header.rs, so every item
+// here has to stay in the simple shapes it understands: constants, opaque structs, #[repr(C)] structs
+// with plain fields, and extern "C" functions whose comments become the header's comments.
+//
+// the rules for callers are the usual ones for C: pointers passed in must be null or point at what
+// the function expects, and everything handed out by a *_new or puzzle_solve must be given back to
+// the matching *_free exactly once. when a function fails puzzle_last_error says why.
+#![allow(clippy::missing_safety_doc)]
+
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use crate::notation::moves_to_string;
+use crate::parse::parse_puzzle_rows;
+use crate::slides;
+use crate::{
+    heuristic_from_name, search_with_options, Algorithm, EightPuzzle, Moves, Problem, SearchLimits,
+    SearchOutcome, StopReason, PRINT_EXPANDING_NODES_TOGGLE,
+};
+
+// bumped whenever a function or struct in this file changes in a way old callers would notice
+pub const PUZZLE_ABI_VERSION: u32 = 1;
+
+pub const PUZZLE_ALGORITHM_ASTAR: u32 = 0;
+pub const PUZZLE_ALGORITHM_UCS: u32 = 1;
+pub const PUZZLE_ALGORITHM_GREEDY: u32 = 2;
+
+pub const PUZZLE_HEURISTIC_UCS: u32 = 0;
+pub const PUZZLE_HEURISTIC_MISPLACED: u32 = 1;
+pub const PUZZLE_HEURISTIC_MANHATTAN: u32 = 2;
+pub const PUZZLE_HEURISTIC_LINEAR_CONFLICT: u32 = 3;
+pub const PUZZLE_HEURISTIC_WALKING: u32 = 4;
+pub const PUZZLE_HEURISTIC_WALKING_LINEAR_CONFLICT: u32 = 5;
+pub const PUZZLE_HEURISTIC_GASCHNIG: u32 = 6;
+pub const PUZZLE_HEURISTIC_INVERSION_DISTANCE: u32 = 7;
+
+pub const PUZZLE_STATUS_SOLVED: u32 = 0;
+pub const PUZZLE_STATUS_UNSOLVABLE: u32 = 1;
+pub const PUZZLE_STATUS_EXPANSION_LIMIT: u32 = 2;
+pub const PUZZLE_STATUS_TIME_LIMIT: u32 = 3;
+
+// moves of the space, as written by puzzle_result_moves
+pub const PUZZLE_MOVE_UP: u8 = 0;
+pub const PUZZLE_MOVE_DOWN: u8 = 1;
+pub const PUZZLE_MOVE_LEFT: u8 = 2;
+pub const PUZZLE_MOVE_RIGHT: u8 = 3;
+
+// a board, made by puzzle_board_new
+pub struct PuzzleBoard {
+    puzzle: EightPuzzle,
+}
+
+// what a search found, made by puzzle_solve
+pub struct PuzzleResult {
+    status: u32,
+    moves: Vec<Moves>,
+    move_string: CString,
+    stats: PuzzleStats,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct PuzzleStats {
+    pub expanded: u64,
+    pub max_queue: u64,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(message: String) {
+    // messages come from our own formatting, but a stray nul would make CString refuse them
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = message);
+}
+
+fn clear_last_error() {
+    set_last_error(String::new());
+}
+
+fn move_code(space_move: Moves) -> u8 {
+    match space_move {
+        Moves::Up => PUZZLE_MOVE_UP,
+        Moves::Down => PUZZLE_MOVE_DOWN,
+        Moves::Left => PUZZLE_MOVE_LEFT,
+        Moves::Right => PUZZLE_MOVE_RIGHT,
+        Moves::Nothing => u8::MAX,
+    }
+}
+
+// the goal given, or the usual one for the size of the initial board when it's null
+unsafe fn problem_from_boards(
+    initial: *const PuzzleBoard,
+    goal: *const PuzzleBoard,
+) -> Option<Problem> {
+    let initial = match initial.as_ref() {
+        Some(board) => board.puzzle.clone(),
+        None => {
+            set_last_error("the initial board is null".to_string());
+            return None;
+        }
+    };
+
+    let goal = match goal.as_ref() {
+        Some(board) if board.puzzle.rows != initial.rows || board.puzzle.cols != initial.cols => {
+            set_last_error(format!(
+                "the goal is {}x{} but the initial board is {}x{}",
+                board.puzzle.rows, board.puzzle.cols, initial.rows, initial.cols
+            ));
+            return None;
+        }
+        Some(board) => board.puzzle.clone(),
+        None => EightPuzzle::solved_like(&initial),
+    };
+
+    // the moves handed back are only directions, which can't say which of several spaces moved
+    if slides::space_count(&initial) > 1 {
+        set_last_error("boards with more than one space aren't supported".to_string());
+        return None;
+    }
+
+    Some(Problem::new(initial, goal))
+}
+
+// the PUZZLE_ABI_VERSION the library was built with, to check against the header's
+#[no_mangle]
+pub extern "C" fn puzzle_abi_version() -> u32 {
+    PUZZLE_ABI_VERSION
+}
+
+// why the last call on this thread failed, empty if it didn't
+// the text belongs to the library and is only good until the next call
+#[no_mangle]
+pub extern "C" fn puzzle_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| last_error.borrow().as_ptr())
+}
+
+// makes a board from rows * cols tiles in row order, 0 is the space
+// returns null if the tiles don't make a valid board
+#[no_mangle]
+pub unsafe extern "C" fn puzzle_board_new(
+    tiles: *const u16,
+    rows: usize,
+    cols: usize,
+) -> *mut PuzzleBoard {
+    clear_last_error();
+
+    if tiles.is_null() {
+        set_last_error("the tiles are null".to_string());
+        return ptr::null_mut();
+    }
+
+    let count = match rows.checked_mul(cols) {
+        Some(count) => count,
+        None => {
+            set_last_error(format!("a {}x{} board is too big", rows, cols));
+            return ptr::null_mut();
+        }
+    };
+
+    // the same checks as typed puzzles, so C callers get the same messages
+    let tiles = slice::from_raw_parts(tiles, count);
+    let lines: Vec<String> = tiles
+        .chunks(cols.max(1))
+        .map(|row| {
+            let tiles: Vec<String> = row.iter().map(|tile| tile.to_string()).collect();
+            tiles.join(" ")
+        })
+        .collect();
+    let lines: Vec<&str> = lines.iter().map(|line| line.as_str()).collect();
+
+    match parse_puzzle_rows(&lines, rows, cols) {
+        Ok(puzzle) => Box::into_raw(Box::new(PuzzleBoard { puzzle })),
+        Err(errors) => {
+            set_last_error(errors.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn puzzle_board_free(board: *mut PuzzleBoard) {
+    if !board.is_null() {
+        drop(Box::from_raw(board));
+    }
+}
+
+// 1 if the goal can be reached from the initial board, 0 if not, -1 on bad arguments
+// a null goal means the usual one, tiles counting up with the space in the bottom right corner
+#[no_mangle]
+pub unsafe extern "C" fn puzzle_is_solvable(
+    initial: *const PuzzleBoard,
+    goal: *const PuzzleBoard,
+) -> i32 {
+    clear_last_error();
+
+    match problem_from_boards(initial, goal) {
+        Some(problem) => problem.is_solvable() as i32,
+        None => -1,
+    }
+}
+
+// searches from the initial board to the goal, a null goal means the usual one
+// algorithm and heuristic are PUZZLE_ALGORITHM_* and PUZZLE_HEURISTIC_* values, a limit of 0 means
+// no limit. returns null on bad arguments, otherwise a result to read and then free
+#[no_mangle]
+pub unsafe extern "C" fn puzzle_solve(
+    initial: *const PuzzleBoard,
+    goal: *const PuzzleBoard,
+    algorithm: u32,
+    heuristic: u32,
+    max_expansions: u64,
+    timeout_ms: u64,
+) -> *mut PuzzleResult {
+    clear_last_error();
+
+    let problem = match problem_from_boards(initial, goal) {
+        Some(problem) => problem,
+        None => return ptr::null_mut(),
+    };
+
+    // a library has no business printing every expansion to the caller's stdout
+    PRINT_EXPANDING_NODES_TOGGLE.store(false, Ordering::Relaxed);
+
+    let algorithm = match algorithm {
+        PUZZLE_ALGORITHM_ASTAR => Algorithm::AStar,
+        PUZZLE_ALGORITHM_UCS => Algorithm::UniformCost,
+        PUZZLE_ALGORITHM_GREEDY => Algorithm::Greedy,
+        other => {
+            set_last_error(format!("unknown algorithm {}", other));
+            return ptr::null_mut();
+        }
+    };
+
+    let name = match heuristic {
+        PUZZLE_HEURISTIC_UCS => "ucs",
+        PUZZLE_HEURISTIC_MISPLACED => "misplaced",
+        PUZZLE_HEURISTIC_MANHATTAN => "manhattan",
+        PUZZLE_HEURISTIC_LINEAR_CONFLICT => "linear-conflict",
+        PUZZLE_HEURISTIC_WALKING => "walking",
+        PUZZLE_HEURISTIC_WALKING_LINEAR_CONFLICT => "walking-linear-conflict",
+        PUZZLE_HEURISTIC_GASCHNIG => "gaschnig",
+        PUZZLE_HEURISTIC_INVERSION_DISTANCE => "inversion-distance",
+        other => {
+            set_last_error(format!("unknown heuristic {}", other));
+            return ptr::null_mut();
+        }
+    };
+    let heuristic = heuristic_from_name(name).expect("every heuristic constant has a name");
+
+    let limits = SearchLimits {
+        max_expansions: if max_expansions == 0 {
+            None
+        } else {
+            Some(max_expansions)
+        },
+        deadline: if timeout_ms == 0 {
+            None
+        } else {
+            Some(Instant::now() + Duration::from_millis(timeout_ms))
+        },
+        ..SearchLimits::default()
+    };
+
+    let (outcome, stats) = search_with_options(problem, heuristic, algorithm, limits);
+    let (status, moves) = match outcome {
+        SearchOutcome::Solved(solution) => (PUZZLE_STATUS_SOLVED, solution.moves),
+        SearchOutcome::Unsolvable => (PUZZLE_STATUS_UNSOLVABLE, Vec::new()),
+        SearchOutcome::Stopped(StopReason::ExpansionLimit) => {
+            (PUZZLE_STATUS_EXPANSION_LIMIT, Vec::new())
+        }
+        SearchOutcome::Stopped(StopReason::TimeLimit) => (PUZZLE_STATUS_TIME_LIMIT, Vec::new()),
+    };
+
+    let move_string = CString::new(moves_to_string(&moves)).expect("move letters are never nul");
+    Box::into_raw(Box::new(PuzzleResult {
+        status,
+        moves,
+        move_string,
+        stats: PuzzleStats {
+            expanded: stats.expanded,
+            max_queue: stats.max_queue as u64,
+        },
+    }))
+}
+
+// one of the PUZZLE_STATUS_* values
+#[no_mangle]
+pub unsafe extern "C" fn puzzle_result_status(result: *const PuzzleResult) -> u32 {
+    match result.as_ref() {
+        Some(result) => result.status,
+        None => PUZZLE_STATUS_UNSOLVABLE,
+    }
+}
+
+// how many moves the solution takes, 0 unless the status is PUZZLE_STATUS_SOLVED
+#[no_mangle]
+pub unsafe extern "C" fn puzzle_result_move_count(result: *const PuzzleResult) -> usize {
+    match result.as_ref() {
+        Some(result) => result.moves.len(),
+        None => 0,
+    }
+}
+
+// copies up to capacity PUZZLE_MOVE_* values into moves and returns how many were copied
+#[no_mangle]
+pub unsafe extern "C" fn puzzle_result_moves(
+    result: *const PuzzleResult,
+    moves: *mut u8,
+    capacity: usize,
+) -> usize {
+    let result = match result.as_ref() {
+        Some(result) => result,
+        None => return 0,
+    };
+    if moves.is_null() {
+        return 0;
+    }
+
+    let count = result.moves.len().min(capacity);
+    let out = slice::from_raw_parts_mut(moves, count);
+    for (slot, space_move) in out.iter_mut().zip(result.moves.iter()) {
+        *slot = move_code(*space_move);
+    }
+
+    count
+}
+
+// the solution in move notation, like "RRDD", owned by the result
+#[no_mangle]
+pub unsafe extern "C" fn puzzle_result_move_string(result: *const PuzzleResult) -> *const c_char {
+    match result.as_ref() {
+        Some(result) => result.move_string.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn puzzle_result_stats(result: *const PuzzleResult) -> PuzzleStats {
+    match result.as_ref() {
+        Some(result) => result.stats,
+        None => PuzzleStats {
+            expanded: 0,
+            max_queue: 0,
+        },
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn puzzle_result_free(result: *mut PuzzleResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
@@ -0,0 +1,207 @@
+// writes the C header for ffi.rs by reading its source, so the header can't drift from the code
+//
+// only the shapes ffi.rs sticks to are understood:
+// * pub const NAME: type = value;                  becomes a #define
+// * pub struct Name { private fields }             becomes an opaque typedef
+// * #[repr(C)] pub struct Name { pub fields }      becomes a struct typedef
+// * pub (unsafe) extern "C" fn name(args) -> type  becomes a prototype
+// the // comments right above an item are carried over, everything else is skipped.
+//
+// run `ai_homework ffi-header > include/ai_homework.h` after changing ffi.rs
+
+const FFI_SOURCE: &str = include_str!("ffi.rs");
+
+const PREAMBLE: &str = "\
+/* generated by `ai_homework ffi-header` from src/ffi.rs, don't edit by hand */
+
+#ifndef AI_HOMEWORK_H
+#define AI_HOMEWORK_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+";
+
+const POSTAMBLE: &str = "\
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* AI_HOMEWORK_H */
+";
+
+fn c_type(rust_type: &str) -> String {
+    let rust_type = rust_type.trim();
+
+    if let Some(pointee) = rust_type.strip_prefix("*const ") {
+        return format!("const {} *", c_type(pointee));
+    }
+    if let Some(pointee) = rust_type.strip_prefix("*mut ") {
+        return format!("{} *", c_type(pointee));
+    }
+
+    match rust_type {
+        "u8" => "uint8_t",
+        "u16" => "uint16_t",
+        "u32" => "uint32_t",
+        "u64" => "uint64_t",
+        "i32" => "int32_t",
+        "i64" => "int64_t",
+        "usize" => "size_t",
+        "c_char" => "char",
+        "" | "()" => "void",
+        // anything else is one of our own structs, which are all typedef'd
+        other => other,
+    }
+    .to_string()
+}
+
+// "name: type" to "type name", keeping pointer stars next to the name
+fn c_declaration(field: &str) -> String {
+    let mut parts = field.splitn(2, ':');
+    let name = parts.next().unwrap_or("").trim();
+    let rust_type = parts.next().unwrap_or("").trim();
+
+    let c_type = c_type(rust_type);
+    if c_type.ends_with('*') {
+        format!("{}{}", c_type, name)
+    } else {
+        format!("{} {}", c_type, name)
+    }
+}
+
+fn c_comment(comments: &[&str], out: &mut String) {
+    for comment in comments {
+        let text = comment.trim_start_matches("//").trim();
+        if !text.is_empty() {
+            out.push_str(&format!("/* {} */\n", text));
+        }
+    }
+}
+
+// a prototype from a function signature, which may run over several lines
+fn c_prototype(signature: &str) -> String {
+    let name_start = signature.find("fn ").expect("a function has fn") + 3;
+    let open = signature.find('(').expect("a function has arguments");
+    let close = signature.rfind(')').expect("a function has arguments");
+
+    let name = signature[name_start..open].trim();
+    let arguments: Vec<String> = signature[open + 1..close]
+        .split(',')
+        .map(|argument| argument.trim())
+        .filter(|argument| !argument.is_empty())
+        .map(c_declaration)
+        .collect();
+    let arguments = if arguments.is_empty() {
+        "void".to_string()
+    } else {
+        arguments.join(", ")
+    };
+
+    let returns = match signature[close..].find("->") {
+        Some(arrow) => signature[close + arrow + 2..].trim_end_matches('{').trim(),
+        None => "",
+    };
+    let returns = c_type(returns);
+
+    if returns.ends_with('*') {
+        format!("{}{}({});\n", returns, name, arguments)
+    } else {
+        format!("{} {}({});\n", returns, name, arguments)
+    }
+}
+
+pub fn generate() -> String {
+    let mut out = String::from(PREAMBLE);
+    let mut comments: Vec<&str> = Vec::new();
+    let mut repr_c = false;
+    let mut previous_const = false;
+    let mut lines = FFI_SOURCE.lines();
+
+    // the module comment at the top is for Rust readers, skip to the first item
+    for line in lines.by_ref() {
+        if line.starts_with("use ") {
+            break;
+        }
+    }
+
+    while let Some(line) = lines.next() {
+        if line.starts_with("//") {
+            comments.push(line);
+            continue;
+        }
+
+        if line.starts_with("#[repr(C)]") {
+            repr_c = true;
+            continue;
+        }
+        if line.starts_with("#[") {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("pub const ") {
+            let mut parts = rest.trim_end_matches(';').splitn(2, '=');
+            let name = parts.next().unwrap_or("").split(':').next().unwrap_or("");
+            let value = parts.next().unwrap_or("").trim();
+
+            // runs of constants stay together unless a comment starts a new group
+            if !previous_const || !comments.is_empty() {
+                out.push('\n');
+            }
+            c_comment(&comments, &mut out);
+            out.push_str(&format!("#define {} {}\n", name.trim(), value));
+            comments.clear();
+            previous_const = true;
+            continue;
+        } else if let Some(rest) = line.strip_prefix("pub struct ") {
+            let name = rest.trim_end_matches('{').trim();
+            out.push('\n');
+            c_comment(&comments, &mut out);
+
+            let mut fields = Vec::new();
+            for field in lines.by_ref() {
+                if field.starts_with('}') {
+                    break;
+                }
+                if let Some(field) = field.trim().strip_prefix("pub ") {
+                    fields.push(c_declaration(field.trim_end_matches(',')));
+                }
+            }
+
+            if repr_c {
+                out.push_str(&format!("typedef struct {} {{\n", name));
+                for field in fields {
+                    out.push_str(&format!("    {};\n", field));
+                }
+                out.push_str(&format!("}} {};\n", name));
+            } else {
+                out.push_str(&format!("typedef struct {} {};\n", name, name));
+            }
+        } else if line.starts_with("pub extern \"C\" fn")
+            || line.starts_with("pub unsafe extern \"C\" fn")
+        {
+            let mut signature = line.to_string();
+            while !signature.trim_end().ends_with('{') {
+                match lines.next() {
+                    Some(next) => signature.push_str(next.trim()),
+                    None => break,
+                }
+            }
+
+            out.push('\n');
+            c_comment(&comments, &mut out);
+            out.push_str(&c_prototype(&signature));
+        }
+
+        comments.clear();
+        repr_c = false;
+        previous_const = false;
+    }
+
+    out.push('\n');
+    out.push_str(POSTAMBLE);
+    out
+}
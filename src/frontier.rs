@@ -0,0 +1,394 @@
+// searches that keep the boards still to be expanded and forget the ones that were, so memory
+// grows with the frontier of the search instead of with everything it has seen
+//
+// frontier A* (after Korf) is A* without a closed list. every node remembers which of its moves
+// lead back to a board already expanded, one bit a direction. expanding a node only makes the
+// children it has no bit for, and sets the bit back to it on each of them, so an expanded board
+// is never made again as long as the heuristic is consistent (never drops by more than the cost
+// of a move), like manhattan distance, linear conflict and walking distance.
+//
+// breadth first heuristic search (after Zhou and Hansen) goes a layer of moves at a time, pruning
+// the boards whose g(n) + h(n) is over a bound, and keeps only the layer before the one being
+// expanded to catch the moves that go back. the bound starts at h(n) of the initial board and
+// goes up to the lowest pruned g(n) + h(n) each time a search ends without the goal, so the first
+// answer is the cheapest.
+//
+// neither keeps the parents to walk back along, so the path is found by divide and conquer. every
+// node carries a board from about halfway along the path that reached it: for frontier A* the first
+// one where g(n) caught up with h(n), for breadth first heuristic search the one in the layer
+// halfway to the bound. once the goal is found, the paths from the initial board to that middle
+// board and from there to the goal are found the same way, until they're a single move.
+//
+// both work on boards with one space where every move costs 1, with or without walls and wrapping
+// edges.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::heuristic::Heuristic;
+use crate::slides::{self, Slide};
+use crate::{
+    search_with_heuristic, Algorithm, EightPuzzle, Moves, Problem, SearchLimits, SearchOutcome,
+    Solution, StopReason,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrontierAlgorithm {
+    FrontierAStar,
+    BreadthFirstHeuristic,
+}
+
+impl FrontierAlgorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            FrontierAlgorithm::FrontierAStar => "frontier-astar",
+            FrontierAlgorithm::BreadthFirstHeuristic => "bfhs",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<FrontierAlgorithm> {
+        match name {
+            "frontier-astar" => Some(FrontierAlgorithm::FrontierAStar),
+            "bfhs" => Some(FrontierAlgorithm::BreadthFirstHeuristic),
+            _ => None,
+        }
+    }
+}
+
+// how much work the searches did and how many boards they kept
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrontierStats {
+    // nodes expanded, counting the searches that found the halves of the path
+    pub expanded: u64,
+    // the most boards kept at one time
+    pub max_stored: usize,
+    // the boards the first search expanded, a search with a closed list keeps every one of them
+    pub closed_boards: u64,
+    // how many searches it took, the first one and the ones finding the halves of the path
+    pub searches: u64,
+}
+
+const DIRECTIONS: [Moves; 4] = [Moves::Up, Moves::Down, Moves::Left, Moves::Right];
+
+fn direction_bit(direction: Moves) -> u8 {
+    match direction {
+        Moves::Up => 1,
+        Moves::Down => 2,
+        Moves::Left => 4,
+        Moves::Right => 8,
+        Moves::Nothing => 0,
+    }
+}
+
+// what's shared by every search of one problem
+struct Run<'a> {
+    heuristic: &'a dyn Heuristic,
+    algorithm: FrontierAlgorithm,
+    limits: SearchLimits,
+    stats: FrontierStats,
+}
+
+impl<'a> Run<'a> {
+    fn stored(&mut self, boards: usize) {
+        self.stats.max_stored = self.stats.max_stored.max(boards);
+    }
+
+    // the cost of the cheapest path between two boards and a board about halfway along it, None
+    // when there's no path
+    fn halfway(
+        &mut self,
+        start: &EightPuzzle,
+        goal: &EightPuzzle,
+    ) -> Result<Option<(u64, EightPuzzle)>, StopReason> {
+        let expanded = self.stats.expanded;
+        let found = match self.algorithm {
+            FrontierAlgorithm::FrontierAStar => self.frontier_astar(start, goal),
+            FrontierAlgorithm::BreadthFirstHeuristic => self.breadth_first_heuristic(start, goal),
+        };
+        if self.stats.searches == 0 {
+            self.stats.closed_boards = self.stats.expanded - expanded;
+        }
+        self.stats.searches += 1;
+        found
+    }
+
+    fn frontier_astar(
+        &mut self,
+        start: &EightPuzzle,
+        goal: &EightPuzzle,
+    ) -> Result<Option<(u64, EightPuzzle)>, StopReason> {
+        struct Node {
+            g: u64,
+            h: u64,
+            // the moves that lead back to a board already expanded
+            used: u8,
+            middle: Option<EightPuzzle>,
+        }
+
+        let h = self.heuristic.estimate(start, goal);
+        let mut open = HashMap::new();
+        open.insert(
+            start.clone(),
+            Node {
+                g: 0,
+                h,
+                used: 0,
+                middle: None,
+            },
+        );
+        // the open boards by f(n) and then h(n), a board whose g(n) went down since is left in
+        // its old place and skipped when it comes up
+        let mut queue: BTreeMap<(u64, u64), Vec<EightPuzzle>> = BTreeMap::new();
+        queue.insert((h, h), vec![start.clone()]);
+
+        loop {
+            self.stored(open.len());
+            self.limits.check(self.stats.expanded)?;
+
+            let (key, puzzle) = {
+                let lowest = match queue.iter_mut().next() {
+                    Some(lowest) => lowest,
+                    None => return Ok(None),
+                };
+                let puzzle = lowest.1.pop().expect("empty buckets are removed");
+                (*lowest.0, puzzle)
+            };
+            if queue[&key].is_empty() {
+                queue.remove(&key);
+            }
+            let current = match open.get(&puzzle) {
+                Some(node) if (node.g + node.h, node.h) == key => open.remove(&puzzle).unwrap(),
+                _ => continue,
+            };
+
+            if puzzle == *goal {
+                let middle = current.middle.unwrap_or_else(|| puzzle.clone());
+                return Ok(Some((current.g, middle)));
+            }
+            self.stats.expanded += 1;
+
+            for direction in DIRECTIONS.iter() {
+                if current.used & direction_bit(*direction) != 0 {
+                    continue;
+                }
+                let child = puzzle.apply_move(*direction);
+                if child == puzzle {
+                    continue;
+                }
+
+                let g = current.g + 1;
+                let back = direction_bit(direction.opposite());
+                if let Some(node) = open.get_mut(&child) {
+                    node.used |= back;
+                    if g < node.g {
+                        node.g = g;
+                        node.middle = current.middle.clone().or_else(|| {
+                            if g >= node.h {
+                                Some(child.clone())
+                            } else {
+                                None
+                            }
+                        });
+                        queue.entry((g + node.h, node.h)).or_default().push(child);
+                    }
+                    continue;
+                }
+
+                let h = self.heuristic.estimate(&child, goal);
+                let middle =
+                    current
+                        .middle
+                        .clone()
+                        .or_else(|| if g >= h { Some(child.clone()) } else { None });
+                queue.entry((g + h, h)).or_default().push(child.clone());
+                open.insert(
+                    child,
+                    Node {
+                        g,
+                        h,
+                        used: back,
+                        middle,
+                    },
+                );
+            }
+        }
+    }
+
+    fn breadth_first_heuristic(
+        &mut self,
+        start: &EightPuzzle,
+        goal: &EightPuzzle,
+    ) -> Result<Option<(u64, EightPuzzle)>, StopReason> {
+        if start == goal {
+            return Ok(Some((0, start.clone())));
+        }
+
+        let mut bound = self.heuristic.estimate(start, goal);
+        loop {
+            // the boards in the layer halfway to the bound are the middles of the boards after
+            let middle_depth = bound / 2;
+            let mut previous: HashMap<EightPuzzle, Option<EightPuzzle>> = HashMap::new();
+            let mut current = HashMap::new();
+            current.insert(
+                start.clone(),
+                if middle_depth == 0 {
+                    Some(start.clone())
+                } else {
+                    None
+                },
+            );
+            let mut depth = 0;
+            let mut next_bound: Option<u64> = None;
+
+            while !current.is_empty() {
+                let mut next: HashMap<EightPuzzle, Option<EightPuzzle>> = HashMap::new();
+
+                for (puzzle, middle) in current.iter() {
+                    self.stored(previous.len() + current.len() + next.len());
+                    self.limits.check(self.stats.expanded)?;
+                    self.stats.expanded += 1;
+
+                    for direction in DIRECTIONS.iter() {
+                        let child = puzzle.apply_move(*direction);
+                        if child == *puzzle
+                            || previous.contains_key(&child)
+                            || current.contains_key(&child)
+                            || next.contains_key(&child)
+                        {
+                            continue;
+                        }
+
+                        let f = depth + 1 + self.heuristic.estimate(&child, goal);
+                        if f > bound {
+                            next_bound = Some(next_bound.map_or(f, |lowest| lowest.min(f)));
+                            continue;
+                        }
+
+                        let middle = if depth + 1 == middle_depth {
+                            Some(child.clone())
+                        } else {
+                            middle.clone()
+                        };
+                        if child == *goal {
+                            let middle = middle.unwrap_or_else(|| start.clone());
+                            return Ok(Some((depth + 1, middle)));
+                        }
+                        next.insert(child, middle);
+                    }
+                }
+
+                previous = current;
+                current = next;
+                depth += 1;
+            }
+
+            // nothing was pruned, so every board that can be reached was seen
+            match next_bound {
+                Some(next_bound) => bound = next_bound,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    // the moves of the cheapest path between two boards, None when there's none
+    fn path(
+        &mut self,
+        start: &EightPuzzle,
+        goal: &EightPuzzle,
+    ) -> Result<Option<Vec<Moves>>, StopReason> {
+        let (cost, middle) = match self.halfway(start, goal)? {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+
+        match cost {
+            0 => Ok(Some(Vec::new())),
+            1 => Ok(DIRECTIONS
+                .iter()
+                .find(|direction| start.apply_move(**direction) == *goal)
+                .map(|direction| vec![*direction])),
+            // only a heuristic that isn't consistent leaves the middle at one end, then this part
+            // is found the way A* does
+            _ if middle == *start || middle == *goal => Ok(self.astar_path(start, goal)),
+            _ => {
+                let mut moves = self
+                    .path(start, &middle)?
+                    .expect("the middle can be reached");
+                moves.extend(self.path(&middle, goal)?.expect("the goal can be reached"));
+                Ok(Some(moves))
+            }
+        }
+    }
+
+    fn astar_path(&mut self, start: &EightPuzzle, goal: &EightPuzzle) -> Option<Vec<Moves>> {
+        let problem = Problem::new(start.clone(), goal.clone());
+        let (outcome, stats) = search_with_heuristic(
+            problem,
+            self.heuristic,
+            Algorithm::AStar,
+            SearchLimits::default(),
+        );
+        self.stats.expanded += stats.expanded;
+        self.stored(stats.expanded as usize + stats.max_queue);
+        outcome.into_solution().map(|solution| solution.moves)
+    }
+}
+
+// the problems these searches can solve
+fn check_problem(problem: &Problem) -> Result<(), String> {
+    if !slides::is_plain(problem) {
+        return Err("these searches only work with one space and single tile moves".to_string());
+    }
+    if !problem.cost_model.is_unit() {
+        return Err("these searches only work when every move costs 1".to_string());
+    }
+    if !problem.colors.is_empty() {
+        return Err("these searches don't know about colours".to_string());
+    }
+    Ok(())
+}
+
+// finds the cheapest path with one of the searches, the heuristic has to be consistent
+pub fn search(
+    problem: &Problem,
+    heuristic: &dyn Heuristic,
+    algorithm: FrontierAlgorithm,
+    limits: SearchLimits,
+) -> Result<(SearchOutcome, FrontierStats), String> {
+    check_problem(problem)?;
+
+    let mut run = Run {
+        heuristic,
+        algorithm,
+        limits,
+        stats: FrontierStats::default(),
+    };
+    let outcome = match run.path(&problem.initial_state, &problem.goal_state) {
+        Ok(Some(moves)) => SearchOutcome::Solved(solution(problem, moves)),
+        Ok(None) => SearchOutcome::Unsolvable,
+        Err(reason) => SearchOutcome::Stopped(reason),
+    };
+
+    Ok((outcome, run.stats))
+}
+
+fn solution(problem: &Problem, moves: Vec<Moves>) -> Solution {
+    let mut puzzle = problem.initial_state.clone();
+    let mut slides = Vec::new();
+    for direction in moves.iter() {
+        let blank = puzzle.blank_position();
+        slides.push(Slide {
+            row: blank.row,
+            col: blank.col,
+            direction: *direction,
+            tiles: 1,
+        });
+        puzzle = puzzle.apply_move(*direction);
+    }
+
+    Solution {
+        puzzle,
+        cost: moves.len() as u64,
+        moves,
+        slides,
+    }
+}
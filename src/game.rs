@@ -0,0 +1,176 @@
+// lets a person play the puzzle in the terminal, with the solver on hand for hints
+//
+// controls:
+// * the arrow keys or w a s d move the space
+// * u undoes a move and r redoes it
+// * h asks A* with the manhattan distance heuristic for the best next move
+// * q or escape gives up
+
+use std::io;
+use std::io::prelude::*;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+
+use crate::colors::ColoredHeuristic;
+use crate::heuristic::Heuristic;
+use crate::playback::{draw_board, moved_tile, RawModeGuard};
+use crate::{manhattan_distance_heuristic, search, EightPuzzle, Moves, Problem};
+
+// length of an optimal solution from the board and its first move, None if the goal can't be
+// reached. everything but the start is the game's problem, so its colours and costs still count
+fn optimal_solution(game: &Problem, puzzle: &EightPuzzle) -> Option<(usize, Moves)> {
+    let problem = Problem {
+        initial_state: puzzle.clone(),
+        ..game.clone()
+    };
+    // A* would look through every board it can reach before giving up on one that can't be solved
+    if !problem.is_solvable() {
+        return None;
+    }
+
+    search(problem, manhattan_distance_heuristic).map(|solution| {
+        (
+            solution.moves.len(),
+            solution.moves.first().cloned().unwrap_or(Moves::Nothing),
+        )
+    })
+}
+
+fn read_key() -> io::Result<KeyCode> {
+    loop {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        {
+            return Ok(code);
+        }
+    }
+}
+
+pub fn play(problem: &Problem) -> io::Result<()> {
+    let goal = &problem.goal_state;
+    let estimate = ColoredHeuristic::new(&manhattan_distance_heuristic, &problem.colors);
+
+    // history[position] is the board on screen, anything after it can be redone
+    let mut history = vec![problem.initial_state.clone()];
+    let mut position = 0;
+    let mut total_moves = 0;
+    let mut hints = 0;
+    let mut message = String::new();
+    let mut highlight = None;
+
+    let guard = RawModeGuard::enable()?;
+
+    loop {
+        let current = history[position].clone();
+        let solved = problem.colors.matches(&current, goal);
+
+        let mut screen = String::new();
+        draw_board(&current, highlight, &mut screen);
+        screen.push_str(&format!(
+            "\tmoves: {} \t total moves including undone: {} \t hints: {}\r\n",
+            position, total_moves, hints
+        ));
+        screen.push_str(&format!(
+            "\th(n) = {}\r\n",
+            estimate.estimate(&current, goal)
+        ));
+        screen.push_str(&format!("\t{}\r\n\r\n", message));
+        if solved {
+            screen.push_str("\tsolved! press any key to see your score\r\n");
+        } else {
+            screen.push_str("\tarrows/wasd move, u undo, r redo, h hint, q quit\r\n");
+        }
+        print!("{}", screen);
+        io::stdout().flush()?;
+
+        let key = read_key()?;
+        if solved {
+            break;
+        }
+
+        message.clear();
+        highlight = None;
+
+        let space_move = match key {
+            KeyCode::Up | KeyCode::Char('w') => Moves::Up,
+            KeyCode::Down | KeyCode::Char('s') => Moves::Down,
+            KeyCode::Left | KeyCode::Char('a') => Moves::Left,
+            KeyCode::Right | KeyCode::Char('d') => Moves::Right,
+            KeyCode::Char('u') => {
+                if position > 0 {
+                    position -= 1;
+                } else {
+                    message.push_str("nothing to undo");
+                }
+                continue;
+            }
+            KeyCode::Char('r') => {
+                if position + 1 < history.len() {
+                    position += 1;
+                } else {
+                    message.push_str("nothing to redo");
+                }
+                continue;
+            }
+            KeyCode::Char('h') => {
+                hints += 1;
+                message = match optimal_solution(problem, &current) {
+                    Some((length, best_move)) => {
+                        highlight = moved_tile(&current, &current.apply_move(best_move));
+                        format!(
+                            "hint: move the space {}, the goal is {} moves away",
+                            best_move.name(),
+                            length
+                        )
+                    }
+                    None => "hint: the goal can't be reached from here".to_string(),
+                };
+                continue;
+            }
+            KeyCode::Char('q') | KeyCode::Esc => {
+                drop(guard);
+                println!("\r\nGave up after {} moves.", position);
+                return Ok(());
+            }
+            _ => continue,
+        };
+
+        let next = current.apply_move(space_move);
+        if next == current {
+            message.push_str("the space can't move that way");
+            continue;
+        }
+
+        // a new move throws away whatever could have been redone
+        history.truncate(position + 1);
+        history.push(next);
+        position += 1;
+        total_moves += 1;
+    }
+
+    drop(guard);
+
+    println!();
+    println!("Solved in {} moves using {} hints.", position, hints);
+    match optimal_solution(problem, &problem.initial_state) {
+        Some((optimal, _)) => {
+            println!("The optimal solution takes {} moves.", optimal);
+            // when moves cost different amounts the cheapest way can take more of them
+            if position <= optimal {
+                println!("Perfect score!");
+            } else {
+                println!(
+                    "That's {} moves more than needed, an efficiency of {:.0}%.",
+                    position - optimal,
+                    100.0 * optimal as f64 / position as f64
+                );
+            }
+        }
+        None => println!("The solver couldn't find a solution to compare against."),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,534 @@
+// heuristics as values instead of bare functions, so they can carry data and be put together
+//
+// any fn or closure taking the board and the goal is a Heuristic already. on top of those:
+// * MaxHeuristic takes the largest of several estimates and counts how often each part won
+// * AdditiveHeuristic adds up parts that each only count moves of their own tiles, which keeps the
+//   sum admissible as long as no tile is in two parts
+// * ScaledHeuristic multiplies an estimate, above 1 it trades the shortest path for a faster search
+// * FallbackHeuristic uses the first part that can handle the board
+//
+// when moves cost different amounts (see cost.rs) an estimate counting moves is scaled by the
+// cheapest move there is. manhattan distance does better and prices each tile's moves by what that
+// tile costs to slide, and the combinations pass the costs on to their parts.
+//
+// they can also be written out, the way the command line, documents and the server take them:
+//     manhattan                              any name heuristic_from_name knows
+//     manhattan[1 2 3 4]                     manhattan distance of just those tiles
+//     max(walking, linear-conflict)
+//     sum(manhattan[1 2 3 4], manhattan[5 6 7 8])
+//     scale(1.5, manhattan)
+//     fallback(walking, linear-conflict)
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cost::CheapestMoves;
+use crate::walking_distance::WalkingDistance;
+use crate::{
+    heuristic_from_name, manhattan_distance_heuristic, torus, walls, EightPuzzle, BLANK, WALL,
+};
+
+pub trait Heuristic: Send + Sync {
+    fn estimate(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64;
+
+    // the estimate as a cost instead of a number of moves, never more than the cheapest path costs
+    // as long as the plain estimate never counts more moves than there are
+    fn weighted_estimate(
+        &self,
+        puzzle: &EightPuzzle,
+        goal: &EightPuzzle,
+        cheapest: &CheapestMoves,
+    ) -> u64 {
+        self.estimate(puzzle, goal) * cheapest.overall()
+    }
+
+    // the tiles whose moves this counts, None when it looks at the whole board
+    fn tiles(&self) -> Option<Vec<u16>> {
+        None
+    }
+
+    // whether the board is one this can give a useful estimate for
+    fn supports(&self, _puzzle: &EightPuzzle, _goal: &EightPuzzle) -> bool {
+        true
+    }
+
+    // how often each part of a max() was the largest since the last call, and starts counting over
+    fn take_max_counts(&self) -> Vec<ComponentCount> {
+        Vec::new()
+    }
+}
+
+impl<F> Heuristic for F
+where
+    F: Fn(&EightPuzzle, &EightPuzzle) -> u64 + Send + Sync,
+{
+    fn estimate(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+        self(puzzle, goal)
+    }
+}
+
+// how many estimates one part of a max() gave
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ComponentCount {
+    pub name: String,
+    pub count: u64,
+}
+
+pub struct MaxHeuristic {
+    parts: Vec<(String, Box<dyn Heuristic>)>,
+    wins: Vec<AtomicU64>,
+}
+
+impl MaxHeuristic {
+    // the names are what the counts are reported under
+    pub fn new(parts: Vec<(String, Box<dyn Heuristic>)>) -> MaxHeuristic {
+        let wins = parts.iter().map(|_| AtomicU64::new(0)).collect();
+        MaxHeuristic { parts, wins }
+    }
+
+    fn largest(&self, estimate: impl Fn(&dyn Heuristic) -> u64) -> u64 {
+        let mut best = (0, 0); // index, estimate
+
+        for (index, (_, part)) in self.parts.iter().enumerate() {
+            let estimate = estimate(part.as_ref());
+            // ties go to the earlier part
+            if index == 0 || estimate > best.1 {
+                best = (index, estimate);
+            }
+        }
+
+        if let Some(wins) = self.wins.get(best.0) {
+            wins.fetch_add(1, Ordering::Relaxed);
+        }
+        best.1
+    }
+}
+
+impl Heuristic for MaxHeuristic {
+    fn estimate(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+        self.largest(|part| part.estimate(puzzle, goal))
+    }
+
+    fn weighted_estimate(
+        &self,
+        puzzle: &EightPuzzle,
+        goal: &EightPuzzle,
+        cheapest: &CheapestMoves,
+    ) -> u64 {
+        self.largest(|part| part.weighted_estimate(puzzle, goal, cheapest))
+    }
+
+    fn tiles(&self) -> Option<Vec<u16>> {
+        union_of_tiles(self.parts.iter().map(|(_, part)| part.as_ref()))
+    }
+
+    fn take_max_counts(&self) -> Vec<ComponentCount> {
+        let mut counts: Vec<ComponentCount> = self
+            .parts
+            .iter()
+            .zip(self.wins.iter())
+            .map(|((name, _), wins)| ComponentCount {
+                name: name.clone(),
+                count: wins.swap(0, Ordering::Relaxed),
+            })
+            .collect();
+
+        for (_, part) in self.parts.iter() {
+            counts.extend(part.take_max_counts());
+        }
+        counts
+    }
+}
+
+pub struct AdditiveHeuristic {
+    parts: Vec<Box<dyn Heuristic>>,
+}
+
+impl AdditiveHeuristic {
+    // refuses parts that could count the same move twice
+    pub fn new(parts: Vec<Box<dyn Heuristic>>) -> Result<AdditiveHeuristic, String> {
+        let mut seen = HashSet::new();
+
+        for (index, part) in parts.iter().enumerate() {
+            let tiles = part.tiles().ok_or_else(|| {
+                format!(
+                    "part {} of the sum counts every tile, only parts over some of the tiles can be added",
+                    index + 1
+                )
+            })?;
+
+            for tile in tiles {
+                if !seen.insert(tile) {
+                    return Err(format!("tile {} is in more than one part of the sum", tile));
+                }
+            }
+        }
+
+        Ok(AdditiveHeuristic { parts })
+    }
+}
+
+impl Heuristic for AdditiveHeuristic {
+    fn estimate(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+        self.parts
+            .iter()
+            .map(|part| part.estimate(puzzle, goal))
+            .sum()
+    }
+
+    fn weighted_estimate(
+        &self,
+        puzzle: &EightPuzzle,
+        goal: &EightPuzzle,
+        cheapest: &CheapestMoves,
+    ) -> u64 {
+        self.parts
+            .iter()
+            .map(|part| part.weighted_estimate(puzzle, goal, cheapest))
+            .sum()
+    }
+
+    fn tiles(&self) -> Option<Vec<u16>> {
+        union_of_tiles(self.parts.iter().map(|part| part.as_ref()))
+    }
+
+    fn take_max_counts(&self) -> Vec<ComponentCount> {
+        self.parts
+            .iter()
+            .flat_map(|part| part.take_max_counts())
+            .collect()
+    }
+}
+
+pub struct ScaledHeuristic {
+    weight: f64,
+    inner: Box<dyn Heuristic>,
+}
+
+impl ScaledHeuristic {
+    pub fn new(weight: f64, inner: Box<dyn Heuristic>) -> Result<ScaledHeuristic, String> {
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(format!("can't scale a heuristic by {}", weight));
+        }
+
+        Ok(ScaledHeuristic { weight, inner })
+    }
+}
+
+impl Heuristic for ScaledHeuristic {
+    // rounded down, so a weight of at most 1 keeps an admissible heuristic admissible
+    fn estimate(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+        (self.inner.estimate(puzzle, goal) as f64 * self.weight).floor() as u64
+    }
+
+    fn weighted_estimate(
+        &self,
+        puzzle: &EightPuzzle,
+        goal: &EightPuzzle,
+        cheapest: &CheapestMoves,
+    ) -> u64 {
+        (self.inner.weighted_estimate(puzzle, goal, cheapest) as f64 * self.weight).floor() as u64
+    }
+
+    fn tiles(&self) -> Option<Vec<u16>> {
+        self.inner.tiles()
+    }
+
+    fn supports(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> bool {
+        self.inner.supports(puzzle, goal)
+    }
+
+    fn take_max_counts(&self) -> Vec<ComponentCount> {
+        self.inner.take_max_counts()
+    }
+}
+
+pub struct FallbackHeuristic {
+    parts: Vec<Box<dyn Heuristic>>,
+}
+
+impl FallbackHeuristic {
+    pub fn new(parts: Vec<Box<dyn Heuristic>>) -> Result<FallbackHeuristic, String> {
+        if parts.is_empty() {
+            return Err("fallback needs at least one heuristic".to_string());
+        }
+
+        Ok(FallbackHeuristic { parts })
+    }
+
+    // the last part is used when none of the others can handle the board
+    fn pick(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> &dyn Heuristic {
+        let last = self.parts.len() - 1;
+        self.parts[..last]
+            .iter()
+            .find(|part| part.supports(puzzle, goal))
+            .unwrap_or(&self.parts[last])
+            .as_ref()
+    }
+}
+
+impl Heuristic for FallbackHeuristic {
+    fn estimate(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+        self.pick(puzzle, goal).estimate(puzzle, goal)
+    }
+
+    fn weighted_estimate(
+        &self,
+        puzzle: &EightPuzzle,
+        goal: &EightPuzzle,
+        cheapest: &CheapestMoves,
+    ) -> u64 {
+        self.pick(puzzle, goal)
+            .weighted_estimate(puzzle, goal, cheapest)
+    }
+
+    fn supports(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> bool {
+        self.parts.iter().any(|part| part.supports(puzzle, goal))
+    }
+
+    fn take_max_counts(&self) -> Vec<ComponentCount> {
+        self.parts
+            .iter()
+            .flat_map(|part| part.take_max_counts())
+            .collect()
+    }
+}
+
+// manhattan distance of some of the tiles, the pattern for additive sums
+pub struct TileManhattan {
+    tiles: Vec<u16>,
+}
+
+impl TileManhattan {
+    pub fn new(tiles: Vec<u16>) -> Result<TileManhattan, String> {
+        if tiles.contains(&BLANK) {
+            return Err("the space isn't a tile, leave 0 out of the pattern".to_string());
+        }
+        if tiles.contains(&WALL) {
+            return Err("walls don't move, leave them out of the pattern".to_string());
+        }
+
+        Ok(TileManhattan { tiles })
+    }
+}
+
+impl Heuristic for TileManhattan {
+    fn estimate(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+        weighted_manhattan(puzzle, goal, &self.tiles, |_| 1)
+    }
+
+    fn weighted_estimate(
+        &self,
+        puzzle: &EightPuzzle,
+        goal: &EightPuzzle,
+        cheapest: &CheapestMoves,
+    ) -> u64 {
+        weighted_manhattan(puzzle, goal, &self.tiles, |tile| cheapest.tile(tile))
+    }
+
+    fn tiles(&self) -> Option<Vec<u16>> {
+        Some(self.tiles.clone())
+    }
+}
+
+// manhattan distance of every tile, the plain function for unit costs
+pub struct ManhattanDistance;
+
+impl Heuristic for ManhattanDistance {
+    fn estimate(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+        manhattan_distance_heuristic(puzzle, goal)
+    }
+
+    fn weighted_estimate(
+        &self,
+        puzzle: &EightPuzzle,
+        goal: &EightPuzzle,
+        cheapest: &CheapestMoves,
+    ) -> u64 {
+        let tiles: Vec<u16> = puzzle
+            .tiles
+            .iter()
+            .cloned()
+            .filter(|tile| *tile != BLANK && *tile != WALL)
+            .collect();
+        weighted_manhattan(puzzle, goal, &tiles, |tile| cheapest.tile(tile))
+    }
+}
+
+// how far each of the tiles is from its goal spot, times what moving that tile costs. around walls
+// it's the shortest way around them. on a canonical board with colours (see colors.rs) only the
+// first tile of a colour is counted, to the nearest cell of its colour
+fn weighted_manhattan(
+    puzzle: &EightPuzzle,
+    goal: &EightPuzzle,
+    tiles: &[u16],
+    cost: impl Fn(u16) -> u64,
+) -> u64 {
+    let distances = if walls::has_walls(puzzle) {
+        Some(walls::cell_distances(puzzle))
+    } else {
+        None
+    };
+    let (rows, cols) = (puzzle.rows, puzzle.cols);
+    let wraps = torus::wraps(puzzle);
+    let distance = |at: usize, goal_at: usize| -> u64 {
+        match &distances {
+            Some(distances) => distances.between(at, goal_at),
+            None => {
+                torus::axis_distance(at / cols, goal_at / cols, rows, wraps)
+                    + torus::axis_distance(at % cols, goal_at % cols, cols, wraps)
+            }
+        }
+    };
+
+    let mut counted = tiles.to_vec();
+    counted.sort_unstable();
+    counted.dedup();
+    counted
+        .iter()
+        .filter_map(|tile| {
+            let at = puzzle.tiles.iter().position(|other| other == tile)?;
+            goal.tiles
+                .iter()
+                .enumerate()
+                .filter(|(_, other)| *other == tile)
+                .map(|(goal_at, _)| distance(at, goal_at))
+                .min()
+                .map(|nearest| nearest * cost(*tile))
+        })
+        .sum()
+}
+
+// every tile some part counts, None if any part counts them all
+fn union_of_tiles<'a>(parts: impl Iterator<Item = &'a dyn Heuristic>) -> Option<Vec<u16>> {
+    let mut tiles = Vec::new();
+    for part in parts {
+        for tile in part.tiles()? {
+            if !tiles.contains(&tile) {
+                tiles.push(tile);
+            }
+        }
+    }
+    Some(tiles)
+}
+
+// splits "a, f(b, c), d" on the commas that aren't inside brackets
+fn split_arguments(text: &str) -> Result<Vec<&str>, String> {
+    let mut arguments = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, character) in text.char_indices() {
+        match character {
+            '(' | '[' => depth += 1,
+            ')' | ']' => {
+                if depth == 0 {
+                    return Err(format!("unmatched {} in {}", character, text));
+                }
+                depth -= 1;
+            }
+            ',' if depth == 0 => {
+                arguments.push(text[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err(format!("unclosed bracket in {}", text));
+    }
+    arguments.push(text[start..].trim());
+
+    if arguments.iter().any(|argument| argument.is_empty()) {
+        return Err(format!("empty argument in {}", text));
+    }
+    Ok(arguments)
+}
+
+// a heuristic written out the way the module comment shows
+pub fn parse_heuristic(spec: &str) -> Result<Box<dyn Heuristic>, String> {
+    let spec = spec.trim();
+
+    if let Some(open) = spec.find('(') {
+        if !spec.ends_with(')') {
+            return Err(format!("{} is missing its closing )", spec));
+        }
+        let function = spec[..open].trim();
+        let arguments = split_arguments(&spec[open + 1..spec.len() - 1])?;
+
+        return match function {
+            "max" => {
+                let parts = arguments
+                    .iter()
+                    .map(|argument| Ok((argument.to_string(), parse_heuristic(argument)?)))
+                    .collect::<Result<Vec<_>, String>>()?;
+                Ok(Box::new(MaxHeuristic::new(parts)))
+            }
+            "sum" => {
+                let parts = parse_all(&arguments)?;
+                Ok(Box::new(AdditiveHeuristic::new(parts)?))
+            }
+            "scale" => {
+                if arguments.len() != 2 {
+                    return Err(format!(
+                        "scale takes a weight and a heuristic, not {}",
+                        spec
+                    ));
+                }
+                let weight = arguments[0]
+                    .parse::<f64>()
+                    .map_err(|_| format!("bad weight {}", arguments[0]))?;
+                Ok(Box::new(ScaledHeuristic::new(
+                    weight,
+                    parse_heuristic(arguments[1])?,
+                )?))
+            }
+            "fallback" => Ok(Box::new(FallbackHeuristic::new(parse_all(&arguments)?)?)),
+            _ => Err(format!("unknown combinator {}", function)),
+        };
+    }
+
+    if let Some(open) = spec.find('[') {
+        if !spec.ends_with(']') {
+            return Err(format!("{} is missing its closing ]", spec));
+        }
+        let name = spec[..open].trim();
+        if name != "manhattan" {
+            return Err(format!(
+                "only manhattan can be limited to some tiles, not {}",
+                name
+            ));
+        }
+
+        let tiles = spec[open + 1..spec.len() - 1]
+            .split(|character: char| character.is_whitespace() || character == ',')
+            .filter(|tile| !tile.is_empty())
+            .map(|tile| {
+                tile.parse::<u16>()
+                    .map_err(|_| format!("bad tile {}", tile))
+            })
+            .collect::<Result<Vec<u16>, String>>()?;
+        return Ok(Box::new(TileManhattan::new(tiles)?));
+    }
+
+    // walking distance knows which boards its tables can handle and manhattan distance knows
+    // what each tile costs, the plain functions don't
+    match spec {
+        "walking" => return Ok(Box::new(WalkingDistance::new())),
+        "manhattan" => return Ok(Box::new(ManhattanDistance)),
+        _ => {}
+    }
+
+    heuristic_from_name(spec)
+        .map(|heuristic| Box::new(heuristic) as Box<dyn Heuristic>)
+        .ok_or_else(|| format!("unknown heuristic {}", spec))
+}
+
+fn parse_all(arguments: &[&str]) -> Result<Vec<Box<dyn Heuristic>>, String> {
+    arguments
+        .iter()
+        .map(|argument| parse_heuristic(argument))
+        .collect()
+}
@@ -0,0 +1,1260 @@
+// the puzzle, the search and the heuristics, shared by the interactive program in main.rs and
+// anything else that wants to solve puzzles
+extern crate num;
+
+pub mod analysis;
+pub mod checkpoint;
+pub mod colors;
+pub mod constructive;
+pub mod cost;
+pub mod dot;
+pub mod external_bfs;
+pub mod ffi;
+pub mod ffi_header;
+pub mod frontier;
+pub mod game;
+pub mod heuristic;
+pub mod macros;
+pub mod notation;
+pub mod optimal;
+pub mod parse;
+pub mod paths;
+pub mod playback;
+pub mod schema;
+pub mod server;
+pub mod slides;
+pub mod torus;
+pub mod trace;
+pub mod walking_distance;
+pub mod walls;
+
+use colors::{ColoredHeuristic, TileClasses, TileColors};
+use cost::{CostModel, CostWeightedHeuristic};
+use dot::SearchTreeExport;
+use heuristic::{ComponentCount, Heuristic};
+use num::abs;
+use serde::{Deserialize, Serialize};
+use slides::{MoveModel, Slide, SlideCountHeuristic};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use torus::Topology;
+use trace::{TraceEvent, TraceEventKind, TraceWriter};
+
+// atomic so searches running on several threads can all read it
+pub static PRINT_EXPANDING_NODES_TOGGLE: AtomicBool = AtomicBool::new(true);
+
+// the number used for the space, the same way it's typed in
+pub const BLANK: u16 = 0;
+// a cell no tile can enter, typed as # (see walls.rs)
+pub const WALL: u16 = u16::MAX;
+
+pub struct IndexTuple {
+    pub row: usize,
+    pub col: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EightPuzzle {
+    pub rows: usize,
+    pub cols: usize,
+    // every tile in row order, BLANK marks the space and WALL the cells that aren't part of the board
+    pub tiles: Vec<u16>,
+    // whether the edges wrap around (see torus.rs)
+    pub topology: Topology,
+}
+
+// implementations for printing eight puzzle and moving space around
+impl EightPuzzle {
+    // the usual goal, tiles counting up in row order with the space in the bottom right corner
+    pub fn solved(rows: usize, cols: usize) -> EightPuzzle {
+        let mut tiles: Vec<u16> = (1..(rows * cols) as u16).collect();
+        tiles.push(BLANK);
+
+        EightPuzzle {
+            rows,
+            cols,
+            tiles,
+            topology: Topology::Flat,
+        }
+    }
+
+    // the usual goal for a board of the same shape, the walls where they are and the other cells
+    // counting up around them, with as many spaces as it has in the last open cells
+    pub fn solved_like(puzzle: &EightPuzzle) -> EightPuzzle {
+        let mut spaces = slides::space_count(puzzle).max(1);
+        let mut next_tile = 1;
+        let mut tiles: Vec<u16> = puzzle
+            .tiles
+            .iter()
+            .map(|tile| {
+                if *tile == WALL {
+                    WALL
+                } else {
+                    next_tile += 1;
+                    next_tile - 1
+                }
+            })
+            .collect();
+
+        for tile in tiles.iter_mut().rev() {
+            if spaces == 0 {
+                break;
+            }
+            if *tile != WALL {
+                *tile = BLANK;
+                spaces -= 1;
+            }
+        }
+
+        EightPuzzle {
+            rows: puzzle.rows,
+            cols: puzzle.cols,
+            tiles,
+            topology: puzzle.topology,
+        }
+    }
+
+    // the same tiles on a board with other edges
+    pub fn with_topology(&self, topology: Topology) -> EightPuzzle {
+        EightPuzzle {
+            topology,
+            ..self.clone()
+        }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> u16 {
+        self.tiles[row * self.cols + col]
+    }
+
+    // the first space in row order when there's more than one, it's the one the moves below move
+    pub fn blank_position(&self) -> IndexTuple {
+        let index = self
+            .tiles
+            .iter()
+            .position(|&tile| tile == BLANK)
+            .expect("a puzzle always has a space");
+
+        IndexTuple {
+            row: index / self.cols,
+            col: index % self.cols,
+        }
+    }
+
+    pub fn print_puzzle(&self) {
+        for row in self.tiles.chunks(self.cols) {
+            for tile in row {
+                if *tile == BLANK {
+                    print!("\t *")
+                } else if *tile == WALL {
+                    print!("\t #")
+                } else {
+                    print!("\t {}", tile)
+                }
+            }
+
+            println!()
+        }
+    }
+
+    // swaps the space with the tile at the given spot, a wall there stops it like the edge does
+    pub fn move_space_to(&self, from: IndexTuple, to: IndexTuple) -> EightPuzzle {
+        let mut new_puzzle = self.clone();
+
+        if self.get(to.row, to.col) == WALL {
+            return new_puzzle;
+        }
+
+        new_puzzle
+            .tiles
+            .swap(from.row * self.cols + from.col, to.row * self.cols + to.col);
+
+        new_puzzle
+    }
+
+    // on a torus the space goes off one edge and comes back on the other, see torus.rs
+    pub fn move_up(&self) -> EightPuzzle {
+        let index = self.blank_position();
+
+        if index.row == 0 && !torus::wraps(self) {
+            return self.clone();
+        }
+
+        let to = IndexTuple {
+            row: (index.row + self.rows - 1) % self.rows,
+            col: index.col,
+        };
+        self.move_space_to(index, to)
+    }
+
+    pub fn move_down(&self) -> EightPuzzle {
+        let index = self.blank_position();
+
+        if index.row == self.rows - 1 && !torus::wraps(self) {
+            return self.clone();
+        }
+
+        let to = IndexTuple {
+            row: (index.row + 1) % self.rows,
+            col: index.col,
+        };
+        self.move_space_to(index, to)
+    }
+
+    pub fn move_right(&self) -> EightPuzzle {
+        let index = self.blank_position();
+
+        if index.col == self.cols - 1 && !torus::wraps(self) {
+            return self.clone();
+        }
+
+        let to = IndexTuple {
+            row: index.row,
+            col: (index.col + 1) % self.cols,
+        };
+        self.move_space_to(index, to)
+    }
+
+    pub fn move_left(&self) -> EightPuzzle {
+        let index = self.blank_position();
+
+        if index.col == 0 && !torus::wraps(self) {
+            return self.clone();
+        }
+
+        let to = IndexTuple {
+            row: index.row,
+            col: (index.col + self.cols - 1) % self.cols,
+        };
+        self.move_space_to(index, to)
+    }
+
+    pub fn apply_move(&self, space_move: Moves) -> EightPuzzle {
+        match space_move {
+            Moves::Up => self.move_up(),
+            Moves::Down => self.move_down(),
+            Moves::Left => self.move_left(),
+            Moves::Right => self.move_right(),
+            Moves::Nothing => self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Problem {
+    pub initial_state: EightPuzzle,
+    pub goal_state: EightPuzzle,
+    pub cost_model: CostModel,
+    pub move_model: MoveModel,
+    // tiles that can stand in for each other, the goal only asks for their colours (see colors.rs)
+    pub colors: TileColors,
+}
+
+impl Problem {
+    // the usual problem between two boards: every move costs 1, one tile moves at a time and every
+    // tile has to reach its own place
+    pub fn new(initial_state: EightPuzzle, goal_state: EightPuzzle) -> Problem {
+        Problem {
+            initial_state,
+            goal_state,
+            cost_model: CostModel::Unit,
+            move_model: MoveModel::SingleTile,
+            colors: TileColors::default(),
+        }
+    }
+
+    // every move swaps the space with a tile, which flips both the parity of the permutation taking
+    // the tiles to the goal and the parity of the space's distance from its goal spot. the two
+    // always agree on a reachable board, and on boards at least 2x2 every board where they agree
+    // can be reached
+    pub fn is_solvable(&self) -> bool {
+        // the same tests hold on the canonical boards, as long as they know tiles can turn up twice
+        if !self.colors.is_empty() {
+            let classes = self.colors.classes();
+            let initial = classes.canonical(&self.initial_state);
+            let goal = classes.canonical(&self.goal_state);
+
+            let mut tiles = initial.tiles.clone();
+            let mut goal_tiles = goal.tiles.clone();
+            tiles.sort_unstable();
+            goal_tiles.sort_unstable();
+            if tiles != goal_tiles {
+                return false;
+            }
+
+            let canonical = Problem {
+                initial_state: initial,
+                goal_state: goal,
+                colors: TileColors::default(),
+                ..self.clone()
+            };
+            return canonical.is_solvable();
+        }
+
+        let (initial, goal) = (&self.initial_state, &self.goal_state);
+
+        if initial.topology != goal.topology {
+            return false;
+        }
+        if walls::has_walls(initial) || walls::has_walls(goal) {
+            return walls::is_solvable(initial, goal);
+        }
+        if let Some(solvable) = torus::is_solvable(initial, goal) {
+            return solvable;
+        }
+
+        // on a single row or column the tiles can't pass each other
+        if initial.rows == 1 || initial.cols == 1 {
+            let tiles = |puzzle: &EightPuzzle| -> Vec<u16> {
+                puzzle
+                    .tiles
+                    .iter()
+                    .cloned()
+                    .filter(|tile| *tile != BLANK)
+                    .collect()
+            };
+            return tiles(initial) == tiles(goal);
+        }
+
+        // with a second space any two tiles can trade places, so only which tiles there are matters
+        if slides::space_count(initial) > 1 || slides::space_count(goal) > 1 {
+            let mut tiles = initial.tiles.clone();
+            let mut goal_tiles = goal.tiles.clone();
+            tiles.sort_unstable();
+            goal_tiles.sort_unstable();
+            return tiles == goal_tiles;
+        }
+
+        // two tiles of a colour can trade places to fix the parity
+        if colors::has_interchangeable_tiles(initial) {
+            return true;
+        }
+
+        let mut goal_index = vec![0; goal.tiles.len()];
+        for (index, tile) in goal.tiles.iter().enumerate() {
+            goal_index[*tile as usize] = index;
+        }
+
+        // a cycle of length n takes n - 1 swaps
+        let mut swaps = 0;
+        let mut visited = vec![false; initial.tiles.len()];
+        for start in 0..initial.tiles.len() {
+            let mut index = start;
+            let mut length = 0;
+            while !visited[index] {
+                visited[index] = true;
+                index = goal_index[initial.tiles[index] as usize];
+                length += 1;
+            }
+            if length > 0 {
+                swaps += length - 1;
+            }
+        }
+
+        let blank = initial.blank_position();
+        let goal_blank = goal.blank_position();
+        let distance = (blank.row as i64 - goal_blank.row as i64).abs()
+            + (blank.col as i64 - goal_blank.col as i64).abs();
+
+        swaps % 2 == distance % 2
+    }
+}
+
+// the goal a search reached and the moves of the space that lead there from the initial state
+pub struct Solution {
+    pub puzzle: EightPuzzle,
+    pub moves: Vec<Moves>,
+    // the same moves saying which space moved and how far, all that's needed to follow a path when
+    // there are several spaces or several tiles slide at once (see slides.rs)
+    pub slides: Vec<Slide>,
+    // what the moves add up to under the problem's cost model
+    pub cost: u64,
+}
+
+// how much work a search did to find its answer
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchStats {
+    // nodes that had their children generated
+    pub expanded: u64,
+    // the most nodes waiting in the queue at one time
+    pub max_queue: usize,
+    // how often each part of a max() heuristic gave the estimate, empty for other heuristics
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub max_components: Vec<ComponentCount>,
+}
+
+// which node a search takes off the queue next
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Algorithm {
+    // lowest g(n) + h(n), the path is optimal when the heuristic never overestimates
+    #[default]
+    #[serde(rename = "astar")]
+    AStar,
+    // lowest g(n), the heuristic is ignored
+    #[serde(rename = "ucs")]
+    UniformCost,
+    // lowest h(n), usually quick but the path is rarely the shortest
+    #[serde(rename = "greedy")]
+    Greedy,
+}
+
+impl Algorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::AStar => "astar",
+            Algorithm::UniformCost => "ucs",
+            Algorithm::Greedy => "greedy",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Algorithm> {
+        match name {
+            "astar" => Some(Algorithm::AStar),
+            "ucs" => Some(Algorithm::UniformCost),
+            "greedy" => Some(Algorithm::Greedy),
+            _ => None,
+        }
+    }
+}
+
+// when a search should give up and what it writes down as it goes, nothing by default
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    pub max_expansions: Option<u64>,
+    pub deadline: Option<Instant>,
+    // shared so the trace and the tree can be taken back once the search is over
+    pub recorder: Option<Arc<Mutex<SearchRecorder>>>,
+}
+
+impl SearchLimits {
+    // whether a search that has expanded so many nodes has to stop now
+    pub fn check(&self, expanded: u64) -> Result<(), StopReason> {
+        if let Some(max_expansions) = self.max_expansions {
+            if expanded >= max_expansions {
+                return Err(StopReason::ExpansionLimit);
+            }
+        }
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return Err(StopReason::TimeLimit);
+            }
+        }
+        Ok(())
+    }
+}
+
+// a trace file (see trace.rs) and a search tree to export (see dot.rs) for a search to fill in
+#[derive(Default)]
+pub struct SearchRecorder {
+    pub trace: Option<TraceWriter>,
+    pub tree: Option<SearchTreeExport>,
+}
+
+impl fmt::Debug for SearchRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SearchRecorder")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    ExpansionLimit,
+    TimeLimit,
+}
+
+pub enum SearchOutcome {
+    Solved(Solution),
+    // every reachable board was expanded without finding the goal
+    Unsolvable,
+    // a limit ran out first, so the goal may or may not be reachable
+    Stopped(StopReason),
+}
+
+impl SearchOutcome {
+    pub fn solution(&self) -> Option<&Solution> {
+        match self {
+            SearchOutcome::Solved(solution) => Some(solution),
+            _ => None,
+        }
+    }
+
+    pub fn into_solution(self) -> Option<Solution> {
+        match self {
+            SearchOutcome::Solved(solution) => Some(solution),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Moves {
+    Up,
+    Down,
+    Left,
+    Right,
+    Nothing,
+}
+
+impl Moves {
+    pub fn name(self) -> &'static str {
+        match self {
+            Moves::Up => "up",
+            Moves::Down => "down",
+            Moves::Left => "left",
+            Moves::Right => "right",
+            Moves::Nothing => "nothing",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Moves> {
+        match name {
+            "up" => Some(Moves::Up),
+            "down" => Some(Moves::Down),
+            "left" => Some(Moves::Left),
+            "right" => Some(Moves::Right),
+            "nothing" => Some(Moves::Nothing),
+            _ => None,
+        }
+    }
+
+    // the move that takes the space back where it came from, on a board with edges
+    pub fn opposite(self) -> Moves {
+        match self {
+            Moves::Up => Moves::Down,
+            Moves::Down => Moves::Up,
+            Moves::Left => Moves::Right,
+            Moves::Right => Moves::Left,
+            Moves::Nothing => Moves::Nothing,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct EightPuzzleNode {
+    pub id: u64,
+    pub parent: Option<u64>,
+    pub puzzle: EightPuzzle,
+    pub g: u64,
+    pub h: u64,
+    pub depth: u64,
+    pub prev_move: Moves,
+    // the move that made this board, None for the initial one
+    pub slide: Option<Slide>,
+}
+
+// everything one search keeps track of besides its queue, so searches never share counters
+#[derive(Clone)]
+pub struct SearchState {
+    pub next_id: u64,
+    pub stats: SearchStats,
+}
+
+impl SearchState {
+    // hands out a unique id to every node the search creates so traces can link children to parents
+    fn next_node_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
+
+// all a search has made so far, enough to carry on where it stopped (see checkpoint.rs)
+#[derive(Clone)]
+pub struct SearchProgress {
+    pub queue: Vec<EightPuzzleNode>,
+    // the boards expanded, told apart by their colours
+    pub closed: HashSet<EightPuzzle>,
+    // parent and move of every expanded node, enough to walk back from the goal
+    pub expanded: HashMap<u64, (Option<u64>, Option<Slide>)>,
+    pub state: SearchState,
+}
+
+impl SearchProgress {
+    // a search that has only the initial board in its queue
+    fn start(
+        problem: &Problem,
+        heuristic: &dyn Heuristic,
+        recorder: Option<&Mutex<SearchRecorder>>,
+    ) -> SearchProgress {
+        let mut state = SearchState {
+            next_id: 0,
+            stats: SearchStats::default(),
+        };
+
+        let root = EightPuzzleNode {
+            id: state.next_node_id(),
+            parent: None,
+            h: heuristic.estimate(&problem.initial_state, &problem.goal_state),
+            puzzle: problem.initial_state.clone(),
+            g: 0,
+            depth: 0,
+            prev_move: Moves::Nothing,
+            slide: None,
+        };
+        trace_node(recorder, TraceEventKind::Generate, &root);
+
+        SearchProgress {
+            queue: vec![root],
+            closed: HashSet::new(),
+            expanded: HashMap::new(),
+            state,
+        }
+    }
+}
+
+// records an event about a node if a trace file or a search tree export was requested
+fn trace_node(
+    recorder: Option<&Mutex<SearchRecorder>>,
+    kind: TraceEventKind,
+    node: &EightPuzzleNode,
+) {
+    let recorder = match recorder {
+        Some(recorder) => recorder,
+        None => return,
+    };
+    let mut recorder = recorder.lock().unwrap();
+
+    let event = TraceEvent::from_node(kind, node);
+    if let Some(writer) = &mut recorder.trace {
+        writer.record(&event);
+    }
+    if let Some(export) = &mut recorder.tree {
+        export.record(&event);
+    }
+}
+
+// takes a problem and heuristic function
+// returns the goal state and how to get there if we can complete the puzzle, returned None if we cant
+pub fn search(
+    problem: Problem,
+    heuristic: fn(&EightPuzzle, &EightPuzzle) -> u64,
+) -> Option<Solution> {
+    search_with_stats(problem, heuristic).0
+}
+
+// the same search, also giving back how many nodes it expanded and how long the queue got
+pub fn search_with_stats(
+    problem: Problem,
+    heuristic: fn(&EightPuzzle, &EightPuzzle) -> u64,
+) -> (Option<Solution>, SearchStats) {
+    let (outcome, stats) = search_with_options(
+        problem,
+        heuristic,
+        Algorithm::AStar,
+        SearchLimits::default(),
+    );
+    (outcome.into_solution(), stats)
+}
+
+// searches with any of the algorithms and stops early once one of the limits runs out
+pub fn search_with_options(
+    problem: Problem,
+    heuristic: fn(&EightPuzzle, &EightPuzzle) -> u64,
+    algorithm: Algorithm,
+    limits: SearchLimits,
+) -> (SearchOutcome, SearchStats) {
+    search_with_heuristic(problem, &heuristic, algorithm, limits)
+}
+
+// the same search for any Heuristic, like the combinations in heuristic.rs or ones carrying data
+pub fn search_with_heuristic(
+    problem: Problem,
+    heuristic: &dyn Heuristic,
+    algorithm: Algorithm,
+    limits: SearchLimits,
+) -> (SearchOutcome, SearchStats) {
+    let (outcome, progress) = resume_search(problem, heuristic, algorithm, limits, None);
+    (outcome, progress.state.stats)
+}
+
+// the same search, carried on from the progress of an earlier one with the same problem,
+// heuristic and algorithm when there is one. the progress is given back to carry on again later
+pub fn resume_search(
+    problem: Problem,
+    heuristic: &dyn Heuristic,
+    algorithm: Algorithm,
+    limits: SearchLimits,
+    progress: Option<SearchProgress>,
+) -> (SearchOutcome, SearchProgress) {
+    with_search_heuristic(&problem, heuristic, algorithm, |heuristic| {
+        // counts left over from an earlier search with the same heuristic don't belong to this one
+        heuristic.take_max_counts();
+        let mut progress = progress.unwrap_or_else(|| {
+            SearchProgress::start(&problem, heuristic, limits.recorder.as_deref())
+        });
+        let outcome = run_search(&problem, heuristic, limits, algorithm, &mut progress);
+
+        // a search carried on adds to the counts of the part before
+        let counts = heuristic.take_max_counts();
+        let components = &mut progress.state.stats.max_components;
+        if components.len() == counts.len() {
+            for (component, count) in components.iter_mut().zip(counts) {
+                component.count += count.count;
+            }
+        } else {
+            *components = counts;
+        }
+
+        (outcome, progress)
+    })
+}
+
+// hands over the heuristic as the search uses it on a problem, turned to follow its colours, count
+// its slides and price its moves
+pub fn with_search_heuristic<R>(
+    problem: &Problem,
+    heuristic: &dyn Heuristic,
+    algorithm: Algorithm,
+    f: impl FnOnce(&dyn Heuristic) -> R,
+) -> R {
+    let heuristic: &dyn Heuristic = match algorithm {
+        Algorithm::UniformCost => &uniform_search_heuristic,
+        _ => heuristic,
+    };
+
+    // estimates follow the colours, not the tiles
+    let colored;
+    let heuristic: &dyn Heuristic = if problem.colors.is_empty() {
+        heuristic
+    } else {
+        colored = ColoredHeuristic::new(heuristic, &problem.colors);
+        &colored
+    };
+
+    // estimates count moves of one tile, when a move can push several they're turned into slides
+    let counted;
+    let heuristic: &dyn Heuristic = match problem.move_model {
+        MoveModel::SingleTile => heuristic,
+        MoveModel::MultiTile => {
+            counted = SlideCountHeuristic::new(heuristic, &problem.initial_state);
+            &counted
+        }
+    };
+
+    // estimates count moves, when those cost different amounts they're turned into costs
+    let weighted;
+    let heuristic: &dyn Heuristic = if problem.cost_model.is_unit() {
+        heuristic
+    } else {
+        weighted =
+            CostWeightedHeuristic::new(heuristic, &problem.cost_model, &problem.initial_state);
+        &weighted
+    };
+
+    f(heuristic)
+}
+
+fn run_search(
+    problem: &Problem,
+    heuristic: &dyn Heuristic,
+    limits: SearchLimits,
+    algorithm: Algorithm,
+    progress: &mut SearchProgress,
+) -> SearchOutcome {
+    let SearchProgress {
+        queue,
+        closed,
+        expanded,
+        state,
+    } = progress;
+
+    // boards are told apart by their colours, which are the tiles themselves without any
+    let classes = problem.colors.classes();
+    let goal = classes.canonical(&problem.goal_state);
+    let recorder = limits.recorder.as_deref();
+
+    loop {
+        if queue.len() > state.stats.max_queue {
+            state.stats.max_queue = queue.len();
+        }
+
+        if queue.is_empty() {
+            return SearchOutcome::Unsolvable;
+        }
+
+        if let Err(reason) = limits.check(state.stats.expanded) {
+            return SearchOutcome::Stopped(reason);
+        }
+
+        let node = dequeueing_function(queue, algorithm);
+        trace_node(recorder, TraceEventKind::Pop, &node.0);
+
+        let canonical = classes.canonical(&node.0.puzzle);
+        if canonical == goal {
+            let slides = solution_slides(&node.0, expanded);
+            let solution = Solution {
+                moves: slides.iter().map(|slide| slide.direction).collect(),
+                slides,
+                puzzle: node.0.puzzle,
+                cost: node.0.g,
+            };
+            return SearchOutcome::Solved(solution);
+        }
+
+        // the same board can be queued along several paths, only the cheapest one gets expanded
+        if !closed.insert(canonical) {
+            trace_node(recorder, TraceEventKind::Prune, &node.0);
+            continue;
+        }
+        expanded.insert(node.0.id, (node.0.parent, node.0.slide));
+
+        let (node, index) = node;
+        let children = expand_node(node, problem, heuristic, state, recorder);
+        enqueueing_function(queue, closed, &classes, index, children, recorder);
+    }
+}
+
+// follows the parents of the goal node back to the root and gives back the moves in order
+fn solution_slides(
+    goal: &EightPuzzleNode,
+    expanded: &HashMap<u64, (Option<u64>, Option<Slide>)>,
+) -> Vec<Slide> {
+    let mut slides = Vec::new();
+    let mut slide = goal.slide;
+    let mut parent = goal.parent;
+
+    while let Some(id) = parent {
+        slides.extend(slide);
+        let (grandparent, parent_slide) = expanded[&id];
+        parent = grandparent;
+        slide = parent_slide;
+    }
+
+    slides.reverse();
+    slides
+}
+
+// place the children of a previously dequeued node into our queue of nodes, where it was taken from
+// children whose board has already been expanded are pruned instead of queued
+fn enqueueing_function(
+    nodes: &mut Vec<EightPuzzleNode>,
+    closed: &HashSet<EightPuzzle>,
+    classes: &TileClasses,
+    index: usize,
+    mut new_nodes: Vec<EightPuzzleNode>,
+    recorder: Option<&Mutex<SearchRecorder>>,
+) {
+    new_nodes.reverse();
+
+    for element in new_nodes {
+        if closed.contains(&classes.canonical(&element.puzzle)) {
+            trace_node(recorder, TraceEventKind::Prune, &element);
+            continue;
+        }
+
+        trace_node(recorder, TraceEventKind::Generate, &element);
+        nodes.insert(index, element);
+    }
+}
+
+// builds the child reached from a node by one slide, priced by the tiles it pushed
+fn child_node(
+    node: &EightPuzzleNode,
+    puzzle: EightPuzzle,
+    slide: Slide,
+    problem: &Problem,
+    heuristic: &dyn Heuristic,
+) -> EightPuzzleNode {
+    let step_cost = problem
+        .cost_model
+        .slide_cost(&slides::slid_tiles(&node.puzzle, &slide), slide.direction);
+
+    EightPuzzleNode {
+        id: 0,
+        parent: Some(node.id),
+        h: heuristic.estimate(&puzzle, &problem.goal_state),
+        puzzle,
+        g: node.g + step_cost,
+        depth: node.depth + 1,
+        prev_move: slide.direction,
+        slide: Some(slide),
+    }
+}
+
+// takes a node and then depending on the previous move, expand it in different ways
+// for example, if our previous move was to move the space down, we wouldn't want to move it back up
+// this would create unnecessary nodes just ping ponging back and forth
+fn expand_node(
+    node: EightPuzzleNode,
+    problem: &Problem,
+    heuristic: &dyn Heuristic,
+    state: &mut SearchState,
+    recorder: Option<&Mutex<SearchRecorder>>,
+) -> Vec<EightPuzzleNode> {
+    state.stats.expanded += 1;
+
+    if PRINT_EXPANDING_NODES_TOGGLE.load(Ordering::Relaxed) {
+        println!(
+            "Expanding the following state with g(n) = {} and h(n) = {}",
+            node.g, node.h
+        );
+        node.puzzle.print_puzzle();
+        println!();
+    }
+    trace_node(recorder, TraceEventKind::Expand, &node);
+
+    // with more spaces or longer slides every move is tried, the closed set catches the ones that
+    // undo the last
+    if !slides::is_plain(problem) {
+        return slides::slides(&node.puzzle, problem.move_model)
+            .into_iter()
+            .map(|(slide, puzzle)| {
+                let mut element = child_node(&node, puzzle, slide, problem, heuristic);
+                element.id = state.next_node_id();
+                element
+            })
+            .collect();
+    }
+
+    // the plain moves of the one space, named by their direction
+    let blank = node.puzzle.blank_position();
+    let child = |puzzle, direction| {
+        let slide = Slide {
+            row: blank.row,
+            col: blank.col,
+            direction,
+            tiles: 1,
+        };
+        child_node(&node, puzzle, slide, problem, heuristic)
+    };
+
+    let created_nodes = match node.prev_move {
+        Moves::Up => vec![
+            child(node.puzzle.move_up(), Moves::Up),
+            child(node.puzzle.move_left(), Moves::Left),
+            child(node.puzzle.move_right(), Moves::Right),
+        ],
+        Moves::Down => vec![
+            child(node.puzzle.move_down(), Moves::Down),
+            child(node.puzzle.move_left(), Moves::Left),
+            child(node.puzzle.move_right(), Moves::Right),
+        ],
+        Moves::Left => vec![
+            child(node.puzzle.move_up(), Moves::Up),
+            child(node.puzzle.move_down(), Moves::Down),
+            child(node.puzzle.move_left(), Moves::Left),
+        ],
+        Moves::Right => vec![
+            child(node.puzzle.move_up(), Moves::Up),
+            child(node.puzzle.move_down(), Moves::Down),
+            child(node.puzzle.move_right(), Moves::Right),
+        ],
+        Moves::Nothing => vec![
+            child(node.puzzle.move_up(), Moves::Up),
+            child(node.puzzle.move_down(), Moves::Down),
+            child(node.puzzle.move_left(), Moves::Left),
+            child(node.puzzle.move_right(), Moves::Right),
+        ],
+    };
+
+    let mut new_nodes: Vec<EightPuzzleNode> = Vec::new();
+
+    // moving the space into a wall gives back the same board, those aren't real children
+    for mut element in created_nodes {
+        if element.puzzle != node.puzzle {
+            element.id = state.next_node_id();
+            new_nodes.push(element);
+        }
+    }
+
+    new_nodes
+}
+
+// removes a node and give us back both the node and the index with the queue it was taken from
+fn dequeueing_function(
+    nodes: &mut Vec<EightPuzzleNode>,
+    algorithm: Algorithm,
+) -> (EightPuzzleNode, usize) {
+    let mut lowest_cost = (0, u64::MAX); // index, cost
+
+    for (index, element) in nodes.iter().enumerate() {
+        let cost = match algorithm {
+            Algorithm::Greedy => element.h,
+            _ => element.g + element.h,
+        };
+
+        if cost < lowest_cost.1 {
+            lowest_cost = (index, cost);
+        }
+    }
+    let return_node = nodes.remove(lowest_cost.0);
+
+    (return_node, lowest_cost.0)
+}
+
+// uniform search is A* where h(x) is always zero
+pub fn uniform_search_heuristic(_puzzle: &EightPuzzle, _goal: &EightPuzzle) -> u64 {
+    0
+}
+
+// the heuristics by the names used on the command line and in documents
+pub fn heuristic_from_name(name: &str) -> Option<fn(&EightPuzzle, &EightPuzzle) -> u64> {
+    match name {
+        "ucs" => Some(uniform_search_heuristic),
+        "misplaced" => Some(misplaced_tile_heuristic),
+        "manhattan" => Some(manhattan_distance_heuristic),
+        "linear-conflict" => Some(linear_conflict_heuristic),
+        "walking" => Some(walking_distance::walking_distance_heuristic),
+        "walking-linear-conflict" => Some(walking_linear_conflict_heuristic),
+        "gaschnig" => Some(gaschnig_heuristic),
+        "inversion-distance" => Some(inversion_distance_heuristic),
+        _ => None,
+    }
+}
+
+// looks at every tile and checks to see if it's where it's supposed to be
+pub fn misplaced_tile_heuristic(puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+    let mut count = 0;
+
+    for (tile, goal_tile) in puzzle.tiles.iter().zip(goal.tiles.iter()) {
+        if tile != goal_tile {
+            count += 1;
+        }
+    }
+
+    count
+}
+
+// returns the cumulative manhattan distance between the numbers' current spot
+// and where they're supposed to be in the goal
+pub fn manhattan_distance_heuristic(puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+    // tiles of one colour can take any of their goal cells, the cheapest way to share them is used
+    if colors::has_interchangeable_tiles(goal) {
+        return colors::matched_manhattan(puzzle, goal);
+    }
+
+    let mut total_distance = 0;
+
+    // goal_index[tile] is where that tile sits in the goal
+    let mut goal_index = vec![0; goal.tiles.len()];
+    for (index, tile) in goal.tiles.iter().enumerate() {
+        if *tile != WALL {
+            goal_index[*tile as usize] = index;
+        }
+    }
+
+    // around walls the straight line isn't always open, so the shortest way around is counted
+    if walls::has_walls(puzzle) {
+        let distances = walls::cell_distances(puzzle);
+        return puzzle
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| **tile != BLANK && **tile != WALL)
+            .map(|(index, tile)| distances.between(index, goal_index[*tile as usize]))
+            .sum();
+    }
+
+    let wraps = torus::wraps(puzzle);
+    for (index, number) in puzzle.tiles.iter().enumerate() {
+        if *number == BLANK {
+            continue;
+        }
+
+        let x_value = (index % puzzle.cols) as i64;
+        let x_goal = (goal_index[*number as usize] % puzzle.cols) as i64;
+
+        let y_value = (index / puzzle.cols) as i64;
+        let y_goal = (goal_index[*number as usize] / puzzle.cols) as i64;
+
+        let (mut x_distance, mut y_distance) = (abs(x_value - x_goal), abs(y_value - y_goal));
+        // on a torus going the other way round can be shorter
+        if wraps {
+            x_distance = x_distance.min(puzzle.cols as i64 - x_distance);
+            y_distance = y_distance.min(puzzle.rows as i64 - y_distance);
+        }
+        total_distance += x_distance + y_distance;
+    }
+
+    total_distance as u64
+}
+
+// the length of the longest run of values that only goes up, found by patience sorting
+fn longest_increasing_run(values: &[usize]) -> usize {
+    let mut tails: Vec<usize> = Vec::new();
+
+    for value in values {
+        match tails.binary_search(value) {
+            Ok(_) => {}
+            Err(position) if position == tails.len() => tails.push(*value),
+            Err(position) => tails[position] = *value,
+        }
+    }
+
+    tails.len()
+}
+
+// manhattan distance, plus 2 moves for every tile that has to step out of its goal row or column
+// to let another tile in the same line past it
+pub fn linear_conflict_heuristic(puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+    // a wall between two tiles already sends one of them out of the line, so conflicts could count
+    // the same detour twice. on a torus the line is a ring and either tile can go round the other,
+    // and with colours the tile in the way might be one that belongs further along anyway
+    if walls::has_walls(puzzle) || torus::wraps(puzzle) || colors::has_interchangeable_tiles(goal) {
+        return manhattan_distance_heuristic(puzzle, goal);
+    }
+
+    let mut goal_index = vec![0; goal.tiles.len()];
+    for (index, tile) in goal.tiles.iter().enumerate() {
+        goal_index[*tile as usize] = index;
+    }
+
+    let cols = puzzle.cols;
+    let mut conflicts = 0;
+
+    // in each row, the goal columns of the tiles that belong in that row, left to right
+    for row in 0..puzzle.rows {
+        let goal_cols: Vec<usize> = (0..cols)
+            .map(|col| puzzle.get(row, col))
+            .filter(|tile| *tile != BLANK && goal_index[*tile as usize] / cols == row)
+            .map(|tile| goal_index[tile as usize] % cols)
+            .collect();
+        conflicts += goal_cols.len() - longest_increasing_run(&goal_cols);
+    }
+
+    for col in 0..cols {
+        let goal_rows: Vec<usize> = (0..puzzle.rows)
+            .map(|row| puzzle.get(row, col))
+            .filter(|tile| *tile != BLANK && goal_index[*tile as usize] % cols == col)
+            .map(|tile| goal_index[tile as usize] / cols)
+            .collect();
+        conflicts += goal_rows.len() - longest_increasing_run(&goal_rows);
+    }
+
+    manhattan_distance_heuristic(puzzle, goal) + 2 * conflicts as u64
+}
+
+// gaschnig's heuristic: how many swaps it takes to sort the board when any tile may swap with the
+// blank, wherever it is. while the blank is away from its goal spot it swaps with the tile that
+// belongs there, once it's home it swaps with any tile still out of place
+pub fn gaschnig_heuristic(puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+    // with more spaces a tile can jump into whichever is handiest, and with colours it can go to any
+    // cell of its colour. each misplaced one still has to move at least once
+    if slides::space_count(puzzle) > 1 || colors::has_interchangeable_tiles(goal) {
+        return puzzle
+            .tiles
+            .iter()
+            .zip(goal.tiles.iter())
+            .filter(|(tile, goal_tile)| **tile != BLANK && tile != goal_tile)
+            .count() as u64;
+    }
+
+    // position[tile] is where that tile is now
+    let mut position = vec![0; puzzle.tiles.len()];
+    for (index, tile) in puzzle.tiles.iter().enumerate() {
+        if *tile != WALL {
+            position[*tile as usize] = index;
+        }
+    }
+
+    let blank_goal = goal.tiles.iter().position(|tile| *tile == BLANK).unwrap();
+    let mut misplaced = puzzle
+        .tiles
+        .iter()
+        .zip(goal.tiles.iter())
+        .filter(|(tile, goal_tile)| **tile != BLANK && tile != goal_tile)
+        .count();
+    // every spot before this one already holds its goal tile, so the search never goes back
+    let mut next_unsorted = 0;
+    let mut swaps = 0;
+
+    while misplaced > 0 {
+        let blank = position[BLANK as usize];
+
+        let tile = if blank != blank_goal {
+            misplaced -= 1;
+            goal.tiles[blank]
+        } else {
+            while goal.tiles[next_unsorted] == BLANK
+                || goal.tiles[next_unsorted] == WALL
+                || position[goal.tiles[next_unsorted] as usize] == next_unsorted
+            {
+                next_unsorted += 1;
+            }
+            // this tile is still out of place after the swap, so nothing gets sorted
+            goal.tiles[next_unsorted]
+        };
+
+        position[BLANK as usize] = position[tile as usize];
+        position[tile as usize] = blank;
+        swaps += 1;
+    }
+
+    swaps
+}
+
+// the fewest moves that change an inversion count by `inversions`, when one move changes it by at
+// most `jump`. on a full board it always changes by an amount with the same parity as `jump`, walls
+// in between take that away
+fn moves_for_inversions(inversions: u64, jump: u64, same_parity: bool) -> u64 {
+    if jump == 0 {
+        return 0;
+    }
+
+    let mut moves = inversions.div_ceil(jump);
+    if same_parity && jump % 2 == 1 && moves % 2 != inversions % 2 {
+        moves += 1;
+    }
+    moves
+}
+
+// how many pairs of tiles are out of order, reading the board in the given order and ranking each
+// tile by where the goal has it in that same order
+fn inversions_in_order(puzzle: &EightPuzzle, goal: &EightPuzzle, order: &[usize]) -> u64 {
+    let mut rank = vec![0; goal.tiles.len()];
+    for (position, index) in order.iter().enumerate() {
+        if goal.tiles[*index] != WALL {
+            rank[goal.tiles[*index] as usize] = position;
+        }
+    }
+
+    let ranks: Vec<usize> = order
+        .iter()
+        .map(|index| puzzle.tiles[*index])
+        .filter(|tile| *tile != BLANK && *tile != WALL)
+        .map(|tile| rank[tile as usize])
+        .collect();
+
+    let mut inversions = 0;
+    for (i, first) in ranks.iter().enumerate() {
+        inversions += ranks[i + 1..]
+            .iter()
+            .filter(|second| *second < first)
+            .count() as u64;
+    }
+    inversions
+}
+
+// inversion distance: read row by row, only vertical moves reorder the tiles and each one jumps a
+// tile over cols - 1 others, so it fixes at most that many inversions. reading column by column
+// the same goes for horizontal moves. the two kinds of moves are counted apart so they add up
+pub fn inversion_distance_heuristic(puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+    // a move across the edge of a torus jumps a tile from one end of the order to the other, and
+    // with colours there's no one order to sort the tiles into
+    if torus::wraps(puzzle) || colors::has_interchangeable_tiles(goal) {
+        return manhattan_distance_heuristic(puzzle, goal);
+    }
+
+    let (rows, cols) = (puzzle.rows, puzzle.cols);
+
+    let row_major: Vec<usize> = (0..rows * cols).collect();
+    let column_major: Vec<usize> = (0..cols)
+        .flat_map(|col| (0..rows).map(move |row| row * cols + col))
+        .collect();
+
+    let vertical = inversions_in_order(puzzle, goal, &row_major);
+    let horizontal = inversions_in_order(puzzle, goal, &column_major);
+
+    let full = !walls::has_walls(puzzle) && slides::space_count(puzzle) == 1;
+    moves_for_inversions(vertical, cols as u64 - 1, full)
+        + moves_for_inversions(horizontal, rows as u64 - 1, full)
+}
+
+// the two can't be added since both count the same moves, but the larger one is still admissible
+pub fn walking_linear_conflict_heuristic(puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+    walking_distance::walking_distance_heuristic(puzzle, goal)
+        .max(linear_conflict_heuristic(puzzle, goal))
+}
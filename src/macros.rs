@@ -0,0 +1,397 @@
+// macro operators, runs of moves that put one more tile in place without leaving any tile before
+// it out of place, after Korf
+//
+// the tiles are put in place in a fixed order, the space first and then the others in the order
+// of their goal cells. for each of them the table has a macro for every cell it can be in, which
+// starts with the earlier ones all in place (so the space in its goal cell), takes this one to its
+// goal cell and ends with the earlier ones back in theirs. the macros may move the earlier tiles
+// on the way, that's what lets the last tiles of a row in. following the table from any board that
+// can be solved reaches the goal in at most the sum of the longest macro for each tile, without
+// searching at all.
+//
+// the macros for a tile come from one breadth first search out from the goal, over where the
+// space, the earlier tiles and this tile are, ignoring the rest. that's a lot of boards once many
+// tiles are placed, so learning is only practical for boards of around ten cells, a limit on the
+// boards kept stops it before it runs out of memory. tables are saved as documents like those in
+// schema.rs:
+//     {
+//       "version": 1,
+//       "goal": [[1, 2, 3], [4, 5, 6], [7, 8, 0]],
+//       "order": [0, 1, 2, 3, 4, 5, 6, 7, 8],
+//       "macros": [{ "0": "RRDD", ... }, { "1": "LURDDR", ... }, ...]
+//     }
+// with the macros for each tile of the order by the cell it's in, counted from 0 in row order.
+//
+// a search can also be given the table, then the next macro of a board is one more child of it
+// next to the single moves, priced by the moves in it. with A* the answer is still the cheapest,
+// greedy search gets to the goal far sooner with it.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::heuristic::Heuristic;
+use crate::notation::{moves_to_string, parse_moves};
+use crate::schema::SchemaVersion;
+use crate::slides::{self, Slide};
+use crate::walking_distance::DEFAULT_TABLE_DIRECTORY;
+use crate::{
+    torus, walls, with_search_heuristic, Algorithm, EightPuzzle, Moves, Problem, SearchLimits,
+    SearchOutcome, SearchStats, Solution, BLANK,
+};
+
+// how many boards learning keeps for one tile before it gives up, a 3x3 board needs 181440
+pub const DEFAULT_MAX_BOARDS: usize = 2_000_000;
+
+// where the command line keeps the table for a board size when it isn't told otherwise
+pub fn default_path(rows: usize, cols: usize) -> PathBuf {
+    Path::new(DEFAULT_TABLE_DIRECTORY).join(format!("macros-{}x{}.json", rows, cols))
+}
+
+const DIRECTIONS: [Moves; 4] = [Moves::Up, Moves::Down, Moves::Left, Moves::Right];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MacroTable {
+    pub version: SchemaVersion,
+    pub goal: EightPuzzle,
+    // the tiles in the order they're put in place, the space first
+    pub order: Vec<u16>,
+    // for each tile of the order, its macros by the cell it starts in
+    pub macros: Vec<BTreeMap<usize, String>>,
+}
+
+// the boards the tables work for, cells are kept as bytes while learning
+fn check_board(puzzle: &EightPuzzle) -> Result<(), String> {
+    if walls::has_walls(puzzle) || torus::wraps(puzzle) {
+        return Err("macros only work on flat boards without walls".to_string());
+    }
+    if slides::space_count(puzzle) != 1 {
+        return Err("macros only work on boards with one space".to_string());
+    }
+    if puzzle.tiles.len() > u8::MAX as usize {
+        return Err("the board is too big to learn macros for".to_string());
+    }
+    Ok(())
+}
+
+fn cell_of(puzzle: &EightPuzzle, tile: u16) -> usize {
+    puzzle
+        .tiles
+        .iter()
+        .position(|other| *other == tile)
+        .unwrap()
+}
+
+// where the space goes from a cell, None off the edge
+fn neighbour(puzzle: &EightPuzzle, cell: usize, direction: Moves) -> Option<usize> {
+    let (row, col) = (cell / puzzle.cols, cell % puzzle.cols);
+    match direction {
+        Moves::Up if row > 0 => Some(cell - puzzle.cols),
+        Moves::Down if row + 1 < puzzle.rows => Some(cell + puzzle.cols),
+        Moves::Left if col > 0 => Some(cell - 1),
+        Moves::Right if col + 1 < puzzle.cols => Some(cell + 1),
+        _ => None,
+    }
+}
+
+// the cells of the space and the tiles followed after a move, the space is always first
+fn follow_move(puzzle: &EightPuzzle, cells: &[u8], direction: Moves) -> Option<Vec<u8>> {
+    let next = neighbour(puzzle, cells[0] as usize, direction)? as u8;
+    let mut moved = cells.to_vec();
+    if let Some(index) = moved[1..].iter().position(|cell| *cell == next) {
+        moved[index + 1] = cells[0];
+    }
+    moved[0] = next;
+    Some(moved)
+}
+
+// the macros for the last tile in homes, with the ones before it in place
+fn learn_tile(
+    goal: &EightPuzzle,
+    homes: &[u8],
+    max_boards: usize,
+) -> Result<BTreeMap<usize, String>, String> {
+    let placed = &homes[..homes.len() - 1];
+    let wanted = goal.tiles.len() - placed.len();
+    let mut macros = BTreeMap::new();
+
+    // the move that first reached each set of cells, walking them back leads to the goal
+    let mut came_by: HashMap<Vec<u8>, Moves> = HashMap::new();
+    let mut queue = VecDeque::new();
+    came_by.insert(homes.to_vec(), Moves::Nothing);
+    queue.push_back(homes.to_vec());
+
+    while let Some(cells) = queue.pop_front() {
+        let start = cells[placed.len()] as usize;
+        if cells[..placed.len()] == *placed && !macros.contains_key(&start) {
+            let mut moves = Vec::new();
+            let mut at = cells.clone();
+            while at != homes {
+                let back = came_by[&at].opposite();
+                moves.push(back);
+                at = follow_move(goal, &at, back).unwrap();
+            }
+            macros.insert(start, moves_to_string(&moves));
+
+            if macros.len() == wanted {
+                return Ok(macros);
+            }
+        }
+
+        for direction in DIRECTIONS.iter() {
+            let moved = match follow_move(goal, &cells, *direction) {
+                Some(moved) => moved,
+                None => continue,
+            };
+            if !came_by.contains_key(&moved) {
+                if came_by.len() >= max_boards {
+                    return Err(format!("it needs more than {} boards", max_boards));
+                }
+                came_by.insert(moved.clone(), *direction);
+                queue.push_back(moved);
+            }
+        }
+    }
+
+    // the cells left can't be reached, boards with the tile there have the wrong parity
+    Ok(macros)
+}
+
+// learns the macros that lead to a goal, keeping at most max_boards boards for any one tile
+pub fn learn(goal: &EightPuzzle, max_boards: usize) -> Result<MacroTable, String> {
+    check_board(goal)?;
+
+    let mut order = vec![BLANK];
+    order.extend(goal.tiles.iter().cloned().filter(|tile| *tile != BLANK));
+    let homes: Vec<u8> = order
+        .iter()
+        .map(|tile| cell_of(goal, *tile) as u8)
+        .collect();
+
+    let mut macros = Vec::new();
+    for (index, tile) in order.iter().enumerate() {
+        let learned = learn_tile(goal, &homes[..=index], max_boards)
+            .map_err(|error| format!("could not learn the macros for tile {}: {}", tile, error))?;
+        macros.push(learned);
+    }
+
+    Ok(MacroTable {
+        version: SchemaVersion,
+        goal: goal.clone(),
+        order,
+        macros,
+    })
+}
+
+impl MacroTable {
+    // the table has to lead to the problem's goal
+    pub fn check(&self, problem: &Problem) -> Result<(), String> {
+        check_board(&problem.initial_state)?;
+        if !problem.colors.is_empty() {
+            return Err("macros don't know about colours".to_string());
+        }
+        if problem.goal_state != self.goal {
+            return Err(format!(
+                "the macros lead to {}, not {}",
+                self.goal, problem.goal_state
+            ));
+        }
+        if self.macros.len() != self.order.len() {
+            return Err("the table needs macros for every tile of its order".to_string());
+        }
+        Ok(())
+    }
+
+    // the macro for the first tile of the order that isn't in place, None at the goal
+    pub fn next_macro(&self, puzzle: &EightPuzzle) -> Result<Option<Vec<Moves>>, String> {
+        for (tile, macros) in self.order.iter().zip(self.macros.iter()) {
+            let cell = cell_of(puzzle, *tile);
+            if cell == cell_of(&self.goal, *tile) {
+                continue;
+            }
+
+            let text = macros.get(&cell).ok_or_else(|| {
+                format!("the table has no macro for tile {} in cell {}", tile, cell)
+            })?;
+            let moves = parse_moves(text)
+                .map_err(|error| format!("bad macro for tile {}: {}", tile, error))?;
+            return Ok(Some(moves));
+        }
+
+        Ok(None)
+    }
+
+    // the longest each tile's macros get, what following the table can take at most
+    pub fn longest(&self) -> usize {
+        self.macros
+            .iter()
+            .map(|macros| macros.values().map(|text| text.len()).max().unwrap_or(0))
+            .sum()
+    }
+}
+
+fn apply_moves(puzzle: &EightPuzzle, moves: &[Moves]) -> EightPuzzle {
+    moves.iter().fold(puzzle.clone(), |puzzle, direction| {
+        puzzle.apply_move(*direction)
+    })
+}
+
+// follows the table from the problem's board to its goal
+pub fn solve(table: &MacroTable, problem: &Problem) -> Result<Vec<Moves>, String> {
+    table.check(problem)?;
+    if !problem.is_solvable() {
+        return Err("the goal can't be reached from this board".to_string());
+    }
+
+    let mut puzzle = problem.initial_state.clone();
+    let mut moves = Vec::new();
+    // a macro puts at least one more tile in place, so there's never more than one per tile
+    for _ in 0..table.order.len() {
+        match table.next_macro(&puzzle)? {
+            Some(next) => {
+                puzzle = apply_moves(&puzzle, &next);
+                moves.extend(next);
+            }
+            None => return Ok(moves),
+        }
+    }
+
+    match table.next_macro(&puzzle)? {
+        None => Ok(moves),
+        Some(_) => Err("the macros don't reach the goal".to_string()),
+    }
+}
+
+struct MacroNode {
+    puzzle: EightPuzzle,
+    g: u64,
+    parent: Option<usize>,
+    // the moves from the parent, one for a plain move and more for a macro
+    moves: Vec<Moves>,
+}
+
+// the search from lib.rs with the table's next macro as one more child of every board
+pub fn search_with_macros(
+    problem: Problem,
+    heuristic: &dyn Heuristic,
+    table: &MacroTable,
+    algorithm: Algorithm,
+    limits: SearchLimits,
+) -> Result<(SearchOutcome, SearchStats), String> {
+    table.check(&problem)?;
+
+    with_search_heuristic(&problem, heuristic, algorithm, |heuristic| {
+        macro_search(&problem, heuristic, table, algorithm, &limits)
+    })
+}
+
+fn macro_search(
+    problem: &Problem,
+    heuristic: &dyn Heuristic,
+    table: &MacroTable,
+    algorithm: Algorithm,
+    limits: &SearchLimits,
+) -> Result<(SearchOutcome, SearchStats), String> {
+    let goal = &problem.goal_state;
+    let priority = |g: u64, h: u64| match algorithm {
+        Algorithm::Greedy => h,
+        _ => g + h,
+    };
+
+    let mut stats = SearchStats::default();
+    let h = heuristic.estimate(&problem.initial_state, goal);
+    let mut nodes = vec![MacroNode {
+        puzzle: problem.initial_state.clone(),
+        g: 0,
+        parent: None,
+        moves: Vec::new(),
+    }];
+    // ties go to the board closer to the goal, then to the one made first
+    let mut queue = BinaryHeap::new();
+    queue.push(Reverse((priority(0, h), h, 0)));
+    let mut closed: HashSet<EightPuzzle> = HashSet::new();
+
+    loop {
+        stats.max_queue = stats.max_queue.max(queue.len());
+
+        if let Err(reason) = limits.check(stats.expanded) {
+            return Ok((SearchOutcome::Stopped(reason), stats));
+        }
+
+        let index = match queue.pop() {
+            Some(Reverse((_, _, index))) => index,
+            None => return Ok((SearchOutcome::Unsolvable, stats)),
+        };
+
+        if nodes[index].puzzle == *goal {
+            return Ok((SearchOutcome::Solved(solution(&nodes, index)), stats));
+        }
+        if !closed.insert(nodes[index].puzzle.clone()) {
+            continue;
+        }
+        stats.expanded += 1;
+
+        let puzzle = nodes[index].puzzle.clone();
+        let mut children: Vec<Vec<Moves>> = DIRECTIONS
+            .iter()
+            .filter(|direction| puzzle.apply_move(**direction) != puzzle)
+            .map(|direction| vec![*direction])
+            .collect();
+        if let Some(next) = table.next_macro(&puzzle)? {
+            if next.len() > 1 {
+                children.push(next);
+            }
+        }
+
+        for moves in children {
+            let child = apply_moves(&puzzle, &moves);
+            if closed.contains(&child) {
+                continue;
+            }
+
+            let g = nodes[index].g + problem.cost_model.path_cost(&puzzle, &moves);
+            let h = heuristic.estimate(&child, goal);
+            queue.push(Reverse((priority(g, h), h, nodes.len())));
+            nodes.push(MacroNode {
+                puzzle: child,
+                g,
+                parent: Some(index),
+                moves,
+            });
+        }
+    }
+}
+
+// the moves from the root to a node, and the same moves as slides
+fn solution(nodes: &[MacroNode], index: usize) -> Solution {
+    let mut runs = Vec::new();
+    let mut at = Some(index);
+    while let Some(node) = at {
+        runs.push(&nodes[node].moves);
+        at = nodes[node].parent;
+    }
+    let moves: Vec<Moves> = runs.into_iter().rev().flatten().cloned().collect();
+
+    let mut puzzle = nodes[0].puzzle.clone();
+    let mut slides = Vec::new();
+    for direction in moves.iter() {
+        let blank = puzzle.blank_position();
+        slides.push(Slide {
+            row: blank.row,
+            col: blank.col,
+            direction: *direction,
+            tiles: 1,
+        });
+        puzzle = puzzle.apply_move(*direction);
+    }
+
+    Solution {
+        puzzle,
+        moves,
+        slides,
+        cost: nodes[index].g,
+    }
+}
@@ -1,552 +1,83 @@
-extern crate num;
+extern crate ai_homework;
 extern crate rand;
 
-use num::abs;
+mod cli;
+
+use ai_homework::dot::SearchTreeExport;
+use ai_homework::heuristic::{parse_heuristic, Heuristic};
+use ai_homework::schema::{self, ResultDocument};
+use ai_homework::trace::TraceWriter;
+use ai_homework::walking_distance::{self, walking_distance_heuristic};
+use ai_homework::{
+    dot, game, linear_conflict_heuristic, manhattan_distance_heuristic, misplaced_tile_heuristic,
+    notation, parse, playback, search_with_heuristic, slides, uniform_search_heuristic, Algorithm,
+    EightPuzzle, Problem, SearchLimits, SearchRecorder, PRINT_EXPANDING_NODES_TOGGLE,
+};
+use rand::prelude::*;
+use std::env;
 use std::io;
 use std::io::prelude::*;
-use rand::prelude::*;
-
-static mut TOTAL_EXPANSIONS: u64 = 0;
-static mut MAXIMUM_NODES: usize = 0;
-static mut PRINT_EXPANDING_NODES_TOGGLE: bool = true;
-
-struct IndexTuple {
-    row: usize,
-    col: usize,
-}
-
-#[derive(Clone, Copy)]
-struct EightPuzzle {
-    puzzle: [[u8; 3]; 3],
-}
-
-// implementations for printing eight puzzle and moving space around
-impl EightPuzzle {
-    fn print_puzzle(&self) {
-        for row in self.puzzle.iter() {
-            for col in row {
-                if *col == 255 {
-                    print!("\t *")
-                } else {
-                    print!("\t {}", col)
-                }
-            }
-
-            print!("\n")
-        }
-    }
-
-    fn move_up(self) -> EightPuzzle {
-        let mut index = IndexTuple { row: 0, col: 0 };
-
-        for (row, val) in self.puzzle.iter().enumerate() {
-            let retult = val.iter().position(|&x| x == 255);
-
-            match retult {
-                Some(col) => {
-                    index.row = row;
-                    index.col = col;
-                }
-                None => {}
-            }
-        }
-
-        if index.row == 0 {
-            return self.clone();
-        }
-
-        let mut new_puzzle = self.clone();
-
-        new_puzzle.puzzle[index.row][index.col] = new_puzzle.puzzle[index.row - 1][index.col];
-        new_puzzle.puzzle[index.row - 1][index.col] = 255;
-
-        return new_puzzle;
-    }
-
-    fn move_down(self) -> EightPuzzle {
-        let mut index = IndexTuple { row: 0, col: 0 };
-
-        for (row, val) in self.puzzle.iter().enumerate() {
-            let retult = val.iter().position(|&x| x == 255);
-
-            match retult {
-                Some(col) => {
-                    index.row = row;
-                    index.col = col;
-                }
-                None => {}
-            }
-        }
-
-        if index.row == (self.puzzle.len() - 1) {
-            return self.clone();
-        }
-
-        let mut new_puzzle = self.clone();
-
-        new_puzzle.puzzle[index.row][index.col] = new_puzzle.puzzle[index.row + 1][index.col];
-        new_puzzle.puzzle[index.row + 1][index.col] = 255;
-
-        return new_puzzle;
-    }
-
-    fn move_right(self) -> EightPuzzle {
-        let mut index = IndexTuple { row: 0, col: 0 };
-
-        for (row, val) in self.puzzle.iter().enumerate() {
-            let retult = val.iter().position(|&x| x == 255);
-
-            match retult {
-                Some(col) => {
-                    index.row = row;
-                    index.col = col;
-                }
-                None => {}
-            }
-        }
-
-        if index.col == (self.puzzle[0].len() - 1) {
-            return self.clone();
-        }
-
-        let mut new_puzzle = self.clone();
-
-        new_puzzle.puzzle[index.row][index.col] = new_puzzle.puzzle[index.row][index.col + 1];
-        new_puzzle.puzzle[index.row][index.col + 1] = 255;
-
-        return new_puzzle;
-    }
-
-    fn move_left(self) -> EightPuzzle {
-        let mut index = IndexTuple { row: 0, col: 0 };
-
-        for (row, val) in self.puzzle.iter().enumerate() {
-            let retult = val.iter().position(|&x| x == 255);
-
-            match retult {
-                Some(col) => {
-                    index.row = row;
-                    index.col = col;
-                }
-                None => {}
-            }
-        }
-
-        if index.col == 0 {
-            return self.clone();
-        }
-
-        let mut new_puzzle = self.clone();
-
-        new_puzzle.puzzle[index.row][index.col] = new_puzzle.puzzle[index.row][index.col - 1];
-        new_puzzle.puzzle[index.row][index.col - 1] = 255;
-
-        return new_puzzle;
-    }
-}
-
-struct Problem {
-    initial_state: EightPuzzle,
-    goal_state: EightPuzzle,
-}
-
-#[derive(Clone)]
-enum Moves {
-    Up,
-    Down,
-    Left,
-    Right,
-    Nothing,
-}
-
-#[derive(Clone)]
-struct EightPuzzleNode {
-    puzzle: EightPuzzle,
-    g: u64,
-    h: u64,
-    prev_move: Moves,
-}
-
-// takes a problem and heuristic function
-// returns the goal state if we can complete the puzzle, returned None if we cant
-fn search(problem: Problem, heuristic: fn(EightPuzzle, EightPuzzle) -> u64) -> Option<EightPuzzle> {
-    let root = EightPuzzleNode {
-        puzzle: problem.initial_state,
-        g: 0,
-        h: heuristic(problem.initial_state, problem.goal_state),
-        prev_move: Moves::Nothing,
-    };
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
 
-    let mut queue: Vec<EightPuzzleNode> = vec![root];
+// handles taking input and creating a eight puzzle
+// the whole puzzle can be typed on one line, or row by row for a 3x3 board
+// asks again until the input makes a valid puzzle, returns None if the input runs out
+fn build_eightpuzzle_from_input(stdin: &std::io::Stdin) -> Option<EightPuzzle> {
+    println!("Enter your puzzle, use a zero to represent the blank");
 
     loop {
-        unsafe {
-            let queue_length = queue.len();
-            if queue_length > MAXIMUM_NODES as usize {
-                MAXIMUM_NODES = queue_length;
-            }
-        }
-
-        if queue.is_empty() {
+        println!("Enter the whole puzzle on one line, like 1 2 3/4 5 6/7 8 0 or 123456780, or leave blank to enter it row by row: ");
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
             return None;
         }
 
-        let node = dequeueing_function(&mut queue);
-
-        if node.0.puzzle.puzzle == problem.goal_state.puzzle {
-            return Some(node.0.puzzle);
-        }
-
-        enqueueing_function(&mut queue, problem.goal_state, node, heuristic);
-    }
-}
-
-// expand a previously dequeued node and place them into our queue of nodes
-fn enqueueing_function(
-    nodes: &mut Vec<EightPuzzleNode>,
-    goal: EightPuzzle,
-    node: (EightPuzzleNode, usize),
-    heuristic: fn(EightPuzzle, EightPuzzle) -> u64,
-) {
-    let mut new_nodes = expand_node(node.0, goal, heuristic);
-    new_nodes.reverse();
-
-    for element in new_nodes.iter() {
-        nodes.insert(node.1, element.clone());
-    }
-}
-
-// takes a node and then depending on the previous move, expand it in different ways
-// for example, if our previous move was to move the space down, we wouldn't want to move it back up
-// this would create unnecessary nodes just ping ponging back and forth
-fn expand_node(
-    node: EightPuzzleNode,
-    goal: EightPuzzle,
-    heuristic: fn(EightPuzzle, EightPuzzle) -> u64,
-) -> Vec<EightPuzzleNode> {
-    unsafe {
-        TOTAL_EXPANSIONS += 1;
-
-        if PRINT_EXPANDING_NODES_TOGGLE {
-            println!(
-                "Expanding the following state with g(n) = {} and h(n) = {}",
-                node.g, node.h
-            );
-            node.puzzle.print_puzzle();
-            println!();
-        }
-    }
+        let result = if line.trim().is_empty() {
+            let mut rows = Vec::new();
 
-    let mut new_nodes: Vec<EightPuzzleNode> = Vec::new();
-
-    match node.prev_move {
-        Moves::Up => {
-            let up = EightPuzzleNode {
-                puzzle: node.puzzle.move_up(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_up(), goal),
-                prev_move: Moves::Up,
-            };
-            let left = EightPuzzleNode {
-                puzzle: node.puzzle.move_left(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_left(), goal),
-                prev_move: Moves::Left,
-            };
-            let right = EightPuzzleNode {
-                puzzle: node.puzzle.move_right(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_right(), goal),
-                prev_move: Moves::Right,
-            };
-
-            let created_nodes = vec![up, left, right];
-
-            for element in created_nodes {
-                if element.puzzle.puzzle != node.puzzle.puzzle {
-                    new_nodes.push(element);
-                }
-            }
-        }
-        Moves::Down => {
-            let down = EightPuzzleNode {
-                puzzle: node.puzzle.move_down(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_down(), goal),
-                prev_move: Moves::Down,
-            };
-            let left = EightPuzzleNode {
-                puzzle: node.puzzle.move_left(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_left(), goal),
-                prev_move: Moves::Left,
-            };
-            let right = EightPuzzleNode {
-                puzzle: node.puzzle.move_right(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_right(), goal),
-                prev_move: Moves::Right,
-            };
-
-            let created_nodes = vec![down, left, right];
-
-            for element in created_nodes {
-                if element.puzzle.puzzle != node.puzzle.puzzle {
-                    new_nodes.push(element);
-                }
-            }
-        }
-        Moves::Left => {
-            let up = EightPuzzleNode {
-                puzzle: node.puzzle.move_up(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_up(), goal),
-                prev_move: Moves::Up,
-            };
-            let down = EightPuzzleNode {
-                puzzle: node.puzzle.move_down(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_down(), goal),
-                prev_move: Moves::Down,
-            };
-            let left = EightPuzzleNode {
-                puzzle: node.puzzle.move_left(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_left(), goal),
-                prev_move: Moves::Left,
-            };
-
-            let created_nodes = vec![up, down, left];
-
-            for element in created_nodes {
-                if element.puzzle.puzzle != node.puzzle.puzzle {
-                    new_nodes.push(element);
-                }
-            }
-        }
-        Moves::Right => {
-            let up = EightPuzzleNode {
-                puzzle: node.puzzle.move_up(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_up(), goal),
-                prev_move: Moves::Up,
-            };
-            let down = EightPuzzleNode {
-                puzzle: node.puzzle.move_down(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_down(), goal),
-                prev_move: Moves::Down,
-            };
-            let right = EightPuzzleNode {
-                puzzle: node.puzzle.move_right(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_right(), goal),
-                prev_move: Moves::Right,
-            };
-
-            let created_nodes = vec![up, down, right];
-
-            for element in created_nodes {
-                if element.puzzle.puzzle != node.puzzle.puzzle {
-                    new_nodes.push(element);
-                }
-            }
-        }
-        Moves::Nothing => {
-            let up = EightPuzzleNode {
-                puzzle: node.puzzle.move_up(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_up(), goal),
-                prev_move: Moves::Up,
-            };
-            let down = EightPuzzleNode {
-                puzzle: node.puzzle.move_down(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_down(), goal),
-                prev_move: Moves::Down,
-            };
-            let left = EightPuzzleNode {
-                puzzle: node.puzzle.move_left(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_left(), goal),
-                prev_move: Moves::Left,
-            };
-            let right = EightPuzzleNode {
-                puzzle: node.puzzle.move_right(),
-                g: node.g + 1,
-                h: heuristic(node.puzzle.move_right(), goal),
-                prev_move: Moves::Right,
-            };
-
-            let created_nodes = vec![up, down, left, right];
-
-            for element in created_nodes {
-                if element.puzzle.puzzle != node.puzzle.puzzle {
-                    new_nodes.push(element);
+            for name in ["first", "second", "third"].iter() {
+                println!(
+                    "Enter the {} row, use space or tabs between numbers: ",
+                    name
+                );
+                let mut row = String::new();
+                if stdin.lock().read_line(&mut row).unwrap() == 0 {
+                    return None;
                 }
+                rows.push(row);
             }
-        }
-    }
-
-    return new_nodes;
-}
-
-// removes a node and give us back both the node and the index with the queue it was taken from
-fn dequeueing_function(nodes: &mut Vec<EightPuzzleNode>) -> (EightPuzzleNode, usize) {
-    let mut lowest_cost = (0, <u64>::max_value()); // index, cost
-
-    for (index, element) in nodes.iter().enumerate() {
-        let cost = element.g + element.h;
-
-        if cost < lowest_cost.1 {
-            lowest_cost = (index, cost);
-        }
-    }
-    let return_node = nodes.remove(lowest_cost.0);
-
-    return (return_node, lowest_cost.0);
-}
-
-// uniform search is A* where h(x) is always zero
-fn uniform_search_heuristic(_puzzle: EightPuzzle, _goal: EightPuzzle) -> u64 {
-    return 0;
-}
-
-// looks at every tile and checks to see if it's where it's supposed to be
-fn misplaced_tile_heuristic(puzzle: EightPuzzle, goal: EightPuzzle) -> u64 {
-    let mut count = 0;
-
-    let row_count = puzzle.puzzle.len();
-    let col_count = puzzle.puzzle[0].len();
-
-    for i in 0..row_count {
-        for j in 0..col_count {
-            if puzzle.puzzle[i][j] != goal.puzzle[i][j] {
-                count += 1;
-            }
-        }
-    }
-
-    return count;
-}
-
-// returns the cumulative manhattan distance between the numbers' current spot
-// and where they're supposed to be
-fn manhattan_distance_heuristic(puzzle: EightPuzzle, _goal: EightPuzzle) -> u64 {
-    let mut total_distance = 0;
-
-    let row_count = puzzle.puzzle.len();
-    let col_count = puzzle.puzzle[0].len();
-
-    for i in 0..row_count {
-        for j in 0..col_count {
-            let number = puzzle.puzzle[i][j];
-
-            if number == 255 {
-                continue;
-            }
-
-            let x_value = j as i64;
-            let x_goal = ((number - 1) % 3) as i64;
-
-            let y_value = i as i64;
-            let y_goal = ((number as f64 - 1.0) / 3.0).floor() as i64;
-
-            total_distance += abs(x_value - x_goal) + abs(y_value - y_goal);
-        }
-    }
-
-    return total_distance as u64;
-}
-
-// handles taking input and creating a eight puzzle
-fn build_eightpuzzle_from_input(stdin: &std::io::Stdin) -> EightPuzzle {
-    println!("Enter your puzzle, use a zero to represent the blank");
-
-    println!("Enter the first row, use space or tabs between numbers: ");
-    let mut first_row = String::new();;
-    stdin.lock().read_line(&mut first_row).unwrap();
-
-    println!("Enter the second row, use space or tabs between numbers: ");
-    let mut second_row = String::new();;
-    stdin.lock().read_line(&mut second_row).unwrap();
-
-    println!("Enter the third row, use space or tabs between numbers: ");
-    let mut third_row = String::new();;
-    stdin.lock().read_line(&mut third_row).unwrap();
-
-    let first_row_vec: Vec<&str> = first_row.split_whitespace().collect();
-    let second_row_vec: Vec<&str> = second_row.split_whitespace().collect();
-    let third_row_vec: Vec<&str> = third_row.split_whitespace().collect();
 
-    if (first_row_vec.len() != 3) || (second_row_vec.len() != 3) || (third_row_vec.len() != 3) {
-        panic!(
-            "Woops! length is {}, please try again :(",
-            first_row_vec.len()
-        );
-    }
-
-    let first_row_array = [
-        first_row_vec[0].parse::<u8>().unwrap(),
-        first_row_vec[1].parse::<u8>().unwrap(),
-        first_row_vec[2].parse::<u8>().unwrap(),
-    ];
-
-    let second_row_array = [
-        second_row_vec[0].parse::<u8>().unwrap(),
-        second_row_vec[1].parse::<u8>().unwrap(),
-        second_row_vec[2].parse::<u8>().unwrap(),
-    ];
-
-    let third_row_array = [
-        third_row_vec[0].parse::<u8>().unwrap(),
-        third_row_vec[1].parse::<u8>().unwrap(),
-        third_row_vec[2].parse::<u8>().unwrap(),
-    ];
-
-    let mut abcd = EightPuzzle {
-        puzzle: [first_row_array, second_row_array, third_row_array],
-    };
-
-    for row in 0..3 {
-        for col in 0..3 {
-            if abcd.puzzle[row][col] == 0 {
-                abcd.puzzle[row][col] = 255;
+            let rows: Vec<&str> = rows.iter().map(|row| row.as_str()).collect();
+            parse::parse_puzzle_rows(&rows, 3, 3)
+        } else {
+            line.parse::<EightPuzzle>()
+        };
+
+        match result {
+            Ok(puzzle) => return Some(puzzle),
+            Err(errors) => {
+                println!("Woops! that puzzle has some problems:");
+                for error in errors.0.iter() {
+                    println!("\t {}", error);
+                }
+                println!("Please try again :(");
             }
         }
     }
-
-    return abcd;
 }
 
 // let's the user select between some premade eight puzzles
 fn select_prebuild_eightpuzzle(stdin: &std::io::Stdin) -> EightPuzzle {
-    let trivial = EightPuzzle {
-        puzzle: [[1, 2, 3], [4, 5, 6], [7, 8, 255]],
-    };
-
-    let very_easy = EightPuzzle {
-        puzzle: [[1, 2, 3], [4, 5, 6], [7, 255, 8]],
-    };
-
-    let easy = EightPuzzle {
-        puzzle: [[1, 2, 255], [4, 5, 3], [7, 8, 6]],
-    };
-
-    let doable = EightPuzzle {
-        puzzle: [[255, 1, 2], [4, 5, 3], [7, 8, 6]],
-    };
-
-    let oh_boy = EightPuzzle {
-        puzzle: [[8, 7, 1], [6, 255, 2], [5, 4, 3]],
-    };
-
-    let impossible = EightPuzzle {
-        puzzle: [[1, 2, 3], [4, 5, 6], [8, 7, 255]],
-    };
+    let trivial: EightPuzzle = "123456780".parse().unwrap();
+    let very_easy: EightPuzzle = "123456708".parse().unwrap();
+    let easy: EightPuzzle = "120453786".parse().unwrap();
+    let doable: EightPuzzle = "012453786".parse().unwrap();
+    let oh_boy: EightPuzzle = "871602543".parse().unwrap();
+    let impossible: EightPuzzle = "123456870".parse().unwrap();
 
     println!("You wish to use a default puzzle. Please enter a desired difficulty on a scale from 0 to 5.");
-    let mut difficulty_input = String::new();;
+    let mut difficulty_input = String::new();
     stdin.lock().read_line(&mut difficulty_input).unwrap();
 
     let difficulty_number = difficulty_input.trim().parse::<u8>().unwrap();
@@ -554,27 +85,27 @@ fn select_prebuild_eightpuzzle(stdin: &std::io::Stdin) -> EightPuzzle {
     match difficulty_number {
         0 => {
             println!("Difficulty of 'Trivial' selected.");
-            return trivial;
+            trivial
         }
         1 => {
             println!("Difficulty of 'Very Easy' selected.");
-            return very_easy;
+            very_easy
         }
         2 => {
             println!("Difficulty of 'Easy' selected.");
-            return easy;
+            easy
         }
         3 => {
             println!("Difficulty of 'Doable' selected.");
-            return doable;
+            doable
         }
         4 => {
             println!("Difficulty of 'Oh Boy' selected.");
-            return oh_boy;
+            oh_boy
         }
         5 => {
             println!("Difficulty of 'impossible' selected.");
-            return impossible;
+            impossible
         }
         _ => {
             panic!("Woops! invalid difficulty! Try again :(");
@@ -585,99 +116,122 @@ fn select_prebuild_eightpuzzle(stdin: &std::io::Stdin) -> EightPuzzle {
 // build a random eight puzzle
 // this may not always be solvable
 fn select_random_eightpuzzle() -> EightPuzzle {
-    let mut numbers = [0, 1, 2, 3, 4, 5, 6, 7, 8];
+    let mut puzzle = EightPuzzle::solved(3, 3);
 
     let mut rng = thread_rng();
-    rng.shuffle(&mut numbers);
+    rng.shuffle(&mut puzzle.tiles);
 
-    let first_row_array = [numbers[0], numbers[1], numbers[2]];
-    let second_row_array = [numbers[3], numbers[4], numbers[5]];
-    let third_row_array = [numbers[6], numbers[7], numbers[8]];
+    puzzle
+}
 
-    let mut eight_puzzle = EightPuzzle {
-        puzzle: [first_row_array, second_row_array, third_row_array],
-    };
+// a problem with the usual goal for a board the size of the puzzle
+fn usual_problem(initial_state: EightPuzzle) -> Problem {
+    let goal_state = EightPuzzle::solved(initial_state.rows, initial_state.cols);
+    Problem::new(initial_state, goal_state)
+}
 
-    for row in 0..3 {
-        for col in 0..3 {
-            if eight_puzzle.puzzle[row][col] == 0 {
-                eight_puzzle.puzzle[row][col] = 255;
+// reads the initial and goal boards from a problem document
+// asks again until the file can be used, returns None if the input runs out
+fn load_problem_file(stdin: &std::io::Stdin) -> Option<Problem> {
+    loop {
+        println!("Enter the problem file to load (.json, or .yaml and .yml for YAML): ");
+        let mut path = String::new();
+        if stdin.lock().read_line(&mut path).unwrap() == 0 {
+            return None;
+        }
+
+        let path = path.trim();
+        match schema::read_problem(std::path::Path::new(path)) {
+            Ok(problem) => return Some(problem),
+            Err(error) => {
+                println!("Woops! could not load the problem {}: {}", path, error);
+                println!("Please try again :(");
             }
         }
     }
-
-    return eight_puzzle
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() > 1 {
+        cli::run(&args);
+        return;
+    }
+
     let stdin = io::stdin();
+    walking_distance::set_table_directory(Some(walking_distance::DEFAULT_TABLE_DIRECTORY.into()));
 
     println!("Welcome to Christian Pomales's 8-puzzle solver");
-    println!("Type \"1\" to use the default puzzle, \"2\" to enter your own puzzle, or \"3\" to generate a random puzzle");
+    println!("Type \"1\" to use the default puzzle, \"2\" to enter your own puzzle, \"3\" to generate a random puzzle, or \"4\" to load a problem file");
 
-    let mut puzzle_option = String::new();;
+    let mut puzzle_option = String::new();
     stdin.lock().read_line(&mut puzzle_option).unwrap();
 
-    let goal_state = EightPuzzle {
-        puzzle: [[1, 2, 3], [4, 5, 6], [7, 8, 255]],
-    };
-
-    let initial_state: EightPuzzle;
-    let problem: Problem;
-    match puzzle_option.as_str() {
-        "1\n" => {
-            initial_state = select_prebuild_eightpuzzle(&stdin);
-
-            problem = Problem {
-                initial_state: initial_state,
-                goal_state: goal_state,
-            };
-        }
-        "2\n" => {
-            initial_state = build_eightpuzzle_from_input(&stdin);
-
-            problem = Problem {
-                initial_state: initial_state,
-                goal_state: goal_state,
-            };
-        }
-        "3\n" => {
-            initial_state = select_random_eightpuzzle();
-
-            problem = Problem {
-                initial_state: initial_state,
-                goal_state: goal_state,
-            };
-        }
+    let problem = match puzzle_option.as_str() {
+        "1\n" => usual_problem(select_prebuild_eightpuzzle(&stdin)),
+        "2\n" => match build_eightpuzzle_from_input(&stdin) {
+            Some(puzzle) => usual_problem(puzzle),
+            None => return,
+        },
+        "3\n" => usual_problem(select_random_eightpuzzle()),
+        "4\n" => match load_problem_file(&stdin) {
+            Some(problem) => problem,
+            None => return,
+        },
         _ => {
             println!("invalid algorithm choice!");
-            return ();
+            return;
         }
-    }
+    };
+    let initial_state = problem.initial_state.clone();
+    let goal_state = problem.goal_state.clone();
 
     println!("Initial State:");
-    &problem.initial_state.print_puzzle();
-    println!("");
+    problem.initial_state.print_puzzle();
+    println!();
 
     println!("Enter your choice of algorithm");
     println!("1. \t Uniform Cost Search");
     println!("2. \t A* with the Misplaced Tile heuristic.");
     println!("3. \t A* with the Manhattan distance heuristic.");
-    println!("");
+    println!("4. \t Play it yourself, with hints from A* with the Manhattan distance heuristic.");
+    println!("5. \t A* with the Manhattan distance and linear conflict heuristic.");
+    println!("6. \t A* with the walking distance heuristic.");
+    println!("7. \t A* with the larger of walking distance and linear conflict.");
+    println!();
 
-    let mut heuristic_input = String::new();;
+    let mut heuristic_input = String::new();
     stdin.lock().read_line(&mut heuristic_input).unwrap();
 
-    let heuristic: fn(EightPuzzle, EightPuzzle) -> u64;
-    match heuristic_input.as_str() {
-        "1\n" => heuristic = uniform_search_heuristic,
-        "2\n" => heuristic = misplaced_tile_heuristic,
-        "3\n" => heuristic = manhattan_distance_heuristic,
+    // the name is the one the command line uses, it goes into saved results
+    let (heuristic_name, heuristic): (&str, Box<dyn Heuristic>) = match heuristic_input.as_str() {
+        "1\n" => ("ucs", Box::new(uniform_search_heuristic)),
+        "2\n" => ("misplaced", Box::new(misplaced_tile_heuristic)),
+        "3\n" => ("manhattan", Box::new(manhattan_distance_heuristic)),
+        "4\n" => {
+            PRINT_EXPANDING_NODES_TOGGLE.store(false, Ordering::Relaxed);
+
+            // the keys move the one space a tile at a time
+            if !slides::is_plain(&problem) {
+                println!("Woops! the game only plays puzzles with one space that move one tile at a time");
+                return;
+            }
+            if let Err(error) = game::play(&problem) {
+                println!("Woops! could not start the game: {}", error);
+            }
+            return;
+        }
+        "5\n" => ("linear-conflict", Box::new(linear_conflict_heuristic)),
+        "6\n" => ("walking", Box::new(walking_distance_heuristic)),
+        "7\n" => {
+            let name = "max(walking, linear-conflict)";
+            (name, parse_heuristic(name).expect("a known heuristic"))
+        }
         _ => {
             println!("invalid algorithm choice!");
-            return ();
+            return;
         }
-    }
+    };
 
     println!("Type \"1\" to print each node expansion, or \"2\" to not");
     let mut print_expanding_nodes_toggle = String::new();
@@ -686,34 +240,210 @@ fn main() {
         .read_line(&mut print_expanding_nodes_toggle)
         .unwrap();
 
-    unsafe {
-        match print_expanding_nodes_toggle.trim().parse::<u8>().unwrap() {
-            1 => PRINT_EXPANDING_NODES_TOGGLE = true,
-            2 => PRINT_EXPANDING_NODES_TOGGLE = false,
+    match print_expanding_nodes_toggle.trim().parse::<u8>().unwrap() {
+        1 => PRINT_EXPANDING_NODES_TOGGLE.store(true, Ordering::Relaxed),
+        2 => PRINT_EXPANDING_NODES_TOGGLE.store(false, Ordering::Relaxed),
+        _ => {
+            println!("invalid choice!");
+            return;
+        }
+    }
+
+    println!("Enter a file to write a search trace to (.jsonl for JSON Lines, anything else for binary), or leave blank to skip");
+    let mut trace_path = String::new();
+    stdin.lock().read_line(&mut trace_path).unwrap();
+
+    let trace_path = trace_path.trim();
+    let mut recorder = SearchRecorder::default();
+    if !trace_path.is_empty() {
+        match TraceWriter::create(std::path::Path::new(trace_path)) {
+            Ok(writer) => recorder.trace = Some(writer),
+            Err(error) => {
+                println!(
+                    "Woops! could not create the trace {}: {}",
+                    trace_path, error
+                );
+                return;
+            }
+        }
+    }
+
+    println!(
+        "Enter a file to export the search tree to as a Graphviz DOT graph, or leave blank to skip"
+    );
+    let mut dot_path = String::new();
+    stdin.lock().read_line(&mut dot_path).unwrap();
+
+    let dot_path = dot_path.trim();
+    if !dot_path.is_empty() {
+        println!(
+            "Enter the most nodes and the deepest depth to export, or leave blank for {} and {}",
+            dot::DEFAULT_MAX_NODES,
+            dot::DEFAULT_MAX_DEPTH
+        );
+        let mut caps_input = String::new();
+        stdin.lock().read_line(&mut caps_input).unwrap();
+
+        let caps: Vec<&str> = caps_input.split_whitespace().collect();
+        let (max_nodes, max_depth) = match caps.len() {
+            0 => (dot::DEFAULT_MAX_NODES, dot::DEFAULT_MAX_DEPTH),
+            2 => match (caps[0].parse::<usize>(), caps[1].parse::<u64>()) {
+                (Ok(max_nodes), Ok(max_depth)) => (max_nodes, max_depth),
+                _ => {
+                    println!("invalid export limits!");
+                    return;
+                }
+            },
             _ => {
-                println!("invalid choice!");
-                return ();
+                println!("invalid export limits!");
+                return;
             }
+        };
+
+        recorder.tree = Some(
+            SearchTreeExport::new(problem.goal_state.clone(), max_nodes, max_depth)
+                .with_colors(&problem.colors),
+        );
+    }
+
+    let recorder = Arc::new(Mutex::new(recorder));
+    let limits = SearchLimits {
+        recorder: Some(recorder.clone()),
+        ..SearchLimits::default()
+    };
+    let (outcome, stats) =
+        search_with_heuristic(problem.clone(), &*heuristic, Algorithm::AStar, limits);
+    let answer = outcome.solution();
+
+    let mut recorder = recorder.lock().unwrap();
+    if let Some(writer) = recorder.trace.take() {
+        match writer.finish() {
+            Ok(()) => println!("Search trace written to {}", trace_path),
+            Err(error) => println!(
+                "Woops! could not write the search trace {}: {}",
+                trace_path, error
+            ),
         }
     }
 
-    let answer = search(problem, heuristic);
+    if let Some(export) = recorder.tree.take() {
+        match export.write(std::path::Path::new(dot_path)) {
+            Ok(left_out) => {
+                println!("Search tree written to {}", dot_path);
+                if left_out.omitted > 0 {
+                    println!(
+                        "{} nodes were left out of the graph, raise the node cap to see them",
+                        left_out.omitted
+                    );
+                }
+                if left_out.deeper > 0 {
+                    println!(
+                        "{} nodes were deeper than the depth cap, raise it to see them",
+                        left_out.deeper
+                    );
+                }
+            }
+            Err(error) => println!(
+                "Woops! could not write the search tree {}: {}",
+                dot_path, error
+            ),
+        }
+    }
 
     println!("Goal!!");
-    match answer {
-        Some(puzzle) => puzzle.print_puzzle(),
+    match &answer {
+        Some(solution) => {
+            solution.puzzle.print_puzzle();
+
+            let move_names: Vec<&str> = solution.moves.iter().map(|m| m.name()).collect();
+            println!(
+                "The space was moved {} times: {}",
+                solution.moves.len(),
+                move_names.join(", ")
+            );
+            println!(
+                "In move notation: {}",
+                notation::moves_to_string(&solution.moves)
+            );
+        }
         None => println!("no answer :("),
     }
 
-    unsafe {
-        println!("");
+    println!();
+    println!(
+        "To solve this problem the search algorithm expanded a total of {} nodes",
+        stats.expanded
+    );
+    println!(
+        "The maximum number of nodes in the queue at any one time was {}",
+        stats.max_queue
+    );
+    for component in stats.max_components.iter() {
         println!(
-            "To solve this problem the search algorithm expanded a total of {} nodes",
-            TOTAL_EXPANSIONS
+            "{} gave the largest estimate {} times",
+            component.name, component.count
         );
+    }
+
+    println!();
+    println!("Enter a file to save the result to (.json, or .yaml and .yml for YAML), or leave blank to skip");
+    let mut result_path = String::new();
+    stdin.lock().read_line(&mut result_path).unwrap();
+
+    let result_path = result_path.trim();
+    if !result_path.is_empty() {
+        let result =
+            ResultDocument::new(&problem, Algorithm::AStar, heuristic_name, &outcome, stats);
+        match schema::write_document(std::path::Path::new(result_path), &result) {
+            Ok(()) => println!("Result written to {}", result_path),
+            Err(error) => println!(
+                "Woops! could not write the result {}: {}",
+                result_path, error
+            ),
+        }
+    }
+
+    if let Some(solution) = answer {
+        // playback follows the moves of the one space, a tile at a time
+        if !slides::is_plain(&problem) {
+            return;
+        }
+
+        println!();
+        println!("Type \"1\" to play back the solution, or anything else to quit");
+        let mut playback_option = String::new();
+        stdin.lock().read_line(&mut playback_option).unwrap();
+
+        if playback_option.trim() != "1" {
+            return;
+        }
+
         println!(
-            "The maximum number of nodes in the queue at any one time was {}",
-            MAXIMUM_NODES
+            "Enter the delay between moves in milliseconds, or leave blank for {}",
+            playback::DEFAULT_DELAY_MS
         );
+        let mut delay_input = String::new();
+        stdin.lock().read_line(&mut delay_input).unwrap();
+
+        let delay_ms = match delay_input.trim() {
+            "" => playback::DEFAULT_DELAY_MS,
+            delay => match delay.parse::<u64>() {
+                Ok(delay_ms) => delay_ms,
+                Err(_) => {
+                    println!("invalid delay!");
+                    return;
+                }
+            },
+        };
+
+        if let Err(error) = playback::play(
+            initial_state,
+            goal_state,
+            &solution.moves,
+            &*heuristic,
+            delay_ms,
+        ) {
+            println!("Woops! could not play back the solution: {}", error);
+        }
     }
 }
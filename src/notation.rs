@@ -0,0 +1,227 @@
+// one line text forms for puzzles and move sequences, for the command line, logs and files
+//
+// puzzles are written row by row with a / between rows and spaces between tiles, 0 is the space:
+//     1 2 3/4 5 6/7 8 0
+// square boards can also be written as one row, and when every tile is a single digit the spaces
+// can be left out too:
+//     1 2 3 4 5 6 7 8 0
+//     123456780
+// a board read with parse_puzzle and Spaces::Several can have more than one 0
+// moves of the space are written with one letter each, so a solution reads like "RRDD"
+//
+// slides (see slides.rs) also say where the space starts, its row and column counted from 1, and
+// how many tiles it pushes when that's more than one, split by spaces:
+//     3,3U 1,2D2 2,1R
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::parse::{parse_puzzle_rows_with, PuzzleError, PuzzleErrors, Spaces};
+use crate::slides::Slide;
+use crate::{EightPuzzle, Moves, WALL};
+
+// the canonical form, always with separators so it works for any size
+impl fmt::Display for EightPuzzle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows: Vec<String> = self
+            .tiles
+            .chunks(self.cols)
+            .map(|row| {
+                let tiles: Vec<String> = row
+                    .iter()
+                    .map(|tile| match *tile {
+                        WALL => "#".to_string(),
+                        tile => tile.to_string(),
+                    })
+                    .collect();
+                tiles.join(" ")
+            })
+            .collect();
+
+        write!(f, "{}", rows.join("/"))
+    }
+}
+
+// reads a board with one space
+impl FromStr for EightPuzzle {
+    type Err = PuzzleErrors;
+
+    fn from_str(text: &str) -> Result<EightPuzzle, PuzzleErrors> {
+        parse_puzzle(text, Spaces::One)
+    }
+}
+
+pub fn parse_puzzle(text: &str, spaces: Spaces) -> Result<EightPuzzle, PuzzleErrors> {
+    let text = text.trim();
+
+    if text.contains('/') {
+        let rows: Vec<&str> = text.split('/').collect();
+        let cols = rows[0].split_whitespace().count();
+        return parse_puzzle_rows_with(&rows, rows.len(), cols, spaces);
+    }
+
+    let tokens: Vec<String> = if text.contains(char::is_whitespace) {
+        text.split_whitespace()
+            .map(|token| token.to_string())
+            .collect()
+    } else {
+        text.chars().map(|c| c.to_string()).collect()
+    };
+
+    let side = (tokens.len() as f64).sqrt().round() as usize;
+    if side < 2 || side * side != tokens.len() {
+        return Err(PuzzleErrors(vec![PuzzleError::UnknownShape {
+            tiles: tokens.len(),
+        }]));
+    }
+
+    let rows: Vec<String> = tokens.chunks(side).map(|row| row.join(" ")).collect();
+    let rows: Vec<&str> = rows.iter().map(|row| row.as_str()).collect();
+    parse_puzzle_rows_with(&rows, side, side, spaces)
+}
+
+impl Moves {
+    pub fn letter(self) -> char {
+        match self {
+            Moves::Up => 'U',
+            Moves::Down => 'D',
+            Moves::Left => 'L',
+            Moves::Right => 'R',
+            Moves::Nothing => '.',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Moves> {
+        match letter.to_ascii_uppercase() {
+            'U' => Some(Moves::Up),
+            'D' => Some(Moves::Down),
+            'L' => Some(Moves::Left),
+            'R' => Some(Moves::Right),
+            _ => None,
+        }
+    }
+}
+
+pub fn moves_to_string(moves: &[Moves]) -> String {
+    moves.iter().map(|space_move| space_move.letter()).collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseMovesError {
+    // counted from 1, like a column in an editor
+    pub position: usize,
+    pub found: char,
+}
+
+impl fmt::Display for ParseMovesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "character {}: '{}' is not a move, use U, D, L or R",
+            self.position, self.found
+        )
+    }
+}
+
+impl Error for ParseMovesError {}
+
+// letters can be upper or lower case and whitespace between them is ignored
+pub fn parse_moves(text: &str) -> Result<Vec<Moves>, ParseMovesError> {
+    let mut moves = Vec::new();
+
+    for (index, letter) in text.chars().enumerate() {
+        if letter.is_whitespace() {
+            continue;
+        }
+
+        match Moves::from_letter(letter) {
+            Some(space_move) => moves.push(space_move),
+            None => {
+                return Err(ParseMovesError {
+                    position: index + 1,
+                    found: letter,
+                })
+            }
+        }
+    }
+
+    Ok(moves)
+}
+
+pub fn slides_to_string(slides: &[Slide]) -> String {
+    let slides: Vec<String> = slides
+        .iter()
+        .map(|slide| {
+            let tiles = if slide.tiles == 1 {
+                String::new()
+            } else {
+                slide.tiles.to_string()
+            };
+            format!(
+                "{},{}{}{}",
+                slide.row + 1,
+                slide.col + 1,
+                slide.direction.letter(),
+                tiles
+            )
+        })
+        .collect();
+
+    slides.join(" ")
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseSlidesError {
+    // counted from 1
+    pub slide: usize,
+    pub text: String,
+}
+
+impl fmt::Display for ParseSlidesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "slide {}: \"{}\" is not a slide, write them like 2,3U or 2,3U2",
+            self.slide, self.text
+        )
+    }
+}
+
+impl Error for ParseSlidesError {}
+
+fn parse_slide(text: &str) -> Option<Slide> {
+    let (row, rest) = text.split_once(',')?;
+    let letter_at = rest.find(|c: char| c.is_ascii_alphabetic())?;
+    let (col, rest) = rest.split_at(letter_at);
+    let mut letters = rest.chars();
+    let direction = Moves::from_letter(letters.next()?)?;
+    let tiles = match letters.as_str() {
+        "" => 1,
+        count => count.parse().ok().filter(|tiles| *tiles > 0)?,
+    };
+
+    let (row, col): (usize, usize) = (row.parse().ok()?, col.parse().ok()?);
+    if row == 0 || col == 0 {
+        return None;
+    }
+
+    Some(Slide {
+        row: row - 1,
+        col: col - 1,
+        direction,
+        tiles,
+    })
+}
+
+pub fn parse_slides(text: &str) -> Result<Vec<Slide>, ParseSlidesError> {
+    text.split_whitespace()
+        .enumerate()
+        .map(|(index, slide)| {
+            parse_slide(slide).ok_or_else(|| ParseSlidesError {
+                slide: index + 1,
+                text: slide.to_string(),
+            })
+        })
+        .collect()
+}
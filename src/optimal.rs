@@ -0,0 +1,263 @@
+// every cheapest way to the goal, not just the one a search finds
+//
+// A* gives the cheapest cost first. the boards that lie on some path of that cost are then found
+// by a depth first search from the initial board that prunes any board whose g(n) + h(n) goes
+// over it, remembering what each board reached with so much cost left came to. the boards it
+// keeps and the moves between them that stay on a cheapest path make up the optimal subgraph, and
+// the number of cheapest paths from a board is the sum of those of the boards its moves lead to,
+// 1 at the goal. counting them that way takes time for each board of the subgraph instead of for
+// each path, of which there can be far more.
+//
+// the paths themselves are walked out of the subgraph one at a time, each in as many steps as it
+// has moves, since every board in it leads on to the goal.
+//
+// the pruning needs a heuristic that never says more than the cost still to go, like manhattan
+// distance, linear conflict or walking distance. boards with several spaces, multi-tile moves,
+// colours and costed moves are all counted the way the search would solve them, as long as every
+// move costs something: one that's free can be made back and forth forever on a cheapest path.
+// two moves are different when their slides are, even if they end on the same board.
+
+use std::collections::HashMap;
+
+use crate::colors::TileClasses;
+use crate::heuristic::Heuristic;
+use crate::slides::{self, Slide};
+use crate::{
+    search_with_heuristic, with_search_heuristic, Algorithm, EightPuzzle, Moves, Problem,
+    SearchLimits, SearchOutcome, SearchStats, StopReason, BLANK, WALL,
+};
+
+// the boards on the cheapest paths to the goal and the moves between them
+pub struct OptimalGraph {
+    // what each of the paths costs
+    pub cost: u64,
+    nodes: Vec<OptimalNode>,
+    start: usize,
+}
+
+struct OptimalNode {
+    // how many cheapest paths go from here to the goal
+    count: u128,
+    // the moves that stay on one, and the index of the board each leads to
+    next: Vec<(Slide, usize)>,
+}
+
+pub enum OptimalOutcome {
+    Found(OptimalGraph),
+    Unsolvable,
+    Stopped(StopReason),
+}
+
+impl OptimalOutcome {
+    pub fn graph(&self) -> Option<&OptimalGraph> {
+        match self {
+            OptimalOutcome::Found(graph) => Some(graph),
+            _ => None,
+        }
+    }
+}
+
+impl OptimalGraph {
+    // how many different cheapest paths there are, u128::MAX if there are more than that
+    pub fn count(&self) -> u128 {
+        self.nodes[self.start].count
+    }
+
+    // how many boards lie on one of them
+    pub fn boards(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // the paths one at a time as slides, each only made when it's asked for
+    pub fn paths(&self) -> OptimalPaths<'_> {
+        OptimalPaths {
+            graph: self,
+            stack: Vec::new(),
+            slides: Vec::new(),
+            started: false,
+        }
+    }
+}
+
+// the cheapest paths in the order of the moves from each board, see slides::slides
+pub struct OptimalPaths<'a> {
+    graph: &'a OptimalGraph,
+    // the boards along the path so far, with the move being followed from each
+    stack: Vec<(usize, usize)>,
+    slides: Vec<Slide>,
+    started: bool,
+}
+
+impl<'a> Iterator for OptimalPaths<'a> {
+    type Item = Vec<Slide>;
+
+    fn next(&mut self) -> Option<Vec<Slide>> {
+        let nodes = &self.graph.nodes;
+
+        if !self.started {
+            self.started = true;
+            self.stack.push((self.graph.start, 0));
+        } else {
+            // back from the goal to the last board with a move not yet followed
+            self.stack.pop();
+            self.slides.pop();
+            loop {
+                let (node, next) = self.stack.last_mut()?;
+                *next += 1;
+                if *next < nodes[*node].next.len() {
+                    break;
+                }
+                self.stack.pop();
+                self.slides.pop();
+            }
+        }
+
+        // down the first moves to the goal, where there are no more
+        loop {
+            let (node, next) = *self.stack.last()?;
+            match nodes[node].next.get(next) {
+                Some((slide, child)) => {
+                    self.slides.push(*slide);
+                    self.stack.push((*child, 0));
+                }
+                None => return Some(self.slides.clone()),
+            }
+        }
+    }
+}
+
+struct Counter<'a> {
+    problem: &'a Problem,
+    heuristic: &'a dyn Heuristic,
+    classes: TileClasses,
+    goal: EightPuzzle,
+    limits: SearchLimits,
+    stats: SearchStats,
+    // what each board reached with so much cost left came to, None when it leads to no cheapest path
+    seen: HashMap<(EightPuzzle, u64), Option<usize>>,
+    nodes: Vec<OptimalNode>,
+}
+
+impl<'a> Counter<'a> {
+    // the index of the board in the subgraph, None if no path from it reaches the goal for exactly
+    // what's left. a cheapest path never passes the goal before its end, that would be cheaper
+    fn visit(&mut self, puzzle: &EightPuzzle, left: u64) -> Result<Option<usize>, StopReason> {
+        if let Some(node) = self.seen.get(&(puzzle.clone(), left)) {
+            return Ok(*node);
+        }
+
+        let node = if self.classes.canonical(puzzle) == self.goal {
+            if left == 0 {
+                Some(self.add(OptimalNode {
+                    count: 1,
+                    next: Vec::new(),
+                }))
+            } else {
+                None
+            }
+        } else {
+            self.limits.check(self.stats.expanded)?;
+            self.stats.expanded += 1;
+
+            let mut count: u128 = 0;
+            let mut next = Vec::new();
+            for (slide, child) in slides::slides(puzzle, self.problem.move_model) {
+                let cost = self
+                    .problem
+                    .cost_model
+                    .slide_cost(&slides::slid_tiles(puzzle, &slide), slide.direction);
+                if cost > left {
+                    continue;
+                }
+                let child_left = left - cost;
+                if self.heuristic.estimate(&child, &self.problem.goal_state) > child_left {
+                    continue;
+                }
+
+                if let Some(index) = self.visit(&child, child_left)? {
+                    count = count.saturating_add(self.nodes[index].count);
+                    next.push((slide, index));
+                }
+            }
+
+            if next.is_empty() {
+                None
+            } else {
+                Some(self.add(OptimalNode { count, next }))
+            }
+        };
+
+        self.seen.insert((puzzle.clone(), left), node);
+        Ok(node)
+    }
+
+    fn add(&mut self, node: OptimalNode) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+}
+
+// whether some tile of the board can be slid for nothing
+fn has_free_moves(problem: &Problem) -> bool {
+    let directions = [Moves::Up, Moves::Down, Moves::Left, Moves::Right];
+    problem
+        .initial_state
+        .tiles
+        .iter()
+        .filter(|tile| **tile != BLANK && **tile != WALL)
+        .any(|tile| {
+            directions
+                .iter()
+                .any(|direction| problem.cost_model.move_cost(*tile, *direction) == 0)
+        })
+}
+
+// finds the cheapest cost with A* and then every path of that cost. the counts are the nodes
+// expanded by both searches, the limits cover the two together
+pub fn optimal_paths(
+    problem: &Problem,
+    heuristic: &dyn Heuristic,
+    limits: SearchLimits,
+) -> Result<(OptimalOutcome, SearchStats), String> {
+    if has_free_moves(problem) {
+        return Err("a move that costs nothing makes endless cheapest paths".to_string());
+    }
+
+    let (outcome, stats) =
+        search_with_heuristic(problem.clone(), heuristic, Algorithm::AStar, limits.clone());
+    let cost = match outcome {
+        SearchOutcome::Solved(solution) => solution.cost,
+        SearchOutcome::Unsolvable => return Ok((OptimalOutcome::Unsolvable, stats)),
+        SearchOutcome::Stopped(reason) => return Ok((OptimalOutcome::Stopped(reason), stats)),
+    };
+
+    with_search_heuristic(problem, heuristic, Algorithm::AStar, |heuristic| {
+        let classes = problem.colors.classes();
+        let mut counter = Counter {
+            problem,
+            heuristic,
+            goal: classes.canonical(&problem.goal_state),
+            classes,
+            limits,
+            stats,
+            seen: HashMap::new(),
+            nodes: Vec::new(),
+        };
+
+        let outcome = match counter.visit(&problem.initial_state, cost) {
+            Ok(Some(start)) => OptimalOutcome::Found(OptimalGraph {
+                cost,
+                nodes: counter.nodes,
+                start,
+            }),
+            // A* found a path of this cost, only a heuristic that says too much can miss it
+            Ok(None) => {
+                return Err(
+                    "the heuristic says more than the cost left on a cheapest path".to_string(),
+                )
+            }
+            Err(reason) => OptimalOutcome::Stopped(reason),
+        };
+        Ok((outcome, counter.stats))
+    })
+}
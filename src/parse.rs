@@ -0,0 +1,297 @@
+// turns rows of typed numbers into a puzzle of any size, reporting everything wrong with them
+// instead of panicking
+//
+// problems with the shape of the input (wrong number of rows or tiles, tokens that aren't numbers
+// or are out of range) are reported first since the board can't be checked without a full grid.
+// once the grid is complete every missing, duplicated or extra blank tile is reported at once.
+//
+// a # is a wall (see walls.rs), the tiles then only go up to the number of open cells. a board has
+// one 0 unless it's read with Spaces::Several (see slides.rs), then every 0 takes the place of a
+// tile. a typo like "1 2 3/4 5 6/7 0 0" is a missing 8 on the usual board.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::torus::Topology;
+use crate::{EightPuzzle, BLANK, WALL};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PuzzleError {
+    BadSize {
+        rows: usize,
+        cols: usize,
+    },
+    UnknownShape {
+        tiles: usize,
+    },
+    WrongRowCount {
+        expected: usize,
+        found: usize,
+    },
+    WrongRowLength {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    NotANumber {
+        row: usize,
+        col: usize,
+        token: String,
+    },
+    TileOutOfRange {
+        row: usize,
+        col: usize,
+        tile: i64,
+        largest: u16,
+    },
+    DuplicateTile {
+        row: usize,
+        col: usize,
+        tile: u16,
+        first_row: usize,
+        first_col: usize,
+    },
+    MissingTile {
+        tile: u16,
+    },
+    NoBlank,
+    MultipleBlanks {
+        positions: Vec<(usize, usize)>,
+    },
+}
+
+// how many spaces a board may have
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Spaces {
+    One,
+    Several,
+}
+
+// rows and columns are counted from 1 in messages, the same way the prompts count them
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PuzzleError::BadSize { rows, cols } => {
+                write!(f, "a {}x{} board can't be played", rows, cols)
+            }
+            PuzzleError::UnknownShape { tiles } => write!(
+                f,
+                "{} tiles don't make a square board, put a / between rows",
+                tiles
+            ),
+            PuzzleError::WrongRowCount { expected, found } => {
+                write!(f, "expected {} rows but got {}", expected, found)
+            }
+            PuzzleError::WrongRowLength {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "row {} has {} tiles but should have {}",
+                row + 1,
+                found,
+                expected
+            ),
+            PuzzleError::NotANumber { row, col, token } => write!(
+                f,
+                "row {}, column {}: \"{}\" is not a number",
+                row + 1,
+                col + 1,
+                token
+            ),
+            PuzzleError::TileOutOfRange {
+                row,
+                col,
+                tile,
+                largest,
+            } => write!(
+                f,
+                "row {}, column {}: {} is out of range, tiles go from 0 to {}",
+                row + 1,
+                col + 1,
+                tile,
+                largest
+            ),
+            PuzzleError::DuplicateTile {
+                row,
+                col,
+                tile,
+                first_row,
+                first_col,
+            } => write!(
+                f,
+                "row {}, column {}: tile {} is already at row {}, column {}",
+                row + 1,
+                col + 1,
+                tile,
+                first_row + 1,
+                first_col + 1
+            ),
+            PuzzleError::MissingTile { tile } => write!(f, "tile {} is missing", tile),
+            PuzzleError::NoBlank => write!(f, "there is no blank, use a 0 for it"),
+            PuzzleError::MultipleBlanks { positions } => {
+                let places: Vec<String> = positions
+                    .iter()
+                    .map(|(row, col)| format!("row {}, column {}", row + 1, col + 1))
+                    .collect();
+                write!(
+                    f,
+                    "there should be exactly one blank but there are {}: {}",
+                    positions.len(),
+                    places.join("; ")
+                )
+            }
+        }
+    }
+}
+
+impl Error for PuzzleError {}
+
+// every problem found in one piece of input
+#[derive(Clone, Debug, PartialEq)]
+pub struct PuzzleErrors(pub Vec<PuzzleError>);
+
+impl fmt::Display for PuzzleErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let messages: Vec<String> = self.0.iter().map(|error| error.to_string()).collect();
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+impl Error for PuzzleErrors {}
+
+// takes one line of text per row with the tiles split by whitespace and 0 for the blank
+pub fn parse_puzzle_rows(
+    rows: &[&str],
+    expected_rows: usize,
+    expected_cols: usize,
+) -> Result<EightPuzzle, PuzzleErrors> {
+    parse_puzzle_rows_with(rows, expected_rows, expected_cols, Spaces::One)
+}
+
+// the same, for boards that may have several spaces
+pub fn parse_puzzle_rows_with(
+    rows: &[&str],
+    expected_rows: usize,
+    expected_cols: usize,
+    spaces: Spaces,
+) -> Result<EightPuzzle, PuzzleErrors> {
+    let mut errors = Vec::new();
+    let count = |wanted: &str| {
+        rows.iter()
+            .take(expected_rows)
+            .flat_map(|line| line.split_whitespace().take(expected_cols))
+            .filter(|token| *token == wanted)
+            .count() as i64
+    };
+    let blank_cells = match spaces {
+        Spaces::One => 1,
+        Spaces::Several => count("0").max(1),
+    };
+    let largest = (expected_rows * expected_cols) as i64 - count("#") - blank_cells;
+
+    if expected_rows == 0 || expected_cols == 0 || largest >= WALL as i64 {
+        return Err(PuzzleErrors(vec![PuzzleError::BadSize {
+            rows: expected_rows,
+            cols: expected_cols,
+        }]));
+    }
+
+    if rows.len() != expected_rows {
+        errors.push(PuzzleError::WrongRowCount {
+            expected: expected_rows,
+            found: rows.len(),
+        });
+    }
+
+    let mut tiles = vec![BLANK; expected_rows * expected_cols];
+    for (row, line) in rows.iter().enumerate().take(expected_rows) {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+
+        if tokens.len() != expected_cols {
+            errors.push(PuzzleError::WrongRowLength {
+                row,
+                expected: expected_cols,
+                found: tokens.len(),
+            });
+        }
+
+        for (col, token) in tokens.iter().enumerate().take(expected_cols) {
+            if *token == "#" {
+                tiles[row * expected_cols + col] = WALL;
+                continue;
+            }
+
+            match token.parse::<i64>() {
+                Ok(tile) if (0..=largest).contains(&tile) => {
+                    tiles[row * expected_cols + col] = tile as u16
+                }
+                Ok(tile) => errors.push(PuzzleError::TileOutOfRange {
+                    row,
+                    col,
+                    tile,
+                    largest: largest.max(0) as u16,
+                }),
+                Err(_) => errors.push(PuzzleError::NotANumber {
+                    row,
+                    col,
+                    token: token.to_string(),
+                }),
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(PuzzleErrors(errors));
+    }
+
+    let mut blanks = Vec::new();
+    let mut seen: Vec<Option<(usize, usize)>> = vec![None; tiles.len()];
+    for (index, tile) in tiles.iter().enumerate() {
+        let (row, col) = (index / expected_cols, index % expected_cols);
+
+        if *tile == BLANK {
+            blanks.push((row, col));
+            continue;
+        }
+        if *tile == WALL {
+            continue;
+        }
+
+        match seen[*tile as usize] {
+            Some((first_row, first_col)) => errors.push(PuzzleError::DuplicateTile {
+                row,
+                col,
+                tile: *tile,
+                first_row,
+                first_col,
+            }),
+            None => seen[*tile as usize] = Some((row, col)),
+        }
+    }
+
+    match blanks.len() {
+        0 => errors.push(PuzzleError::NoBlank),
+        1 => {}
+        _ if spaces == Spaces::Several => {}
+        _ => errors.push(PuzzleError::MultipleBlanks { positions: blanks }),
+    }
+
+    for tile in 1..=largest.max(0) as u16 {
+        if seen[tile as usize].is_none() {
+            errors.push(PuzzleError::MissingTile { tile });
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(PuzzleErrors(errors));
+    }
+
+    Ok(EightPuzzle {
+        rows: expected_rows,
+        cols: expected_cols,
+        tiles,
+        topology: Topology::Flat,
+    })
+}
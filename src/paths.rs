@@ -0,0 +1,194 @@
+// checking a path of moves someone else came up with, and making it shorter
+//
+// paths are the letters of Moves, so each move slides one tile into the first space. the moves
+// are replayed with the same move_up, move_down, move_left and move_right the search uses. those
+// give back the same board for a move off the edge or into a wall, which is fine while searching,
+// but in a path it's a move that can't be made, so check_path refuses it.
+//
+// greedy searches, weighted A* and people playing wander, so shorten_path takes the loops out of
+// a path and then solves each short stretch of it again with A*, keeping the new moves when they
+// cost less. the boards either side of a stretch stay the same, so the rest of the path still
+// works. it goes over the path until nothing more comes off.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::playback::moved_tile;
+use crate::{
+    manhattan_distance_heuristic, search_with_heuristic, slides, Algorithm, EightPuzzle, Moves,
+    Problem, SearchLimits,
+};
+
+// how many moves shorten_path solves again at a time unless it's told otherwise
+pub const DEFAULT_WINDOW: usize = 12;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathError {
+    // the space would go off the edge or into a wall, the step is counted from 1
+    IllegalMove { step: usize, direction: Moves },
+    // every move could be made but the board at the end isn't the goal
+    NotAtGoal { end: EightPuzzle },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::IllegalMove { step, direction } => write!(
+                f,
+                "move {} ({}) can't be made, the space can't go that way",
+                step,
+                direction.letter()
+            ),
+            PathError::NotAtGoal { end } => {
+                write!(f, "the moves end at {}, which isn't the goal", end)
+            }
+        }
+    }
+}
+
+impl Error for PathError {}
+
+// the board after one move and what the move costs, None if it can't be made. a move that can't
+// be made leaves the space where it was, so no tile ends up there
+fn step(problem: &Problem, puzzle: &EightPuzzle, direction: Moves) -> Option<(EightPuzzle, u64)> {
+    let next = puzzle.apply_move(direction);
+    let tile = moved_tile(puzzle, &next)?;
+
+    Some((next, problem.cost_model.move_cost(tile, direction)))
+}
+
+// every board along the path, starting with the first, and what the moves cost altogether
+fn replay(
+    problem: &Problem,
+    start: &EightPuzzle,
+    moves: &[Moves],
+) -> Result<(Vec<EightPuzzle>, u64), PathError> {
+    let mut boards = vec![start.clone()];
+    let mut cost = 0;
+
+    for (index, direction) in moves.iter().enumerate() {
+        match step(problem, &boards[index], *direction) {
+            Some((next, move_cost)) => {
+                boards.push(next);
+                cost += move_cost;
+            }
+            None => {
+                return Err(PathError::IllegalMove {
+                    step: index + 1,
+                    direction: *direction,
+                })
+            }
+        }
+    }
+
+    Ok((boards, cost))
+}
+
+// makes sure every move can be made and that they end at the goal, and gives back their cost
+pub fn check_path(problem: &Problem, moves: &[Moves]) -> Result<u64, PathError> {
+    let (boards, cost) = replay(problem, &problem.initial_state, moves)?;
+    let end = boards.last().unwrap();
+
+    if !problem.colors.matches(end, &problem.goal_state) {
+        return Err(PathError::NotAtGoal { end: end.clone() });
+    }
+
+    Ok(cost)
+}
+
+// takes out the moves between two visits to the same board, boards that only differ in which tile
+// of a colour is where count as the same
+fn cut_loops(problem: &Problem, moves: &[Moves]) -> Vec<Moves> {
+    let classes = problem.colors.classes();
+    let mut kept: Vec<Moves> = Vec::new();
+    let mut board = classes.canonical(&problem.initial_state);
+    // where each board on the kept path was reached, as a number of kept moves
+    let mut seen: HashMap<EightPuzzle, usize> = HashMap::new();
+    seen.insert(board.clone(), 0);
+
+    for direction in moves {
+        board = board.apply_move(*direction);
+        kept.push(*direction);
+
+        if let Some(at) = seen.get(&board).cloned() {
+            kept.truncate(at);
+            seen.retain(|_, reached| *reached <= at);
+        } else {
+            seen.insert(board.clone(), kept.len());
+        }
+    }
+
+    kept
+}
+
+// the cheapest moves from one board to another, None if a search can't find any
+fn solve_between(
+    problem: &Problem,
+    from: &EightPuzzle,
+    to: &EightPuzzle,
+) -> Option<(Vec<Moves>, u64)> {
+    let stretch = Problem {
+        cost_model: problem.cost_model.clone(),
+        colors: problem.colors.clone(),
+        ..Problem::new(from.clone(), to.clone())
+    };
+
+    let (outcome, _) = search_with_heuristic(
+        stretch,
+        &manhattan_distance_heuristic,
+        Algorithm::AStar,
+        SearchLimits::default(),
+    );
+    outcome
+        .solution()
+        .map(|solution| (solution.moves.clone(), solution.cost))
+}
+
+// a path that gets to the goal for no more than the moves given cost, moves check_path refuses
+// are refused here too. window is how many moves are solved again at once
+pub fn shorten_path(
+    problem: &Problem,
+    moves: &[Moves],
+    window: usize,
+) -> Result<Vec<Moves>, PathError> {
+    check_path(problem, moves)?;
+    let mut moves = cut_loops(problem, moves);
+
+    // with more spaces a search could move one the moves don't say anything about
+    if slides::space_count(&problem.initial_state) > 1 || window < 2 {
+        return Ok(moves);
+    }
+
+    loop {
+        let mut shortened = false;
+        let mut start = 0;
+        // the boards along the moves, which only change when a stretch is swapped for a cheaper one
+        let (mut boards, _) = replay(problem, &problem.initial_state, &moves)
+            .expect("shortening keeps the moves legal");
+
+        while start + 1 < moves.len() {
+            let end = (start + window).min(moves.len());
+            let (_, stretch_cost) = replay(problem, &boards[start], &moves[start..end])
+                .expect("shortening keeps the moves legal");
+
+            if let Some((better, better_cost)) =
+                solve_between(problem, &boards[start], &boards[end])
+            {
+                if better_cost < stretch_cost {
+                    moves.splice(start..end, better);
+                    shortened = true;
+                    boards = replay(problem, &problem.initial_state, &moves)
+                        .expect("shortening keeps the moves legal")
+                        .0;
+                }
+            }
+            start += 1;
+        }
+
+        if !shortened {
+            return Ok(moves);
+        }
+        moves = cut_loops(problem, &moves);
+    }
+}
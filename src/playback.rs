@@ -0,0 +1,217 @@
+// plays a solution back in the terminal, redrawing the board in place for every move
+//
+// controls while playing:
+// * space pauses and resumes
+// * n or the right arrow steps forward, b or the left arrow steps back
+// * + and - speed up and slow down
+// * q or escape stops
+
+use std::io;
+use std::io::prelude::*;
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::terminal;
+
+use crate::heuristic::Heuristic;
+use crate::{slides, EightPuzzle, Moves, BLANK, WALL};
+
+pub const DEFAULT_DELAY_MS: u64 = 500;
+const MIN_DELAY_MS: u64 = 50;
+const MAX_DELAY_MS: u64 = 5000;
+
+// puts the terminal back the way it was even if drawing fails part way through
+pub struct RawModeGuard;
+
+impl RawModeGuard {
+    pub fn enable() -> io::Result<RawModeGuard> {
+        terminal::enable_raw_mode()?;
+        print!("\x1b[?25l");
+        io::stdout().flush()?;
+        Ok(RawModeGuard)
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        print!("\x1b[?25h");
+        let _ = io::stdout().flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// the space moves, so the tile that slides is the one that ends up where the space was
+pub fn moved_tile(before: &EightPuzzle, after: &EightPuzzle) -> Option<u16> {
+    let space = before.blank_position();
+    let tile = after.get(space.row, space.col);
+
+    if tile == BLANK {
+        None
+    } else {
+        Some(tile)
+    }
+}
+
+// the direction the tile slides is the opposite of the direction the space moves
+fn tile_direction(space_move: Moves) -> &'static str {
+    match space_move {
+        Moves::Up => "down",
+        Moves::Down => "up",
+        Moves::Left => "right",
+        Moves::Right => "left",
+        Moves::Nothing => "nowhere",
+    }
+}
+
+// clears the screen and draws one board, the moved tile is shown in reverse video
+pub fn draw_board(puzzle: &EightPuzzle, highlight: Option<u16>, out: &mut String) {
+    out.push_str("\x1b[H\x1b[2J");
+
+    // every cell is as wide as the biggest tile number plus a space on each side
+    let width = (puzzle.tiles.len() - 1).to_string().len() + 1;
+    let border = format!(
+        "\t+{}\r\n",
+        format!("{}+", "-".repeat(width + 1)).repeat(puzzle.cols)
+    );
+
+    for row in puzzle.tiles.chunks(puzzle.cols) {
+        out.push_str(&border);
+        out.push('\t');
+        for tile in row.iter() {
+            out.push('|');
+            if *tile == BLANK {
+                out.push_str(&" ".repeat(width + 1));
+            } else if *tile == WALL {
+                out.push_str(&"#".repeat(width + 1));
+            } else if Some(*tile) == highlight {
+                out.push_str(&format!("\x1b[7m{:>width$} \x1b[0m", tile, width = width));
+            } else {
+                out.push_str(&format!("{:>width$} ", tile, width = width));
+            }
+        }
+        out.push_str("|\r\n");
+    }
+    out.push_str(&border);
+    out.push_str("\r\n");
+}
+
+fn wait_for_key(timeout: Option<Duration>) -> io::Result<Option<KeyCode>> {
+    if let Some(timeout) = timeout {
+        if !event::poll(timeout)? {
+            return Ok(None);
+        }
+    }
+
+    loop {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        {
+            return Ok(Some(code));
+        }
+
+        if timeout.is_some() {
+            return Ok(None);
+        }
+    }
+}
+
+// the moves are of the one space, each sliding a single tile, so a board with more spaces can't be
+// played back
+pub fn play(
+    initial: EightPuzzle,
+    goal: EightPuzzle,
+    moves: &[Moves],
+    heuristic: &dyn Heuristic,
+    delay_ms: u64,
+) -> io::Result<()> {
+    if slides::space_count(&initial) != 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "only boards with one space can be played back",
+        ));
+    }
+
+    let mut states = vec![initial];
+    for space_move in moves.iter() {
+        let next = states[states.len() - 1].apply_move(*space_move);
+        states.push(next);
+    }
+
+    let _guard = RawModeGuard::enable()?;
+
+    let mut step = 0;
+    let mut paused = false;
+    let mut delay_ms = delay_ms.clamp(MIN_DELAY_MS, MAX_DELAY_MS);
+
+    loop {
+        let finished = step == moves.len();
+
+        let mut screen = String::new();
+        let highlight = if step > 0 {
+            moved_tile(&states[step - 1], &states[step])
+        } else {
+            None
+        };
+        draw_board(&states[step], highlight, &mut screen);
+
+        screen.push_str(&format!("\tstep {} of {}\r\n", step, moves.len()));
+        if step > 0 {
+            let space_move = moves[step - 1];
+            screen.push_str(&format!(
+                "\tmove: space {} (tile {} slides {})\r\n",
+                space_move.name(),
+                highlight.unwrap_or(0),
+                tile_direction(space_move)
+            ));
+        } else {
+            screen.push_str("\tmove: start\r\n");
+        }
+        screen.push_str(&format!(
+            "\th(n) = {}\r\n",
+            heuristic.estimate(&states[step], &goal)
+        ));
+        screen.push_str(&format!("\tdelay: {} ms", delay_ms));
+        if finished {
+            screen.push_str(" \t finished");
+        } else if paused {
+            screen.push_str(" \t paused");
+        }
+        screen.push_str("\r\n\r\n");
+        screen.push_str("\tspace pause, n/b or arrows step, +/- speed, q quit\r\n");
+
+        print!("{}", screen);
+        io::stdout().flush()?;
+
+        let timeout = if paused || finished {
+            None
+        } else {
+            Some(Duration::from_millis(delay_ms))
+        };
+
+        match wait_for_key(timeout)? {
+            None => step += 1,
+            Some(KeyCode::Char('q')) | Some(KeyCode::Esc) => break,
+            Some(KeyCode::Char(' ')) => paused = !paused,
+            Some(KeyCode::Char('n')) | Some(KeyCode::Right) => {
+                paused = true;
+                if !finished {
+                    step += 1;
+                }
+            }
+            Some(KeyCode::Char('b')) | Some(KeyCode::Left) => {
+                paused = true;
+                step = step.saturating_sub(1);
+            }
+            Some(KeyCode::Char('+')) | Some(KeyCode::Char('=')) => {
+                delay_ms = (delay_ms / 2).max(MIN_DELAY_MS)
+            }
+            Some(KeyCode::Char('-')) => delay_ms = (delay_ms * 2).min(MAX_DELAY_MS),
+            Some(_) => {}
+        }
+    }
+
+    Ok(())
+}
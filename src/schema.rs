@@ -0,0 +1,449 @@
+// versioned JSON and YAML documents for handing problems to the solver and getting results back,
+// so other tools can use it without answering the prompts
+//
+// a problem looks like this in JSON, YAML uses the same fields:
+//     {
+//       "version": 1,
+//       "size": { "rows": 3, "cols": 3 },
+//       "initial": [[8, 7, 1], [6, 0, 2], [5, 4, 3]],
+//       "goal": [[1, 2, 3], [4, 5, 6], [7, 8, 0]],
+//       "cost_model": { "type": "unit" },
+//       "move_model": "single_tile",
+//       "topology": "flat",
+//       "colors": { "1": "red", "2": "red" }
+//     }
+// walls are written as "#" in the rows. boards can also be written in the one line notation, like
+// "8 7 1/6 0 2/5 4 3". only the version and the initial board are needed, the size comes from the
+// board, the goal defaults to the usual one for its shape, every move costs one and moves one tile,
+// and the board has edges. the other cost models are in cost.rs, the other move model in slides.rs
+// and boards that wrap around in torus.rs. the topology is kept here rather than with each board.
+// colors are left out when no tile has one, see colors.rs.
+//
+// a result holds the problem it answers along with what the search found:
+//     {
+//       "version": 1,
+//       "problem": { "version": 1, "size": ..., "initial": ..., "goal": ..., "cost_model": ... },
+//       "algorithm": "astar",
+//       "heuristic": "manhattan",
+//       "status": "solved",
+//       "path": "RDLU",
+//       "cost": 4,
+//       "stats": { "expanded": 5, "max_queue": 4 }
+//     }
+// the status is one of "solved", "unsolvable", "expansion_limit" or "time_limit". the path is written
+// in move notation, it and the cost are null unless the goal was reached. when the letters alone
+// can't say which space moved or how far, a "slides" field has the path in slide notation too.
+//
+// documents from a version this program doesn't know are refused instead of half read.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::de::{self, DeserializeOwned, Deserializer, SeqAccess, Visitor};
+use serde::ser::{SerializeSeq, Serializer};
+use serde::{Deserialize, Serialize};
+
+use crate::colors::TileColors;
+pub use crate::cost::CostModel;
+use crate::notation::parse_puzzle;
+use crate::notation::{moves_to_string, parse_moves, parse_slides, slides_to_string};
+use crate::parse::{parse_puzzle_rows_with, Spaces};
+use crate::slides::{self, MoveModel, Slide};
+use crate::torus::Topology;
+use crate::walls;
+use crate::{Algorithm, EightPuzzle, Moves, Problem, SearchOutcome, SearchStats, StopReason, WALL};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DocumentFormat {
+    Json,
+    Yaml,
+}
+
+impl DocumentFormat {
+    pub fn from_path(path: &Path) -> DocumentFormat {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml") | Some("yml") => DocumentFormat::Yaml,
+            _ => DocumentFormat::Json,
+        }
+    }
+}
+
+// the version field of every document, written as SCHEMA_VERSION and refused if it's anything else
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SchemaVersion;
+
+impl Serialize for SchemaVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(SCHEMA_VERSION)
+    }
+}
+
+impl<'de> Deserialize<'de> for SchemaVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SchemaVersion, D::Error> {
+        let version = u32::deserialize(deserializer)?;
+
+        if version != SCHEMA_VERSION {
+            return Err(de::Error::custom(format!(
+                "version {} is not supported, this program reads version {}",
+                version, SCHEMA_VERSION
+            )));
+        }
+
+        Ok(SchemaVersion)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BoardSize {
+    pub rows: usize,
+    pub cols: usize,
+}
+
+// one cell of a row, a number or "#" for a wall
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Cell {
+    Tile(i64),
+    Text(String),
+}
+
+// boards are written as a list of rows
+impl Serialize for EightPuzzle {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut rows = serializer.serialize_seq(Some(self.rows))?;
+        for row in self.tiles.chunks(self.cols) {
+            let cells: Vec<Cell> = row
+                .iter()
+                .map(|tile| match *tile {
+                    WALL => Cell::Text("#".to_string()),
+                    tile => Cell::Tile(tile as i64),
+                })
+                .collect();
+            rows.serialize_element(&cells)?;
+        }
+        rows.end()
+    }
+}
+
+// boards are read from a list of rows or from the one line notation, and checked the same way
+// typed puzzles are
+impl<'de> Deserialize<'de> for EightPuzzle {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<EightPuzzle, D::Error> {
+        deserializer.deserialize_any(BoardVisitor)
+    }
+}
+
+struct BoardVisitor;
+
+impl<'de> Visitor<'de> for BoardVisitor {
+    type Value = EightPuzzle;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a list of rows of tiles or a puzzle like \"1 2 3/4 5 6/7 8 0\""
+        )
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<EightPuzzle, E> {
+        parse_puzzle(text, Spaces::Several)
+            .map_err(|errors| E::custom(format!("bad puzzle \"{}\": {}", text, errors)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<EightPuzzle, A::Error> {
+        // tiles are read as any integer so negative ones get the usual out of range message
+        let mut rows: Vec<String> = Vec::new();
+        let mut cols = 0;
+        while let Some(row) = seq.next_element::<Vec<Cell>>()? {
+            if rows.is_empty() {
+                cols = row.len();
+            }
+            let tiles: Vec<String> = row
+                .iter()
+                .map(|cell| match cell {
+                    Cell::Tile(tile) => tile.to_string(),
+                    Cell::Text(text) => text.clone(),
+                })
+                .collect();
+            rows.push(tiles.join(" "));
+        }
+
+        let lines: Vec<&str> = rows.iter().map(|row| row.as_str()).collect();
+        parse_puzzle_rows_with(&lines, rows.len(), cols, Spaces::Several)
+            .map_err(|errors| de::Error::custom(format!("bad puzzle: {}", errors)))
+    }
+}
+
+// a single move is written as its letter
+impl Serialize for Moves {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_char(self.letter())
+    }
+}
+
+impl<'de> Deserialize<'de> for Moves {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Moves, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        let mut letters = text.chars();
+
+        match (letters.next().and_then(Moves::from_letter), letters.next()) {
+            (Some(space_move), None) => Ok(space_move),
+            _ => Moves::from_name(&text).ok_or_else(|| {
+                de::Error::custom(format!("\"{}\" is not a move, use U, D, L or R", text))
+            }),
+        }
+    }
+}
+
+// a whole path is written as one string in move notation, or null if there isn't one
+mod path_notation {
+    use serde::de::{self, Deserialize, Deserializer};
+    use serde::ser::Serializer;
+
+    use super::{moves_to_string, parse_moves};
+    use crate::Moves;
+
+    pub fn serialize<S: Serializer>(
+        path: &Option<Vec<Moves>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match path {
+            Some(moves) => serializer.serialize_str(&moves_to_string(moves)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<Moves>>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(text) => parse_moves(&text)
+                .map(Some)
+                .map_err(|error| de::Error::custom(format!("bad path: {}", error))),
+            None => Ok(None),
+        }
+    }
+}
+
+// the slides of a path written as one string in slide notation, left out when they aren't needed
+mod slide_notation {
+    use serde::de::{self, Deserialize, Deserializer};
+    use serde::ser::Serializer;
+
+    use super::{parse_slides, slides_to_string};
+    use crate::slides::Slide;
+
+    pub fn serialize<S: Serializer>(
+        slides: &Option<Vec<Slide>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match slides {
+            Some(slides) => serializer.serialize_str(&slides_to_string(slides)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<Slide>>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(text) => parse_slides(&text)
+                .map(Some)
+                .map_err(|error| de::Error::custom(format!("bad slides: {}", error))),
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProblemDocument {
+    pub version: SchemaVersion,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<BoardSize>,
+    pub initial: EightPuzzle,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goal: Option<EightPuzzle>,
+    #[serde(default)]
+    pub cost_model: CostModel,
+    #[serde(default)]
+    pub move_model: MoveModel,
+    #[serde(default)]
+    pub topology: Topology,
+    #[serde(default, skip_serializing_if = "TileColors::is_empty")]
+    pub colors: TileColors,
+}
+
+impl ProblemDocument {
+    // the full form, with the size and goal written out
+    pub fn new(problem: &Problem) -> ProblemDocument {
+        ProblemDocument {
+            version: SchemaVersion,
+            size: Some(BoardSize {
+                rows: problem.initial_state.rows,
+                cols: problem.initial_state.cols,
+            }),
+            initial: problem.initial_state.with_topology(Topology::Flat),
+            goal: Some(problem.goal_state.with_topology(Topology::Flat)),
+            cost_model: problem.cost_model.clone(),
+            move_model: problem.move_model,
+            topology: problem.initial_state.topology,
+            colors: problem.colors.clone(),
+        }
+    }
+
+    // fills in the defaults and checks the boards agree with each other and with the size
+    pub fn to_problem(&self) -> Result<Problem, String> {
+        let (rows, cols) = (self.initial.rows, self.initial.cols);
+
+        if let Some(size) = self.size {
+            if size.rows != rows || size.cols != cols {
+                return Err(format!(
+                    "the size is {}x{} but the initial board is {}x{}",
+                    size.rows, size.cols, rows, cols
+                ));
+            }
+        }
+
+        let goal_state = match &self.goal {
+            Some(goal) if goal.rows != rows || goal.cols != cols => {
+                return Err(format!(
+                    "the goal is {}x{} but the initial board is {}x{}",
+                    goal.rows, goal.cols, rows, cols
+                ))
+            }
+            Some(goal) if !walls::same_walls(goal, &self.initial) => {
+                return Err("the goal's walls aren't where the initial board's are".to_string())
+            }
+            Some(goal) if slides::space_count(goal) != slides::space_count(&self.initial) => {
+                return Err(format!(
+                    "the goal has {} spaces but the initial board has {}",
+                    slides::space_count(goal),
+                    slides::space_count(&self.initial)
+                ))
+            }
+            Some(goal) => goal.clone(),
+            None => EightPuzzle::solved_like(&self.initial),
+        };
+        self.colors.check(&self.initial)?;
+
+        Ok(Problem {
+            initial_state: self.initial.with_topology(self.topology),
+            goal_state: goal_state.with_topology(self.topology),
+            cost_model: self.cost_model.clone(),
+            move_model: self.move_model,
+            colors: self.colors.clone(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SolveStatus {
+    Solved,
+    // the search ran out of boards without reaching the goal
+    Unsolvable,
+    // the search gave up early, the goal may still be reachable
+    ExpansionLimit,
+    TimeLimit,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResultDocument {
+    pub version: SchemaVersion,
+    pub problem: ProblemDocument,
+    #[serde(default)]
+    pub algorithm: Algorithm,
+    pub heuristic: String,
+    pub status: SolveStatus,
+    #[serde(with = "path_notation")]
+    pub path: Option<Vec<Moves>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "slide_notation"
+    )]
+    pub slides: Option<Vec<Slide>>,
+    pub cost: Option<u64>,
+    pub stats: SearchStats,
+}
+
+impl ResultDocument {
+    pub fn new(
+        problem: &Problem,
+        algorithm: Algorithm,
+        heuristic: &str,
+        outcome: &SearchOutcome,
+        stats: SearchStats,
+    ) -> ResultDocument {
+        let path = outcome.solution().map(|solution| solution.moves.clone());
+        let slides = match outcome.solution() {
+            Some(solution) if !slides::is_plain(problem) => Some(solution.slides.clone()),
+            _ => None,
+        };
+
+        ResultDocument {
+            version: SchemaVersion,
+            problem: ProblemDocument::new(problem),
+            algorithm,
+            heuristic: heuristic.to_string(),
+            status: match outcome {
+                SearchOutcome::Solved(_) => SolveStatus::Solved,
+                SearchOutcome::Unsolvable => SolveStatus::Unsolvable,
+                SearchOutcome::Stopped(StopReason::ExpansionLimit) => SolveStatus::ExpansionLimit,
+                SearchOutcome::Stopped(StopReason::TimeLimit) => SolveStatus::TimeLimit,
+            },
+            cost: outcome.solution().map(|solution| solution.cost),
+            path,
+            slides,
+            stats,
+        }
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+pub fn to_string<T: Serialize>(document: &T, format: DocumentFormat) -> io::Result<String> {
+    match format {
+        DocumentFormat::Json => serde_json::to_string_pretty(document)
+            .map(|text| text + "\n")
+            .map_err(|error| invalid_data(error.to_string())),
+        DocumentFormat::Yaml => {
+            serde_yaml::to_string(document).map_err(|error| invalid_data(error.to_string()))
+        }
+    }
+}
+
+pub fn from_str<T: DeserializeOwned>(text: &str, format: DocumentFormat) -> io::Result<T> {
+    match format {
+        DocumentFormat::Json => {
+            serde_json::from_str(text).map_err(|error| invalid_data(error.to_string()))
+        }
+        DocumentFormat::Yaml => {
+            serde_yaml::from_str(text).map_err(|error| invalid_data(error.to_string()))
+        }
+    }
+}
+
+// the format is picked from the extension, .yaml and .yml are YAML and anything else is JSON
+pub fn read_document<T: DeserializeOwned>(path: &Path) -> io::Result<T> {
+    from_str(&fs::read_to_string(path)?, DocumentFormat::from_path(path))
+}
+
+pub fn write_document<T: Serialize>(path: &Path, document: &T) -> io::Result<()> {
+    fs::write(path, to_string(document, DocumentFormat::from_path(path))?)
+}
+
+// reads a problem file and turns it into something the search can take
+pub fn read_problem(path: &Path) -> io::Result<Problem> {
+    let document: ProblemDocument = read_document(path)?;
+    document.to_problem().map_err(invalid_data)
+}
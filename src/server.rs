@@ -0,0 +1,515 @@
+// a small HTTP server answering puzzle requests with JSON, for programs that would rather not drive
+// the prompts or start the solver once per puzzle
+//
+//     POST /solve         solves a problem and answers with a result document
+//     POST /jobs          starts a solve in the background and answers with the id of the job
+//     GET  /jobs/<id>     the status of a job, along with its result document once it's done
+//     POST /solvable      whether the goal of a problem document can be reached
+//     GET  /random        a random problem document, ?rows=3&cols=3&solvable=true&seed=7
+//
+// a solve request holds a problem document and how to solve it, only the problem is needed:
+//     {
+//       "problem": { "version": 1, "initial": "8 7 1/6 0 2/5 4 3" },
+//       "algorithm": "astar",
+//       "heuristic": "manhattan",
+//       "max_expansions": 100000,
+//       "timeout_ms": 5000
+//     }
+// the heuristic can be any name or combination heuristic.rs can read, like "max(walking, manhattan)"
+//
+// a finished job is forgotten once its result has been fetched, or after job_ttl_ms if it never is.
+// at most max_solves searches run at once, background jobs included, and anything past that is
+// turned away with a 503. every search stops at the request's timeout or the server's, whichever
+// is shorter, and then reports "time_limit" as its status. errors are answered with a status code
+// and {"error": "..."}.
+
+use std::collections::HashMap;
+use std::io;
+use std::io::prelude::*;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::heuristic::parse_heuristic;
+use crate::schema::{ProblemDocument, ResultDocument};
+use crate::{
+    search_with_heuristic, Algorithm, EightPuzzle, Problem, SearchLimits,
+    PRINT_EXPANDING_NODES_TOGGLE,
+};
+
+// request bodies bigger than this are refused, a problem document is a few hundred bytes
+const MAX_BODY_BYTES: u64 = 1 << 20;
+// random boards are capped so a query can't ask for a huge allocation
+const MAX_RANDOM_SIDE: usize = 100;
+
+#[derive(Clone)]
+pub struct ServerConfig {
+    pub address: String,
+    // searches allowed to run at the same time
+    pub max_solves: usize,
+    // the longest any one search may run, requests can ask for less
+    pub max_timeout_ms: u64,
+    // threads answering requests, a waiting /solve holds one for as long as its search runs
+    pub workers: usize,
+    // how long a finished job's result is kept for a client that hasn't fetched it yet
+    pub job_ttl_ms: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> ServerConfig {
+        ServerConfig {
+            address: "127.0.0.1:8080".to_string(),
+            max_solves: 4,
+            max_timeout_ms: 30_000,
+            workers: 8,
+            job_ttl_ms: 600_000,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SolveRequest {
+    problem: ProblemDocument,
+    #[serde(default)]
+    algorithm: Algorithm,
+    #[serde(default = "default_heuristic")]
+    heuristic: String,
+    #[serde(default)]
+    max_expansions: Option<u64>,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+}
+
+fn default_heuristic() -> String {
+    "manhattan".to_string()
+}
+
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum JobStatus {
+    Running,
+    Done { result: Box<ResultDocument> },
+}
+
+struct Job {
+    status: JobStatus,
+    // when the search ended, for forgetting results nobody comes back for
+    finished: Option<Instant>,
+}
+
+#[derive(Serialize)]
+struct JobReply<'a> {
+    id: u64,
+    #[serde(flatten)]
+    status: &'a JobStatus,
+}
+
+#[derive(Serialize)]
+struct SolvableReply {
+    solvable: bool,
+}
+
+#[derive(Serialize)]
+struct ErrorReply {
+    error: String,
+}
+
+// state every worker thread and background job shares
+struct Shared {
+    config: ServerConfig,
+    running_solves: Mutex<usize>,
+    jobs: Mutex<HashMap<u64, Job>>,
+    next_job_id: Mutex<u64>,
+    stopping: AtomicBool,
+}
+
+// one of the max_solves places for a running search, given back when dropped
+struct SolveSlot {
+    shared: Arc<Shared>,
+}
+
+impl SolveSlot {
+    fn take(shared: &Arc<Shared>) -> Option<SolveSlot> {
+        let mut running = shared.running_solves.lock().unwrap();
+        if *running >= shared.config.max_solves {
+            return None;
+        }
+
+        *running += 1;
+        Some(SolveSlot {
+            shared: Arc::clone(shared),
+        })
+    }
+}
+
+impl Drop for SolveSlot {
+    fn drop(&mut self) {
+        *self.shared.running_solves.lock().unwrap() -= 1;
+    }
+}
+
+pub struct ServerHandle {
+    server: Arc<Server>,
+    shared: Arc<Shared>,
+    address: SocketAddr,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl ServerHandle {
+    // the address actually listened on, useful when the port was 0
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    // blocks for as long as the server runs
+    pub fn wait(self) {
+        for worker in self.workers {
+            let _ = worker.join();
+        }
+    }
+
+    // stops taking requests and waits for the ones being answered, background jobs are left to
+    // finish on their own
+    pub fn shutdown(self) {
+        self.shared.stopping.store(true, Ordering::SeqCst);
+        for _ in 0..self.workers.len() {
+            self.server.unblock();
+        }
+        self.wait();
+    }
+}
+
+pub fn start(config: ServerConfig) -> io::Result<ServerHandle> {
+    let server = Server::http(config.address.as_str())
+        .map_err(|error| io::Error::other(error.to_string()))?;
+    let address = server
+        .server_addr()
+        .to_ip()
+        .ok_or_else(|| io::Error::other("the server isn't listening on an IP address"))?;
+
+    // many searches share the process, so none of them print their expansions
+    PRINT_EXPANDING_NODES_TOGGLE.store(false, Ordering::Relaxed);
+
+    let server = Arc::new(server);
+    let shared = Arc::new(Shared {
+        config: config.clone(),
+        running_solves: Mutex::new(0),
+        jobs: Mutex::new(HashMap::new()),
+        next_job_id: Mutex::new(1),
+        stopping: AtomicBool::new(false),
+    });
+
+    let workers = (0..config.workers.max(1))
+        .map(|_| {
+            let server = Arc::clone(&server);
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || loop {
+                match server.recv() {
+                    Ok(request) => handle_request(request, &shared),
+                    Err(_) if shared.stopping.load(Ordering::SeqCst) => break,
+                    Err(_) => continue,
+                }
+            })
+        })
+        .collect();
+
+    Ok(ServerHandle {
+        server,
+        shared,
+        address,
+        workers,
+    })
+}
+
+// the status code and JSON body of a reply
+type Reply = (u16, String);
+
+fn json_reply<T: Serialize>(status: u16, body: &T) -> Reply {
+    match serde_json::to_string(body) {
+        Ok(text) => (status, text),
+        Err(error) => error_reply(500, format!("could not write the reply: {}", error)),
+    }
+}
+
+fn error_reply(status: u16, message: String) -> Reply {
+    let text =
+        serde_json::to_string(&ErrorReply { error: message }).unwrap_or_else(|_| "{}".to_string());
+    (status, text)
+}
+
+fn handle_request(mut request: Request, shared: &Arc<Shared>) {
+    let mut body = String::new();
+    let read = request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_string(&mut body);
+
+    let url = request.url().to_string();
+    let (path, query) = match url.find('?') {
+        Some(index) => (&url[..index], &url[index + 1..]),
+        None => (url.as_str(), ""),
+    };
+
+    let (status, text) = if read.is_err() {
+        error_reply(400, "the body isn't UTF-8 text".to_string())
+    } else if body.len() as u64 > MAX_BODY_BYTES {
+        error_reply(413, "the body is too big".to_string())
+    } else {
+        match (request.method(), path) {
+            (Method::Post, "/solve") => solve_now(&body, shared),
+            (Method::Post, "/jobs") => start_job(&body, shared),
+            (Method::Get, path) if path.starts_with("/jobs/") => job_status(&path[6..], shared),
+            (Method::Post, "/solvable") => check_solvable(&body),
+            (Method::Get, "/random") => random_problem(query),
+            _ => error_reply(404, format!("nothing at {} {}", request.method(), path)),
+        }
+    };
+
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("the content type header is valid");
+    let response = Response::from_string(text + "\n")
+        .with_status_code(status)
+        .with_header(content_type);
+
+    // the client hanging up early isn't the server's problem
+    let _ = request.respond(response);
+}
+
+fn parse_solve_request(body: &str) -> Result<(SolveRequest, Problem), Reply> {
+    let request: SolveRequest = serde_json::from_str(body)
+        .map_err(|error| error_reply(400, format!("bad solve request: {}", error)))?;
+    let problem = request
+        .problem
+        .to_problem()
+        .map_err(|error| error_reply(400, format!("bad problem: {}", error)))?;
+
+    parse_heuristic(&request.heuristic).map_err(|error| error_reply(400, error))?;
+
+    Ok((request, problem))
+}
+
+fn run_search(request: &SolveRequest, problem: Problem, max_timeout_ms: u64) -> ResultDocument {
+    let heuristic = parse_heuristic(&request.heuristic).expect("checked when parsed");
+    let timeout_ms = request
+        .timeout_ms
+        .map_or(max_timeout_ms, |timeout_ms| timeout_ms.min(max_timeout_ms));
+    let limits = SearchLimits {
+        max_expansions: request.max_expansions,
+        deadline: Some(Instant::now() + Duration::from_millis(timeout_ms)),
+        ..SearchLimits::default()
+    };
+
+    let (outcome, stats) =
+        search_with_heuristic(problem.clone(), &*heuristic, request.algorithm, limits);
+    ResultDocument::new(
+        &problem,
+        request.algorithm,
+        &request.heuristic,
+        &outcome,
+        stats,
+    )
+}
+
+fn busy_reply(shared: &Shared) -> Reply {
+    error_reply(
+        503,
+        format!(
+            "all {} solves are taken, try again later",
+            shared.config.max_solves
+        ),
+    )
+}
+
+fn solve_now(body: &str, shared: &Arc<Shared>) -> Reply {
+    let (request, problem) = match parse_solve_request(body) {
+        Ok(parsed) => parsed,
+        Err(reply) => return reply,
+    };
+
+    let _slot = match SolveSlot::take(shared) {
+        Some(slot) => slot,
+        None => return busy_reply(shared),
+    };
+
+    json_reply(
+        200,
+        &run_search(&request, problem, shared.config.max_timeout_ms),
+    )
+}
+
+fn start_job(body: &str, shared: &Arc<Shared>) -> Reply {
+    let (request, problem) = match parse_solve_request(body) {
+        Ok(parsed) => parsed,
+        Err(reply) => return reply,
+    };
+
+    let slot = match SolveSlot::take(shared) {
+        Some(slot) => slot,
+        None => return busy_reply(shared),
+    };
+
+    let id = {
+        let mut next_job_id = shared.next_job_id.lock().unwrap();
+        let id = *next_job_id;
+        *next_job_id += 1;
+        id
+    };
+    let mut jobs = shared.jobs.lock().unwrap();
+    forget_expired_jobs(&mut jobs, &shared.config);
+    jobs.insert(
+        id,
+        Job {
+            status: JobStatus::Running,
+            finished: None,
+        },
+    );
+    drop(jobs);
+
+    let job_shared = Arc::clone(shared);
+    thread::spawn(move || {
+        let result = run_search(&request, problem, job_shared.config.max_timeout_ms);
+        // the slot is given back first so a client that sees the job done can start another
+        drop(slot);
+        job_shared.jobs.lock().unwrap().insert(
+            id,
+            Job {
+                status: JobStatus::Done {
+                    result: Box::new(result),
+                },
+                finished: Some(Instant::now()),
+            },
+        );
+    });
+
+    json_reply(
+        202,
+        &JobReply {
+            id,
+            status: &JobStatus::Running,
+        },
+    )
+}
+
+fn job_status(id: &str, shared: &Arc<Shared>) -> Reply {
+    let id = match id.parse::<u64>() {
+        Ok(id) => id,
+        Err(_) => return error_reply(404, format!("there is no job {}", id)),
+    };
+
+    let mut jobs = shared.jobs.lock().unwrap();
+    forget_expired_jobs(&mut jobs, &shared.config);
+    match jobs.get(&id) {
+        Some(Job {
+            status: JobStatus::Running,
+            ..
+        }) => json_reply(
+            200,
+            &JobReply {
+                id,
+                status: &JobStatus::Running,
+            },
+        ),
+        // a result is only handed out once, after that the job is gone
+        Some(_) => {
+            let job = jobs.remove(&id).unwrap();
+            json_reply(
+                200,
+                &JobReply {
+                    id,
+                    status: &job.status,
+                },
+            )
+        }
+        None => error_reply(404, format!("there is no job {}", id)),
+    }
+}
+
+// drops the finished jobs whose results have waited longer than the server keeps them
+fn forget_expired_jobs(jobs: &mut HashMap<u64, Job>, config: &ServerConfig) {
+    let ttl = Duration::from_millis(config.job_ttl_ms);
+    jobs.retain(|_, job| match job.finished {
+        Some(finished) => finished.elapsed() < ttl,
+        None => true,
+    });
+}
+
+fn check_solvable(body: &str) -> Reply {
+    let document: ProblemDocument = match serde_json::from_str(body) {
+        Ok(document) => document,
+        Err(error) => return error_reply(400, format!("bad problem: {}", error)),
+    };
+
+    match document.to_problem() {
+        Ok(problem) => json_reply(
+            200,
+            &SolvableReply {
+                solvable: problem.is_solvable(),
+            },
+        ),
+        Err(error) => error_reply(400, format!("bad problem: {}", error)),
+    }
+}
+
+// rows and cols default to 3, solvable defaults to true, and a seed gives the same board every time
+fn random_problem(query: &str) -> Reply {
+    let (mut rows, mut cols, mut solvable, mut seed) = (3, 3, true, None);
+
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let mut parts = pair.splitn(2, '=');
+        let (key, value) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+
+        let parsed = match key {
+            "rows" => value.parse().map(|value| rows = value).is_ok(),
+            "cols" => value.parse().map(|value| cols = value).is_ok(),
+            "solvable" => value.parse().map(|value| solvable = value).is_ok(),
+            "seed" => value.parse().map(|value| seed = Some(value)).is_ok(),
+            _ => return error_reply(400, format!("unknown query parameter {}", key)),
+        };
+        if !parsed {
+            return error_reply(400, format!("bad value for {}: {}", key, value));
+        }
+    }
+
+    if !(2..=MAX_RANDOM_SIDE).contains(&rows) || !(2..=MAX_RANDOM_SIDE).contains(&cols) {
+        return error_reply(
+            400,
+            format!(
+                "rows and cols go from 2 to {}, not {}x{}",
+                MAX_RANDOM_SIDE, rows, cols
+            ),
+        );
+    }
+
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_rng(thread_rng()).expect("the thread rng can seed another"),
+    };
+
+    let goal_state = EightPuzzle::solved(rows, cols);
+    let mut initial_state = goal_state.clone();
+    rng.shuffle(&mut initial_state.tiles);
+
+    let mut problem = Problem::new(initial_state, goal_state);
+
+    // swapping two tiles flips the parity, which is all that decides if a board this shape can be
+    // solved, so one swap turns any board into one of the kind that was asked for
+    if problem.is_solvable() != solvable {
+        let tiles = &mut problem.initial_state.tiles;
+        let first = tiles.iter().position(|tile| *tile != crate::BLANK).unwrap();
+        let second = tiles
+            .iter()
+            .rposition(|tile| *tile != crate::BLANK)
+            .unwrap();
+        tiles.swap(first, second);
+    }
+
+    json_reply(200, &ProblemDocument::new(&problem))
+}
@@ -0,0 +1,234 @@
+// boards with more than one space, and moves that push a whole run of tiles at once
+//
+// any number of cells can be 0. the spaces are all alike, so a board is the same board whichever
+// space went where, and the usual goal has them in the last cells. the moves in Moves only ever
+// move the first space in row order, so searches describe what they did with a Slide, which also
+// says which space moved and how far.
+//
+// the move model says what counts as one move:
+// * single_tile, a space swaps with a tile next to it, the usual puzzle
+// * multi_tile, a space goes any number of cells along its row or column and every tile it passes
+//   is pushed one cell the other way, the way a whole row of a real toy slides under a finger.
+//   however many tiles went it's one move, and under a cost model it costs what its dearest tile
+//   would on its own, so with unit costs every slide costs one
+//
+// a space stops at the edge, at a wall and at another space, going past another space would be
+// the same as moving that one instead. on a torus there's no edge and it can go round until it
+// comes back to itself.
+//
+// the heuristics count single tile moves, and a slide moves at most a row or column's worth of
+// tiles one cell each, so under multi_tile their estimates are divided by that many.
+
+use serde::{Deserialize, Serialize};
+
+use crate::heuristic::{ComponentCount, Heuristic};
+use crate::{torus, EightPuzzle, Moves, Problem, BLANK, WALL};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveModel {
+    #[default]
+    SingleTile,
+    MultiTile,
+}
+
+impl MoveModel {
+    pub fn name(self) -> &'static str {
+        match self {
+            MoveModel::SingleTile => "single-tile",
+            MoveModel::MultiTile => "multi-tile",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<MoveModel> {
+        match name {
+            "single-tile" => Some(MoveModel::SingleTile),
+            "multi-tile" => Some(MoveModel::MultiTile),
+            _ => None,
+        }
+    }
+}
+
+// one move of one space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Slide {
+    // where the space starts, counted from 0
+    pub row: usize,
+    pub col: usize,
+    // the way the space goes, the tiles go the other way
+    pub direction: Moves,
+    // how many tiles it pushes, always 1 for single tile moves
+    pub tiles: usize,
+}
+
+pub fn space_count(puzzle: &EightPuzzle) -> usize {
+    puzzle.tiles.iter().filter(|tile| **tile == BLANK).count()
+}
+
+// the usual puzzle, where the moves in Moves say everything about a path
+pub fn is_plain(problem: &Problem) -> bool {
+    problem.move_model == MoveModel::SingleTile && space_count(&problem.initial_state) == 1
+}
+
+fn space_cell(puzzle: &EightPuzzle, slide: &Slide) -> usize {
+    slide.row * puzzle.cols + slide.col
+}
+
+// the cell next to this one in a direction, None off the edge. on a torus it comes back in on the
+// other side
+fn step(puzzle: &EightPuzzle, cell: usize, direction: Moves) -> Option<usize> {
+    let (rows, cols) = (puzzle.rows, puzzle.cols);
+    let (row, col) = (cell / cols, cell % cols);
+
+    if torus::wraps(puzzle) {
+        return match direction {
+            Moves::Up => Some((row + rows - 1) % rows * cols + col),
+            Moves::Down => Some((row + 1) % rows * cols + col),
+            Moves::Left => Some(row * cols + (col + cols - 1) % cols),
+            Moves::Right => Some(row * cols + (col + 1) % cols),
+            Moves::Nothing => None,
+        };
+    }
+
+    match direction {
+        Moves::Up if row > 0 => Some(cell - puzzle.cols),
+        Moves::Down if row + 1 < puzzle.rows => Some(cell + puzzle.cols),
+        Moves::Left if col > 0 => Some(cell - 1),
+        Moves::Right if col + 1 < puzzle.cols => Some(cell + 1),
+        _ => None,
+    }
+}
+
+// the tiles a space would push going that way, nearest first, as far as it could go
+fn run(puzzle: &EightPuzzle, space: usize, direction: Moves) -> Vec<usize> {
+    let mut cells = Vec::new();
+    let mut cell = space;
+
+    while let Some(next) = step(puzzle, cell, direction) {
+        if puzzle.tiles[next] == BLANK || puzzle.tiles[next] == WALL {
+            break;
+        }
+        cells.push(next);
+        cell = next;
+    }
+
+    cells
+}
+
+// the tiles a slide pushes, empty if it can't be made on this board
+pub fn slid_tiles(puzzle: &EightPuzzle, slide: &Slide) -> Vec<u16> {
+    if slide.row >= puzzle.rows
+        || slide.col >= puzzle.cols
+        || puzzle.get(slide.row, slide.col) != BLANK
+    {
+        return Vec::new();
+    }
+
+    let cells = run(puzzle, space_cell(puzzle, slide), slide.direction);
+    if slide.tiles == 0 || slide.tiles > cells.len() {
+        return Vec::new();
+    }
+
+    cells[..slide.tiles]
+        .iter()
+        .map(|cell| puzzle.tiles[*cell])
+        .collect()
+}
+
+// the board after the slide, None if the slide can't be made on it
+pub fn apply_slide(puzzle: &EightPuzzle, slide: &Slide) -> Option<EightPuzzle> {
+    if slid_tiles(puzzle, slide).is_empty() {
+        return None;
+    }
+
+    // swapping the space along the run pushes each tile back one cell
+    let mut next = puzzle.clone();
+    let mut cell = space_cell(puzzle, slide);
+    for pushed in run(puzzle, cell, slide.direction)
+        .into_iter()
+        .take(slide.tiles)
+    {
+        next.tiles.swap(cell, pushed);
+        cell = pushed;
+    }
+
+    Some(next)
+}
+
+// the board at the end of the slides, None if one of them can't be made
+pub fn apply_slides(start: &EightPuzzle, slides: &[Slide]) -> Option<EightPuzzle> {
+    let mut puzzle = start.clone();
+    for slide in slides {
+        puzzle = apply_slide(&puzzle, slide)?;
+    }
+    Some(puzzle)
+}
+
+// every move that can be made on the board, space by space in row order and then up, down, left
+// and right, shortest slides first
+pub fn slides(puzzle: &EightPuzzle, model: MoveModel) -> Vec<(Slide, EightPuzzle)> {
+    let mut moves = Vec::new();
+    let longest = match model {
+        MoveModel::SingleTile => 1,
+        MoveModel::MultiTile => puzzle.rows.max(puzzle.cols),
+    };
+
+    for space in (0..puzzle.tiles.len()).filter(|cell| puzzle.tiles[*cell] == BLANK) {
+        for direction in [Moves::Up, Moves::Down, Moves::Left, Moves::Right].iter() {
+            let reach = run(puzzle, space, *direction).len().min(longest);
+
+            for tiles in 1..=reach {
+                let slide = Slide {
+                    row: space / puzzle.cols,
+                    col: space % puzzle.cols,
+                    direction: *direction,
+                    tiles,
+                };
+                if let Some(next) = apply_slide(puzzle, &slide) {
+                    moves.push((slide, next));
+                }
+            }
+        }
+    }
+
+    moves
+}
+
+// the most tiles one slide can push on a board this size
+pub fn longest_slide(puzzle: &EightPuzzle) -> u64 {
+    (puzzle.rows.max(puzzle.cols) as u64 - 1).max(1)
+}
+
+// turns a heuristic that counts single tile moves into one that counts slides, the search puts
+// this around the one it was given under the multi_tile model
+pub struct SlideCountHeuristic<'a> {
+    inner: &'a dyn Heuristic,
+    longest: u64,
+}
+
+impl<'a> SlideCountHeuristic<'a> {
+    pub fn new(inner: &'a dyn Heuristic, puzzle: &EightPuzzle) -> SlideCountHeuristic<'a> {
+        SlideCountHeuristic {
+            inner,
+            longest: longest_slide(puzzle),
+        }
+    }
+}
+
+impl<'a> Heuristic for SlideCountHeuristic<'a> {
+    fn estimate(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+        self.inner.estimate(puzzle, goal).div_ceil(self.longest)
+    }
+
+    fn tiles(&self) -> Option<Vec<u16>> {
+        self.inner.tiles()
+    }
+
+    fn supports(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> bool {
+        self.inner.supports(puzzle, goal)
+    }
+
+    fn take_max_counts(&self) -> Vec<ComponentCount> {
+        self.inner.take_max_counts()
+    }
+}
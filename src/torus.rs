@@ -0,0 +1,105 @@
+// boards whose edges wrap around, so the space can leave by one side and come back in on the other
+//
+// a board's topology is kept with its tiles. it's flat unless it's set when the problem is built,
+// and the initial board and the goal need the same one. on a torus every row and column is a ring:
+// moving the space up from the top row takes it to the bottom row, and the tile there slides across
+// the edge to the top. a side only 1 or 2 cells long has nothing new to wrap to.
+//
+// tiles can go either way round, so manhattan distance counts the shorter way along each ring. the
+// heuristics that look at the order of the tiles in a line (linear conflict, walking distance and
+// inversion distance) count on the lines having ends, and give that distance instead.
+//
+// solvability comes from the same theorem as on boards with walls (see walls.rs). while both sides
+// are even or at most 2 long the board still colours like a chessboard and the usual parity test
+// holds. once a side is odd and at least 3 long the space can go round it and come back having made
+// an odd number of moves, so every arrangement can be solved. a board 1 cell high or wide is one
+// ring and its tiles can only turn round it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{slides, EightPuzzle, BLANK};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Topology {
+    #[default]
+    Flat,
+    Torus,
+}
+
+impl Topology {
+    pub fn name(self) -> &'static str {
+        match self {
+            Topology::Flat => "flat",
+            Topology::Torus => "torus",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Topology> {
+        match name {
+            "flat" => Some(Topology::Flat),
+            "torus" => Some(Topology::Torus),
+            _ => None,
+        }
+    }
+}
+
+pub fn wraps(puzzle: &EightPuzzle) -> bool {
+    puzzle.topology == Topology::Torus
+}
+
+// how many moves apart two spots along a row or column are, the shorter way round when it wraps
+pub fn axis_distance(from: usize, to: usize, length: usize, wraps: bool) -> u64 {
+    let straight = from.abs_diff(to);
+
+    if wraps {
+        straight.min(length - straight) as u64
+    } else {
+        straight as u64
+    }
+}
+
+// whether one ring of tiles can be turned into the other
+fn same_ring_order(order: &[u16], goal_order: &[u16]) -> bool {
+    if order.len() != goal_order.len() {
+        return false;
+    }
+
+    let doubled = [goal_order, goal_order].concat();
+    order.is_empty() || doubled.windows(order.len()).any(|window| window == order)
+}
+
+// the answer for a board on a torus, None when it's the same parity test as on a flat board
+pub fn is_solvable(initial: &EightPuzzle, goal: &EightPuzzle) -> Option<bool> {
+    if !wraps(initial) {
+        return None;
+    }
+
+    let tiles = |puzzle: &EightPuzzle| -> Vec<u16> {
+        puzzle
+            .tiles
+            .iter()
+            .cloned()
+            .filter(|tile| *tile != BLANK)
+            .collect()
+    };
+
+    if initial.rows == 1 || initial.cols == 1 {
+        return Some(same_ring_order(&tiles(initial), &tiles(goal)));
+    }
+
+    let mut sorted = tiles(initial);
+    let mut goal_sorted = tiles(goal);
+    sorted.sort_unstable();
+    goal_sorted.sort_unstable();
+    if sorted != goal_sorted {
+        return Some(false);
+    }
+
+    let odd_ring = |length: usize| length >= 3 && length % 2 == 1;
+    if slides::space_count(initial) > 1 || odd_ring(initial.rows) || odd_ring(initial.cols) {
+        return Some(true);
+    }
+
+    None
+}
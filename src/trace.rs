@@ -0,0 +1,478 @@
+// structured record of a search, written as it runs and read back later for analysis or replay
+//
+// two formats are supported, picked from the file extension:
+// * JSON Lines (".jsonl" or ".json"), one object per event, easy to grep and load elsewhere
+// * a compact binary format (anything else), a small header followed by fixed layout records
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::torus::Topology;
+use crate::{EightPuzzle, EightPuzzleNode, Moves};
+
+const BINARY_MAGIC: &[u8; 4] = b"8PTR";
+const BINARY_VERSION: u8 = 2;
+const NO_PARENT: u64 = u64::MAX;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceEventKind {
+    // a node was taken off the front of the queue
+    Pop,
+    // a node had its children generated
+    Expand,
+    // a child was placed into the queue
+    Generate,
+    // a node was thrown away because its board was already expanded
+    Prune,
+}
+
+impl TraceEventKind {
+    fn name(self) -> &'static str {
+        match self {
+            TraceEventKind::Pop => "pop",
+            TraceEventKind::Expand => "expand",
+            TraceEventKind::Generate => "generate",
+            TraceEventKind::Prune => "prune",
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            TraceEventKind::Pop => 0,
+            TraceEventKind::Expand => 1,
+            TraceEventKind::Generate => 2,
+            TraceEventKind::Prune => 3,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<TraceEventKind> {
+        match byte {
+            0 => Some(TraceEventKind::Pop),
+            1 => Some(TraceEventKind::Expand),
+            2 => Some(TraceEventKind::Generate),
+            3 => Some(TraceEventKind::Prune),
+            _ => None,
+        }
+    }
+}
+
+// in JSON Lines an event reads like
+//     {"event":"expand","id":7,"parent":2,"g":2,"h":10,"depth":2,"move":"left","board":[[1,2,3],...],"f":12}
+// where f is only written for whoever loads the file, it's g + h
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TraceEvent {
+    #[serde(rename = "event")]
+    pub kind: TraceEventKind,
+    pub id: u64,
+    pub parent: Option<u64>,
+    pub g: u64,
+    pub h: u64,
+    pub depth: u64,
+    #[serde(rename = "move", with = "move_name")]
+    pub prev_move: Moves,
+    #[serde(rename = "board")]
+    pub puzzle: EightPuzzle,
+}
+
+#[derive(Serialize)]
+struct JsonLine<'a> {
+    #[serde(flatten)]
+    event: &'a TraceEvent,
+    f: u64,
+}
+
+// moves are written by name, "nothing" for the root
+mod move_name {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(space_move: &Moves, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(space_move.name())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Moves, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Moves::from_name(&name)
+            .ok_or_else(|| de::Error::custom(format!("unknown move \"{}\"", name)))
+    }
+}
+
+impl TraceEvent {
+    pub fn from_node(kind: TraceEventKind, node: &EightPuzzleNode) -> TraceEvent {
+        TraceEvent {
+            kind,
+            id: node.id,
+            parent: node.parent,
+            g: node.g,
+            h: node.h,
+            depth: node.depth,
+            prev_move: node.prev_move,
+            puzzle: node.puzzle.clone(),
+        }
+    }
+
+    pub fn f(&self) -> u64 {
+        self.g + self.h
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TraceFormat {
+    JsonLines,
+    Binary,
+}
+
+impl TraceFormat {
+    pub fn from_path(path: &Path) -> TraceFormat {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("jsonl") | Some("json") => TraceFormat::JsonLines,
+            _ => TraceFormat::Binary,
+        }
+    }
+}
+
+pub struct TraceWriter {
+    format: TraceFormat,
+    out: BufWriter<File>,
+    // the first write a search couldn't make, kept for finish
+    error: Option<io::Error>,
+}
+
+impl TraceWriter {
+    pub fn create(path: &Path) -> io::Result<TraceWriter> {
+        let format = TraceFormat::from_path(path);
+        let mut out = BufWriter::new(File::create(path)?);
+
+        if format == TraceFormat::Binary {
+            out.write_all(BINARY_MAGIC)?;
+            out.write_all(&[BINARY_VERSION])?;
+        }
+
+        Ok(TraceWriter {
+            format,
+            out,
+            error: None,
+        })
+    }
+
+    pub fn write_event(&mut self, event: &TraceEvent) -> io::Result<()> {
+        match self.format {
+            TraceFormat::JsonLines => {
+                let line = JsonLine {
+                    event,
+                    f: event.f(),
+                };
+                serde_json::to_writer(&mut self.out, &line)?;
+                writeln!(self.out)
+            }
+            TraceFormat::Binary => self.out.write_all(&event_to_bytes(event)),
+        }
+    }
+
+    // writes an event for a search, which has nowhere to report a failure. the first one is kept
+    // for finish and nothing more is written after it
+    pub fn record(&mut self, event: &TraceEvent) {
+        if self.error.is_none() {
+            if let Err(error) = self.write_event(event) {
+                self.error = Some(error);
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => self.out.flush(),
+        }
+    }
+}
+
+pub struct TraceReader {
+    format: TraceFormat,
+    input: BufReader<File>,
+    line: usize,
+}
+
+impl TraceReader {
+    pub fn open(path: &Path) -> io::Result<TraceReader> {
+        let format = TraceFormat::from_path(path);
+        let mut input = BufReader::new(File::open(path)?);
+
+        if format == TraceFormat::Binary {
+            let mut header = [0; 5];
+            input.read_exact(&mut header)?;
+
+            if &header[..4] != BINARY_MAGIC {
+                return Err(invalid_data("not a search trace file".to_string()));
+            }
+            if header[4] != BINARY_VERSION {
+                return Err(invalid_data(format!(
+                    "unsupported trace version {}",
+                    header[4]
+                )));
+            }
+        }
+
+        Ok(TraceReader {
+            format,
+            input,
+            line: 0,
+        })
+    }
+
+    fn read_json_event(&mut self) -> Option<io::Result<TraceEvent>> {
+        loop {
+            let mut line = String::new();
+            match self.input.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(error) => return Some(Err(error)),
+            }
+            self.line += 1;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let line_number = self.line;
+            return Some(
+                serde_json::from_str(&line)
+                    .map_err(|error| invalid_data(format!("line {}: {}", line_number, error))),
+            );
+        }
+    }
+
+    fn read_binary_event(&mut self) -> Option<io::Result<TraceEvent>> {
+        let mut kind = [0; 1];
+        match self.input.read(&mut kind) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(error) => return Some(Err(error)),
+        }
+
+        Some(event_from_bytes(kind[0], &mut self.input))
+    }
+}
+
+impl Iterator for TraceReader {
+    type Item = io::Result<TraceEvent>;
+
+    fn next(&mut self) -> Option<io::Result<TraceEvent>> {
+        match self.format {
+            TraceFormat::JsonLines => self.read_json_event(),
+            TraceFormat::Binary => self.read_binary_event(),
+        }
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// binary record layout, all integers little endian:
+// kind u8, id u64, parent u64 (u64::MAX for the root), g u64, h u64, depth u64, move u8,
+// rows u16, cols u16, then rows * cols tiles as u16
+fn event_to_bytes(event: &TraceEvent) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(46 + 4 + 2 * event.puzzle.tiles.len());
+
+    bytes.push(event.kind.to_byte());
+    bytes.extend_from_slice(&event.id.to_le_bytes());
+    bytes.extend_from_slice(&event.parent.unwrap_or(NO_PARENT).to_le_bytes());
+    bytes.extend_from_slice(&event.g.to_le_bytes());
+    bytes.extend_from_slice(&event.h.to_le_bytes());
+    bytes.extend_from_slice(&event.depth.to_le_bytes());
+    bytes.push(move_to_byte(event.prev_move));
+
+    bytes.extend_from_slice(&(event.puzzle.rows as u16).to_le_bytes());
+    bytes.extend_from_slice(&(event.puzzle.cols as u16).to_le_bytes());
+    for tile in event.puzzle.tiles.iter() {
+        bytes.extend_from_slice(&tile.to_le_bytes());
+    }
+
+    bytes
+}
+
+fn event_from_bytes(kind: u8, input: &mut impl Read) -> io::Result<TraceEvent> {
+    let kind = TraceEventKind::from_byte(kind)
+        .ok_or_else(|| invalid_data(format!("unknown event kind {}", kind)))?;
+
+    let id = read_u64(input)?;
+    let parent = match read_u64(input)? {
+        NO_PARENT => None,
+        parent => Some(parent),
+    };
+    let g = read_u64(input)?;
+    let h = read_u64(input)?;
+    let depth = read_u64(input)?;
+
+    let mut move_byte = [0; 1];
+    input.read_exact(&mut move_byte)?;
+    let prev_move = move_from_byte(move_byte[0])
+        .ok_or_else(|| invalid_data(format!("unknown move {}", move_byte[0])))?;
+
+    let rows = read_u16(input)? as usize;
+    let cols = read_u16(input)? as usize;
+    let mut tiles = Vec::with_capacity(rows * cols);
+    for _ in 0..rows * cols {
+        tiles.push(read_u16(input)?);
+    }
+    let puzzle = EightPuzzle {
+        rows,
+        cols,
+        tiles,
+        topology: Topology::Flat,
+    };
+
+    Ok(TraceEvent {
+        kind,
+        id,
+        parent,
+        g,
+        h,
+        depth,
+        prev_move,
+        puzzle,
+    })
+}
+
+fn read_u16(input: &mut impl Read) -> io::Result<u16> {
+    let mut bytes = [0; 2];
+    input.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn move_to_byte(prev_move: Moves) -> u8 {
+    match prev_move {
+        Moves::Nothing => 0,
+        Moves::Up => 1,
+        Moves::Down => 2,
+        Moves::Left => 3,
+        Moves::Right => 4,
+    }
+}
+
+fn move_from_byte(byte: u8) -> Option<Moves> {
+    match byte {
+        0 => Some(Moves::Nothing),
+        1 => Some(Moves::Up),
+        2 => Some(Moves::Down),
+        3 => Some(Moves::Left),
+        4 => Some(Moves::Right),
+        _ => None,
+    }
+}
+
+// reads a whole trace and prints the counts, depth and f ranges and the expansions per depth
+pub fn print_summary(path: &Path) -> io::Result<()> {
+    let mut counts: BTreeMap<&'static str, u64> = BTreeMap::new();
+    let mut expansions_per_depth: BTreeMap<u64, u64> = BTreeMap::new();
+    let mut max_depth = 0;
+    let mut max_f = 0;
+    let mut queued: i64 = 0;
+    let mut max_queued: i64 = 0;
+    let mut last_pop: Option<TraceEvent> = None;
+
+    for event in TraceReader::open(path)? {
+        let event = event?;
+        *counts.entry(event.kind.name()).or_insert(0) += 1;
+
+        match event.kind {
+            TraceEventKind::Generate => queued += 1,
+            TraceEventKind::Pop => {
+                queued -= 1;
+                last_pop = Some(event.clone());
+            }
+            TraceEventKind::Expand => {
+                *expansions_per_depth.entry(event.depth).or_insert(0) += 1;
+                max_depth = max_depth.max(event.depth);
+                max_f = max_f.max(event.f());
+            }
+            TraceEventKind::Prune => {}
+        }
+        max_queued = max_queued.max(queued);
+    }
+
+    println!("Trace summary for {}", path.display());
+    for kind in [
+        TraceEventKind::Pop,
+        TraceEventKind::Expand,
+        TraceEventKind::Generate,
+        TraceEventKind::Prune,
+    ]
+    .iter()
+    {
+        println!(
+            "\t {} events: {}",
+            kind.name(),
+            counts.get(kind.name()).cloned().unwrap_or(0)
+        );
+    }
+    println!("\t deepest expansion: {}", max_depth);
+    println!("\t largest expanded f(n): {}", max_f);
+    println!("\t largest queue size: {}", max_queued);
+
+    println!("Expansions per depth:");
+    for (depth, count) in expansions_per_depth.iter() {
+        println!("\t {} \t {}", depth, count);
+    }
+
+    if let Some(event) = last_pop {
+        println!(
+            "Last node taken from the queue had g(n) = {} and h(n) = {}",
+            event.g, event.h
+        );
+        event.puzzle.print_puzzle();
+    }
+
+    Ok(())
+}
+
+// prints the events of a trace in order, the same way the search prints them while running
+pub fn replay(path: &Path) -> io::Result<()> {
+    for event in TraceReader::open(path)? {
+        let event = event?;
+
+        match event.kind {
+            TraceEventKind::Expand => {
+                println!(
+                    "Expanding the following state with g(n) = {} and h(n) = {}",
+                    event.g, event.h
+                );
+                event.puzzle.print_puzzle();
+                println!();
+            }
+            _ => {
+                let parent = match event.parent {
+                    Some(parent) => parent.to_string(),
+                    None => "-".to_string(),
+                };
+                println!(
+                    "{} node {} (parent {}, move {}, depth {}, g(n) = {}, h(n) = {}, f(n) = {})",
+                    event.kind.name(),
+                    event.id,
+                    parent,
+                    event.prev_move.name(),
+                    event.depth,
+                    event.g,
+                    event.h,
+                    event.f()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
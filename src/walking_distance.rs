@@ -0,0 +1,436 @@
+// the walking distance heuristic
+//
+// manhattan distance moves every tile to its goal row as if nothing else were in the way. walking
+// distance only forgets which column a tile is in: a board becomes a table counting, for every row,
+// how many of its tiles belong in each goal row, plus the row the space is in. a vertical move
+// takes one tile from the row above or below the space into the space's row. the fewest such moves
+// from a board's table to the goal's table is its vertical walking distance, the same is done for
+// columns, and the heuristic is the sum of the two. every real move is a move in just one of the
+// tables, so it never overestimates and never drops by more than 1 along a move.
+//
+// the distances of every table are found once with a breadth first search back from the goal and
+// kept for the rest of the program. they depend only on the size of the board and where the space
+// is in the goal, so when a table directory is set they are also saved there and loaded the next
+// time instead of being searched again.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::io::prelude::*;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::heuristic::Heuristic;
+use crate::{colors, manhattan_distance_heuristic, slides, torus, walls, EightPuzzle, BLANK};
+
+const TABLE_MAGIC: &[u8; 4] = b"8PWD";
+const TABLE_VERSION: u8 = 1;
+
+// a 4x4 table has 24964 entries but a 5x5 one runs out of memory, so bigger boards fall back to
+// the manhattan distance along that direction, which the walking distance is never below. long
+// lines grow the table as fast as more of them do, so the width is held to the same bound
+pub const MAX_LINES: usize = 4;
+
+// where the command line keeps its tables
+pub const DEFAULT_TABLE_DIRECTORY: &str = "tables";
+
+// the distance from every table a board can have to the goal's, for one direction
+//
+// for the vertical table the lines are the rows and every line has cols spots, for the horizontal
+// table the lines are the columns and every line has rows spots
+pub struct WalkingDistanceTable {
+    pub lines: usize,
+    pub width: usize,
+    pub goal_blank_line: usize,
+    // the counts in line order followed by the line the space is in
+    distances: HashMap<Vec<u8>, u8>,
+}
+
+impl WalkingDistanceTable {
+    // whether a table for a direction is small enough to build, the counts in it also have to fit
+    // in a byte
+    pub fn fits(lines: usize, width: usize) -> bool {
+        lines <= MAX_LINES && width <= MAX_LINES
+    }
+
+    pub fn build(lines: usize, width: usize, goal_blank_line: usize) -> WalkingDistanceTable {
+        // in the goal every line holds its own tiles and the space takes one spot in its line
+        let mut goal = vec![0u8; lines * lines + 1];
+        for line in 0..lines {
+            goal[line * lines + line] = width as u8;
+        }
+        goal[goal_blank_line * lines + goal_blank_line] -= 1;
+        goal[lines * lines] = goal_blank_line as u8;
+
+        let mut distances = HashMap::new();
+        distances.insert(goal.clone(), 0);
+        let mut order = vec![goal];
+
+        // a tile can always walk back the way it came, so distances back from the goal are
+        // distances to it
+        let mut next = 0;
+        while next < order.len() {
+            let table = order[next].clone();
+            let distance = distances[&table];
+            next += 1;
+
+            let blank_line = table[lines * lines] as usize;
+            let neighbors = [blank_line.checked_sub(1), Some(blank_line + 1)];
+
+            for from in neighbors.iter().flatten().filter(|line| **line < lines) {
+                for class in 0..lines {
+                    if table[from * lines + class] == 0 {
+                        continue;
+                    }
+
+                    let mut moved = table.clone();
+                    moved[from * lines + class] -= 1;
+                    moved[blank_line * lines + class] += 1;
+                    moved[lines * lines] = *from as u8;
+
+                    if !distances.contains_key(&moved) {
+                        distances.insert(moved.clone(), distance + 1);
+                        order.push(moved);
+                    }
+                }
+            }
+        }
+
+        WalkingDistanceTable {
+            lines,
+            width,
+            goal_blank_line,
+            distances,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.distances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.distances.is_empty()
+    }
+
+    // the distance for a table of counts, or None if it can't be reached from the goal
+    pub fn distance(&self, table: &[u8]) -> Option<u64> {
+        self.distances.get(table).map(|distance| *distance as u64)
+    }
+
+    fn file_name(lines: usize, width: usize, goal_blank_line: usize) -> String {
+        format!("walking-{}x{}-{}.bin", lines, width, goal_blank_line)
+    }
+
+    // the header, then every table as its counts, its space line and its distance
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+
+        out.write_all(TABLE_MAGIC)?;
+        out.write_all(&[
+            TABLE_VERSION,
+            self.lines as u8,
+            self.width as u8,
+            self.goal_blank_line as u8,
+        ])?;
+        out.write_all(&(self.distances.len() as u64).to_le_bytes())?;
+
+        for (table, distance) in self.distances.iter() {
+            out.write_all(table)?;
+            out.write_all(&[*distance])?;
+        }
+
+        out.flush()
+    }
+
+    pub fn load(path: &Path) -> io::Result<WalkingDistanceTable> {
+        let mut input = BufReader::new(File::open(path)?);
+
+        let mut header = [0; 8];
+        input.read_exact(&mut header)?;
+        if &header[..4] != TABLE_MAGIC {
+            return Err(invalid_data("not a walking distance table".to_string()));
+        }
+        if header[4] != TABLE_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported walking distance table version {}",
+                header[4]
+            )));
+        }
+        let (lines, width, goal_blank_line) =
+            (header[5] as usize, header[6] as usize, header[7] as usize);
+
+        let mut count = [0; 8];
+        input.read_exact(&mut count)?;
+        let count = u64::from_le_bytes(count);
+
+        let mut distances = HashMap::new();
+        for _ in 0..count {
+            let mut entry = vec![0; lines * lines + 2];
+            input.read_exact(&mut entry)?;
+            let distance = entry.pop().expect("entries are never empty");
+            distances.insert(entry, distance);
+        }
+
+        Ok(WalkingDistanceTable {
+            lines,
+            width,
+            goal_blank_line,
+            distances,
+        })
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// every table built or loaded so far, by lines, width and goal space line
+type TableCache = HashMap<(usize, usize, usize), Arc<WalkingDistanceTable>>;
+
+fn cache() -> &'static Mutex<TableCache> {
+    static CACHE: OnceLock<Mutex<TableCache>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// where tables are saved and loaded from, None keeps them in memory only
+static TABLE_DIRECTORY: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+pub fn set_table_directory(directory: Option<PathBuf>) {
+    *TABLE_DIRECTORY.lock().unwrap() = directory;
+}
+
+// the table for a board size and goal, from memory, then the table directory, then built
+pub fn table(lines: usize, width: usize, goal_blank_line: usize) -> Arc<WalkingDistanceTable> {
+    // held while building so two searches starting together don't both build the same table
+    let mut cache = cache().lock().unwrap();
+    let key = (lines, width, goal_blank_line);
+    if let Some(table) = cache.get(&key) {
+        return table.clone();
+    }
+
+    let directory = TABLE_DIRECTORY.lock().unwrap().clone();
+    let path = directory
+        .as_ref()
+        .map(|directory| directory.join(WalkingDistanceTable::file_name(key.0, key.1, key.2)));
+
+    let loaded = path.as_ref().and_then(|path| {
+        WalkingDistanceTable::load(path)
+            .ok()
+            .filter(|table| (table.lines, table.width, table.goal_blank_line) == key)
+    });
+    let table = match loaded {
+        Some(table) => table,
+        None => {
+            let table = WalkingDistanceTable::build(lines, width, goal_blank_line);
+            // the table can always be built again, so a directory we can't write to isn't an error
+            if let (Some(directory), Some(path)) = (&directory, &path) {
+                let _ = fs::create_dir_all(directory).and_then(|_| table.save(path));
+            }
+            table
+        }
+    };
+
+    let table = Arc::new(table);
+    cache.insert(key, table.clone());
+    table
+}
+
+// builds the tables for both directions of a goal and saves them into a directory, giving back the
+// files written
+pub fn save_tables(directory: &Path, goal: &EightPuzzle) -> io::Result<Vec<PathBuf>> {
+    let goal_blank = goal.blank_position();
+    let mut paths = Vec::new();
+
+    fs::create_dir_all(directory)?;
+    for (lines, width, goal_blank_line) in [
+        (goal.rows, goal.cols, goal_blank.row),
+        (goal.cols, goal.rows, goal_blank.col),
+    ]
+    .iter()
+    {
+        if !WalkingDistanceTable::fits(*lines, *width) {
+            continue;
+        }
+
+        let path = directory.join(WalkingDistanceTable::file_name(
+            *lines,
+            *width,
+            *goal_blank_line,
+        ));
+        if !paths.contains(&path) {
+            table(*lines, *width, *goal_blank_line).save(&path)?;
+            paths.push(path);
+        }
+    }
+
+    Ok(paths)
+}
+
+// the counts table for one direction, line_of gives the line a spot is in
+fn counts(
+    puzzle: &EightPuzzle,
+    goal_index: &[usize],
+    lines: usize,
+    line_of: impl Fn(usize) -> usize,
+) -> Vec<u8> {
+    let mut table = vec![0u8; lines * lines + 1];
+
+    for (index, tile) in puzzle.tiles.iter().enumerate() {
+        if *tile == BLANK {
+            table[lines * lines] = line_of(index) as u8;
+        } else {
+            let class = line_of(goal_index[*tile as usize]);
+            table[line_of(index) * lines + class] += 1;
+        }
+    }
+
+    table
+}
+
+// how far the tiles are from their goal lines added up, the fallback for boards too big for tables
+fn line_distance(
+    puzzle: &EightPuzzle,
+    goal_index: &[usize],
+    line_of: impl Fn(usize) -> usize,
+) -> u64 {
+    puzzle
+        .tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, tile)| **tile != BLANK)
+        .map(|(index, tile)| {
+            let (line, goal_line) = (line_of(index), line_of(goal_index[*tile as usize]));
+            (line as i64 - goal_line as i64).unsigned_abs()
+        })
+        .sum()
+}
+
+// the distance along one direction, lines being the rows or the columns. there's no table for
+// directions with more lines or longer ones than MAX_LINES
+fn direction_distance(
+    puzzle: &EightPuzzle,
+    goal_index: &[usize],
+    lines: usize,
+    table: Option<&WalkingDistanceTable>,
+    line_of: impl Fn(usize) -> usize,
+) -> u64 {
+    let table = match table {
+        Some(table) => table,
+        None => return line_distance(puzzle, goal_index, line_of),
+    };
+
+    // every board that can reach the goal has a table that can too, so this only misses when the
+    // board itself is unsolvable
+    table
+        .distance(&counts(puzzle, goal_index, lines, &line_of))
+        .unwrap_or_else(|| line_distance(puzzle, goal_index, line_of))
+}
+
+// the tables are for full flat boards with one space and every tile different
+fn has_tables(puzzle: &EightPuzzle, goal: &EightPuzzle) -> bool {
+    !walls::has_walls(puzzle)
+        && slides::space_count(puzzle) <= 1
+        && !torus::wraps(puzzle)
+        && !colors::has_interchangeable_tiles(goal)
+}
+
+// what estimates toward one goal need, looked up once instead of from the cache for every board
+struct GoalTables {
+    goal: EightPuzzle,
+    goal_index: Vec<usize>,
+    vertical: Option<Arc<WalkingDistanceTable>>,
+    horizontal: Option<Arc<WalkingDistanceTable>>,
+}
+
+impl GoalTables {
+    fn new(goal: &EightPuzzle) -> GoalTables {
+        let mut tables = GoalTables {
+            goal: goal.clone(),
+            goal_index: Vec::new(),
+            vertical: None,
+            horizontal: None,
+        };
+        if !has_tables(goal, goal) {
+            return tables;
+        }
+
+        tables.goal_index = vec![0; goal.tiles.len()];
+        for (index, tile) in goal.tiles.iter().enumerate() {
+            tables.goal_index[*tile as usize] = index;
+        }
+        let goal_blank = goal.blank_position();
+        let lookup = |lines: usize, width: usize, goal_blank_line: usize| {
+            if WalkingDistanceTable::fits(lines, width) {
+                Some(table(lines, width, goal_blank_line))
+            } else {
+                None
+            }
+        };
+        tables.vertical = lookup(goal.rows, goal.cols, goal_blank.row);
+        tables.horizontal = lookup(goal.cols, goal.rows, goal_blank.col);
+        tables
+    }
+
+    fn estimate(&self, puzzle: &EightPuzzle) -> u64 {
+        if !has_tables(puzzle, &self.goal) || self.goal_index.is_empty() {
+            return manhattan_distance_heuristic(puzzle, &self.goal);
+        }
+
+        let (rows, cols) = (puzzle.rows, puzzle.cols);
+        let vertical = direction_distance(
+            puzzle,
+            &self.goal_index,
+            rows,
+            self.vertical.as_deref(),
+            |index| index / cols,
+        );
+        let horizontal = direction_distance(
+            puzzle,
+            &self.goal_index,
+            cols,
+            self.horizontal.as_deref(),
+            |index| index % cols,
+        );
+
+        vertical + horizontal
+    }
+}
+
+// looks the tables up on every call, WalkingDistance keeps them for the goal it's given
+pub fn walking_distance_heuristic(puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+    GoalTables::new(goal).estimate(puzzle)
+}
+
+// walking distance as a Heuristic, which also knows which boards have tables. the tables of the
+// first goal it's asked about are kept, any other goal looks them up again each time
+#[derive(Default)]
+pub struct WalkingDistance {
+    tables: OnceLock<GoalTables>,
+}
+
+impl WalkingDistance {
+    pub fn new() -> WalkingDistance {
+        WalkingDistance::default()
+    }
+}
+
+impl Heuristic for WalkingDistance {
+    fn estimate(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> u64 {
+        let tables = self.tables.get_or_init(|| GoalTables::new(goal));
+        if tables.goal == *goal {
+            tables.estimate(puzzle)
+        } else {
+            walking_distance_heuristic(puzzle, goal)
+        }
+    }
+
+    fn supports(&self, puzzle: &EightPuzzle, goal: &EightPuzzle) -> bool {
+        puzzle.rows <= MAX_LINES
+            && puzzle.cols <= MAX_LINES
+            && !walls::has_walls(puzzle)
+            && slides::space_count(puzzle) == 1
+            && !torus::wraps(puzzle)
+            && !colors::has_interchangeable_tiles(goal)
+    }
+}
@@ -0,0 +1,365 @@
+// boards with cells no tile can ever enter, for L shapes, rings and boards with holes in them
+//
+// a wall is written as # in place of a tile, like "1 2 #/3 0 4/5 6 7", and is kept in the tiles as
+// WALL. the goal has its walls in the same cells, and the other cells are numbered the usual way,
+// counting up in row order and skipping the walls. moving the space into a wall does nothing, the
+// same as moving it off the edge.
+//
+// tiles have to go around walls, so manhattan distance uses the length of the shortest way around
+// instead of the straight line. those lengths depend only on where the walls are and are worked
+// out once for each shape.
+//
+// on a full board the parity of the tiles decides if it can be solved. by Wilson's theorem that
+// still holds when the cells the space can reach have no cell whose removal cuts them in two and
+// aren't just a ring. on a ring the tiles can only turn round it, and shapes with a narrow neck are
+// searched outright, which is fine for the small boards those come up on. boards with more than one
+// space are searched outright too. on a torus a region that goes round an odd ring can't be
+// coloured like a chessboard, and then the parity doesn't matter.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::torus::{self, Topology};
+use crate::{colors, slides, EightPuzzle, BLANK, WALL};
+
+pub fn has_walls(puzzle: &EightPuzzle) -> bool {
+    puzzle.tiles.contains(&WALL)
+}
+
+pub fn same_walls(puzzle: &EightPuzzle, other: &EightPuzzle) -> bool {
+    wall_cells(puzzle) == wall_cells(other)
+}
+
+fn wall_cells(puzzle: &EightPuzzle) -> Vec<usize> {
+    puzzle
+        .tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, tile)| **tile == WALL)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+// the open cells next to a cell, across the edges on a torus
+fn neighbours(puzzle: &EightPuzzle, index: usize) -> Vec<usize> {
+    let (rows, cols) = (puzzle.rows, puzzle.cols);
+    let (row, col) = (index / cols, index % cols);
+    let mut cells = Vec::new();
+
+    if torus::wraps(puzzle) {
+        cells.push((row + rows - 1) % rows * cols + col);
+        cells.push((row + 1) % rows * cols + col);
+        cells.push(row * cols + (col + cols - 1) % cols);
+        cells.push(row * cols + (col + 1) % cols);
+        // on a side 1 or 2 long both ways round lead to the same cell
+        cells.sort_unstable();
+        cells.dedup();
+        cells.retain(|cell| *cell != index);
+    } else {
+        if row > 0 {
+            cells.push(index - cols);
+        }
+        if row + 1 < rows {
+            cells.push(index + cols);
+        }
+        if col > 0 {
+            cells.push(index - 1);
+        }
+        if col + 1 < cols {
+            cells.push(index + 1);
+        }
+    }
+
+    cells.retain(|cell| puzzle.tiles[*cell] != WALL);
+    cells
+}
+
+// how many moves a tile needs to get from one cell to another, going around the walls
+pub struct CellDistances {
+    cells: usize,
+    distances: Vec<u32>,
+}
+
+impl CellDistances {
+    fn build(puzzle: &EightPuzzle) -> CellDistances {
+        let cells = puzzle.tiles.len();
+        let mut distances = vec![u32::MAX; cells * cells];
+
+        for start in 0..cells {
+            if puzzle.tiles[start] == WALL {
+                continue;
+            }
+
+            let row = &mut distances[start * cells..(start + 1) * cells];
+            row[start] = 0;
+            let mut queue = VecDeque::new();
+            queue.push_back(start);
+
+            while let Some(cell) = queue.pop_front() {
+                for next in neighbours(puzzle, cell) {
+                    if row[next] == u32::MAX {
+                        row[next] = row[cell] + 1;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        CellDistances { cells, distances }
+    }
+
+    // u32::MAX when walls cut the two cells off from each other
+    pub fn between(&self, from: usize, to: usize) -> u64 {
+        self.distances[from * self.cells + to] as u64
+    }
+}
+
+type ShapeKey = (usize, usize, Topology, Vec<usize>);
+
+// the distances for the board's shape, worked out the first time a shape is seen
+pub fn cell_distances(puzzle: &EightPuzzle) -> Arc<CellDistances> {
+    static SHAPES: OnceLock<Mutex<HashMap<ShapeKey, Arc<CellDistances>>>> = OnceLock::new();
+
+    let key = (
+        puzzle.rows,
+        puzzle.cols,
+        puzzle.topology,
+        wall_cells(puzzle),
+    );
+    let mut shapes = SHAPES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap();
+
+    shapes
+        .entry(key)
+        .or_insert_with(|| Arc::new(CellDistances::build(puzzle)))
+        .clone()
+}
+
+// the open cells the space can get to from where it is
+fn blank_region(puzzle: &EightPuzzle) -> Vec<usize> {
+    let start = puzzle.tiles.iter().position(|tile| *tile == BLANK).unwrap();
+    let mut seen = vec![false; puzzle.tiles.len()];
+    let mut region = vec![start];
+    seen[start] = true;
+
+    let mut next = 0;
+    while next < region.len() {
+        for cell in neighbours(puzzle, region[next]) {
+            if !seen[cell] {
+                seen[cell] = true;
+                region.push(cell);
+            }
+        }
+        next += 1;
+    }
+
+    region
+}
+
+// whether taking away any one cell of the region leaves the rest connected, by the usual depth
+// first search for cut vertices
+fn has_cut_cell(puzzle: &EightPuzzle, region: &[usize]) -> bool {
+    fn visit(
+        puzzle: &EightPuzzle,
+        cell: usize,
+        parent: Option<usize>,
+        order: &mut HashMap<usize, usize>,
+        low: &mut HashMap<usize, usize>,
+    ) -> bool {
+        let number = order.len();
+        order.insert(cell, number);
+        low.insert(cell, number);
+        let mut children = 0;
+        let mut cut = false;
+
+        for next in neighbours(puzzle, cell) {
+            if Some(next) == parent {
+                continue;
+            }
+            match order.get(&next) {
+                Some(next_order) => {
+                    let lowest = low[&cell].min(*next_order);
+                    low.insert(cell, lowest);
+                }
+                None => {
+                    children += 1;
+                    cut |= visit(puzzle, next, Some(cell), order, low);
+                    let lowest = low[&cell].min(low[&next]);
+                    low.insert(cell, lowest);
+                    if parent.is_some() && low[&next] >= order[&cell] {
+                        cut = true;
+                    }
+                }
+            }
+        }
+
+        cut || (parent.is_none() && children > 1)
+    }
+
+    visit(
+        puzzle,
+        region[0],
+        None,
+        &mut HashMap::new(),
+        &mut HashMap::new(),
+    )
+}
+
+// the tiles met going once round a ring of cells, the space left out
+fn ring_order(puzzle: &EightPuzzle, region: &[usize]) -> Vec<u16> {
+    let mut order = Vec::new();
+    let mut previous = None;
+    let mut cell = region[0];
+
+    loop {
+        if puzzle.tiles[cell] != BLANK {
+            order.push(puzzle.tiles[cell]);
+        }
+        let next = neighbours(puzzle, cell)
+            .into_iter()
+            .find(|next| Some(*next) != previous)
+            .unwrap();
+        previous = Some(cell);
+        cell = next;
+        if cell == region[0] {
+            return order;
+        }
+    }
+}
+
+// which side of a chessboard colouring each cell of the region is on, None when the region can't
+// be coloured that way, which happens going round an odd ring on a torus
+fn colouring(puzzle: &EightPuzzle, region: &[usize]) -> Option<HashMap<usize, bool>> {
+    let mut colours = HashMap::new();
+    colours.insert(region[0], false);
+    let mut queue = VecDeque::new();
+    queue.push_back(region[0]);
+
+    while let Some(cell) = queue.pop_front() {
+        let colour = colours[&cell];
+        for next in neighbours(puzzle, cell) {
+            match colours.get(&next) {
+                Some(other) if *other == colour => return None,
+                Some(_) => {}
+                None => {
+                    colours.insert(next, !colour);
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    Some(colours)
+}
+
+// the parity test of a full board, only over the cells of the region
+fn parity_agrees(
+    initial: &EightPuzzle,
+    goal: &EightPuzzle,
+    region: &[usize],
+    colours: &HashMap<usize, bool>,
+) -> bool {
+    let mut goal_index = HashMap::new();
+    for cell in region {
+        goal_index.insert(goal.tiles[*cell], *cell);
+    }
+
+    let mut swaps = 0;
+    let mut visited = HashSet::new();
+    for start in region {
+        let mut index = *start;
+        let mut length = 0;
+        while visited.insert(index) {
+            index = goal_index[&initial.tiles[index]];
+            length += 1;
+        }
+        if length > 0 {
+            swaps += length - 1;
+        }
+    }
+
+    let blank = initial
+        .tiles
+        .iter()
+        .position(|tile| *tile == BLANK)
+        .unwrap();
+    let goal_blank = goal.tiles.iter().position(|tile| *tile == BLANK).unwrap();
+    let odd_distance = colours[&blank] != colours[&goal_blank];
+
+    (swaps % 2 == 1) == odd_distance
+}
+
+// every board the initial one can reach, until the goal turns up
+fn search_everything(initial: &EightPuzzle, goal: &EightPuzzle) -> bool {
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    seen.insert(initial.clone());
+    queue.push_back(initial.clone());
+
+    while let Some(puzzle) = queue.pop_front() {
+        if puzzle == *goal {
+            return true;
+        }
+
+        for blank in (0..puzzle.tiles.len()).filter(|cell| puzzle.tiles[*cell] == BLANK) {
+            for cell in neighbours(&puzzle, blank) {
+                let mut next = puzzle.clone();
+                next.tiles.swap(blank, cell);
+                if seen.insert(next.clone()) {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    false
+}
+
+pub fn is_solvable(initial: &EightPuzzle, goal: &EightPuzzle) -> bool {
+    if !same_walls(initial, goal) {
+        return false;
+    }
+
+    // the cells one space can get to change as the others move, so there's no shortcut
+    if slides::space_count(initial) > 1 || slides::space_count(goal) > 1 {
+        return search_everything(initial, goal);
+    }
+
+    // tiles the space can never get to stay where they are
+    let region = blank_region(initial);
+    let in_region: HashSet<usize> = region.iter().cloned().collect();
+    let stuck_tiles_match = (0..initial.tiles.len())
+        .filter(|cell| !in_region.contains(cell))
+        .all(|cell| initial.tiles[cell] == goal.tiles[cell]);
+    if !stuck_tiles_match {
+        return false;
+    }
+
+    let is_ring = region.len() > 2
+        && region
+            .iter()
+            .all(|cell| neighbours(initial, *cell).len() == 2);
+
+    if is_ring {
+        let (order, goal_order) = (ring_order(initial, &region), ring_order(goal, &region));
+        let doubled = [goal_order.clone(), goal_order].concat();
+        order.is_empty()
+            || doubled
+                .windows(order.len())
+                .any(|window| window == &order[..])
+    } else if region.len() > 2 && !has_cut_cell(initial, &region) {
+        match colouring(initial, &region) {
+            // two tiles of a colour can trade places to fix the parity
+            Some(_) if colors::has_interchangeable_tiles(initial) => true,
+            Some(colours) => parity_agrees(initial, goal, &region, &colours),
+            // the one shape the theorem leaves out that can't be coloured has seven cells
+            None if region.len() == 7 => search_everything(initial, goal),
+            // otherwise the space can make either parity and every arrangement can be solved
+            None => true,
+        }
+    } else {
+        search_everything(initial, goal)
+    }
+}
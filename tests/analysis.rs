@@ -0,0 +1,88 @@
+extern crate ai_homework;
+
+use ai_homework::analysis::{self, AnalysisOptions};
+use ai_homework::{
+    manhattan_distance_heuristic, misplaced_tile_heuristic, uniform_search_heuristic, EightPuzzle,
+};
+
+fn options(rows: usize, cols: usize) -> AnalysisOptions {
+    AnalysisOptions {
+        rows,
+        cols,
+        samples: 5,
+        walk_length: 12,
+        searches: 5,
+        ..AnalysisOptions::default()
+    }
+}
+
+#[test]
+fn every_3x3_board_gets_its_distance() {
+    let distances = analysis::exact_distances(&EightPuzzle::solved(3, 3));
+    assert_eq!(distances.len(), 181_440);
+
+    // the hardest 8 puzzles take 31 moves
+    assert_eq!(
+        distances.iter().map(|(_, distance)| *distance).max(),
+        Some(31)
+    );
+}
+
+#[test]
+fn manhattan_distance_is_admissible_and_consistent() {
+    let report = analysis::analyze(&manhattan_distance_heuristic, &options(2, 3));
+
+    assert!(report.exhaustive);
+    assert_eq!(report.boards, 360);
+    assert!(report.is_admissible());
+    assert!(report.is_consistent());
+    assert!(report.mean_ratio > 0.0 && report.mean_ratio <= 1.0);
+    assert_eq!(report.error_histogram.values().sum::<u64>(), 360);
+    assert!(report.error_histogram.keys().all(|error| *error >= 0));
+
+    assert_eq!(report.searches, 5);
+    assert_eq!(report.suboptimal_searches, 0);
+    assert_eq!(report.stopped_searches, 0);
+}
+
+#[test]
+fn misplaced_tiles_overestimate_because_they_count_the_space() {
+    let report = analysis::analyze(&misplaced_tile_heuristic, &options(2, 3));
+
+    assert!(!report.is_admissible());
+    assert!(!report.is_consistent());
+    assert!(report.error_histogram.contains_key(&-1));
+
+    for example in report.admissibility_examples.iter() {
+        assert!(example.estimate > example.distance);
+        assert_eq!(
+            misplaced_tile_heuristic(&example.puzzle, &EightPuzzle::solved(2, 3)),
+            example.estimate
+        );
+    }
+    for example in report.consistency_examples.iter() {
+        assert!(example.estimate > example.next_estimate + 1);
+    }
+}
+
+#[test]
+fn no_heuristic_at_all_is_admissible() {
+    let report = analysis::analyze(&uniform_search_heuristic, &options(2, 2));
+
+    assert!(report.is_admissible());
+    assert!(report.is_consistent());
+    assert_eq!(report.mean_ratio, 0.0);
+}
+
+#[test]
+fn bigger_boards_are_sampled_the_same_way_every_time() {
+    let first = analysis::analyze(&manhattan_distance_heuristic, &options(3, 4));
+    let second = analysis::analyze(&manhattan_distance_heuristic, &options(3, 4));
+
+    assert!(!first.exhaustive);
+    assert!(first.boards > 0);
+    assert!(first.is_admissible());
+    assert_eq!(first.boards, second.boards);
+    assert_eq!(first.error_histogram, second.error_histogram);
+    assert_eq!(first.total_expanded, second.total_expanded);
+}
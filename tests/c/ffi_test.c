@@ -0,0 +1,177 @@
+/* exercises the C interface from C, built and run by tests/ffi.rs
+ *
+ * to build it by hand after `cargo build`:
+ *     cc -Iinclude tests/c/ffi_test.c -Ltarget/debug -lai_homework -o ffi_test
+ *     LD_LIBRARY_PATH=target/debug ./ffi_test
+ */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "ai_homework.h"
+
+static int failures = 0;
+
+#define CHECK(condition)                                                   \
+    do {                                                                   \
+        if (!(condition)) {                                                \
+            fprintf(stderr, "%s:%d: %s failed\n", __FILE__, __LINE__,     \
+                    #condition);                                           \
+            failures++;                                                    \
+        }                                                                  \
+    } while (0)
+
+/* moves the space around a copy of the board and checks it lands on the goal */
+static int replays_to_goal(const uint16_t *start, size_t rows, size_t cols,
+                           const uint8_t *moves, size_t count) {
+    uint16_t tiles[64];
+    size_t blank = 0;
+    size_t i;
+
+    memcpy(tiles, start, rows * cols * sizeof(uint16_t));
+    for (i = 0; i < rows * cols; i++) {
+        if (tiles[i] == 0) {
+            blank = i;
+        }
+    }
+
+    for (i = 0; i < count; i++) {
+        size_t next = blank;
+        switch (moves[i]) {
+        case PUZZLE_MOVE_UP: next = blank - cols; break;
+        case PUZZLE_MOVE_DOWN: next = blank + cols; break;
+        case PUZZLE_MOVE_LEFT: next = blank - 1; break;
+        case PUZZLE_MOVE_RIGHT: next = blank + 1; break;
+        default: return 0;
+        }
+        tiles[blank] = tiles[next];
+        tiles[next] = 0;
+        blank = next;
+    }
+
+    for (i = 0; i + 1 < rows * cols; i++) {
+        if (tiles[i] != i + 1) {
+            return 0;
+        }
+    }
+    return tiles[rows * cols - 1] == 0;
+}
+
+static void solves_the_oh_boy_preset(void) {
+    const uint16_t tiles[] = {8, 7, 1, 6, 0, 2, 5, 4, 3};
+    uint8_t moves[64];
+    PuzzleBoard *board = puzzle_board_new(tiles, 3, 3);
+    PuzzleResult *result;
+    PuzzleStats stats;
+    size_t count;
+
+    CHECK(board != NULL);
+    CHECK(puzzle_is_solvable(board, NULL) == 1);
+
+    result = puzzle_solve(board, NULL, PUZZLE_ALGORITHM_ASTAR,
+                          PUZZLE_HEURISTIC_MANHATTAN, 0, 0);
+    CHECK(result != NULL);
+    CHECK(puzzle_result_status(result) == PUZZLE_STATUS_SOLVED);
+
+    count = puzzle_result_moves(result, moves, sizeof(moves));
+    CHECK(count == puzzle_result_move_count(result));
+    CHECK(count == strlen(puzzle_result_move_string(result)));
+    CHECK(replays_to_goal(tiles, 3, 3, moves, count));
+
+    stats = puzzle_result_stats(result);
+    CHECK(stats.expanded > 0);
+    CHECK(stats.max_queue > 0);
+
+    printf("oh boy: %s in %zu moves, %llu expansions\n",
+           puzzle_result_move_string(result), count,
+           (unsigned long long)stats.expanded);
+
+    puzzle_result_free(result);
+    puzzle_board_free(board);
+}
+
+static void solves_with_a_goal_and_other_algorithms(void) {
+    const uint16_t tiles[] = {1, 2, 3, 4, 0, 6, 7, 5, 8};
+    const uint16_t goal_tiles[] = {1, 2, 3, 4, 5, 6, 7, 8, 0};
+    PuzzleBoard *board = puzzle_board_new(tiles, 3, 3);
+    PuzzleBoard *goal = puzzle_board_new(goal_tiles, 3, 3);
+    PuzzleResult *result;
+
+    result = puzzle_solve(board, goal, PUZZLE_ALGORITHM_GREEDY,
+                          PUZZLE_HEURISTIC_MISPLACED, 0, 0);
+    CHECK(puzzle_result_status(result) == PUZZLE_STATUS_SOLVED);
+    CHECK(strcmp(puzzle_result_move_string(result), "DR") == 0);
+    puzzle_result_free(result);
+
+    result = puzzle_solve(board, goal, PUZZLE_ALGORITHM_UCS,
+                          PUZZLE_HEURISTIC_UCS, 0, 0);
+    CHECK(puzzle_result_move_count(result) == 2);
+    puzzle_result_free(result);
+
+    puzzle_board_free(goal);
+    puzzle_board_free(board);
+}
+
+static void reports_unsolvable_boards_and_limits(void) {
+    const uint16_t tiles[] = {1, 2, 3, 4, 5, 6, 8, 7, 0};
+    const uint16_t hard[] = {8, 7, 1, 6, 0, 2, 5, 4, 3};
+    PuzzleBoard *board = puzzle_board_new(tiles, 3, 3);
+    PuzzleBoard *hard_board = puzzle_board_new(hard, 3, 3);
+    PuzzleResult *result;
+
+    CHECK(puzzle_is_solvable(board, NULL) == 0);
+
+    result = puzzle_solve(hard_board, NULL, PUZZLE_ALGORITHM_ASTAR,
+                          PUZZLE_HEURISTIC_MANHATTAN, 10, 0);
+    CHECK(puzzle_result_status(result) == PUZZLE_STATUS_EXPANSION_LIMIT);
+    CHECK(puzzle_result_move_count(result) == 0);
+    CHECK(puzzle_result_stats(result).expanded == 10);
+    puzzle_result_free(result);
+
+    puzzle_board_free(hard_board);
+    puzzle_board_free(board);
+}
+
+static void rejects_bad_arguments(void) {
+    const uint16_t duplicate[] = {1, 2, 3, 4, 5, 6, 7, 8, 8};
+    const uint16_t small[] = {1, 2, 3, 0};
+    const uint16_t tiles[] = {1, 2, 3, 4, 5, 6, 7, 8, 0};
+    PuzzleBoard *board;
+    PuzzleBoard *small_board;
+
+    CHECK(puzzle_board_new(duplicate, 3, 3) == NULL);
+    CHECK(strstr(puzzle_last_error(), "tile 8") != NULL);
+    CHECK(puzzle_board_new(NULL, 3, 3) == NULL);
+
+    board = puzzle_board_new(tiles, 3, 3);
+    small_board = puzzle_board_new(small, 2, 2);
+    CHECK(strlen(puzzle_last_error()) == 0);
+
+    CHECK(puzzle_solve(board, NULL, 42, PUZZLE_HEURISTIC_MANHATTAN, 0, 0) == NULL);
+    CHECK(strstr(puzzle_last_error(), "algorithm") != NULL);
+    CHECK(puzzle_solve(board, small_board, PUZZLE_ALGORITHM_ASTAR,
+                       PUZZLE_HEURISTIC_MANHATTAN, 0, 0) == NULL);
+    CHECK(puzzle_is_solvable(NULL, NULL) == -1);
+
+    puzzle_board_free(small_board);
+    puzzle_board_free(board);
+    puzzle_board_free(NULL);
+    puzzle_result_free(NULL);
+}
+
+int main(void) {
+    CHECK(puzzle_abi_version() == PUZZLE_ABI_VERSION);
+
+    solves_the_oh_boy_preset();
+    solves_with_a_goal_and_other_algorithms();
+    reports_unsolvable_boards_and_limits();
+    rejects_bad_arguments();
+
+    if (failures > 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+
+    printf("all checks passed\n");
+    return 0;
+}
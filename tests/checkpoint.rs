@@ -0,0 +1,164 @@
+extern crate ai_homework;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use ai_homework::checkpoint::{self, CheckpointOptions};
+use ai_homework::cost::CostModel;
+use ai_homework::heuristic::parse_heuristic;
+use ai_homework::notation::moves_to_string;
+use ai_homework::schema::ProblemDocument;
+use ai_homework::torus::Topology;
+use ai_homework::{
+    search_with_heuristic, Algorithm, EightPuzzle, Problem, SearchLimits, SearchOutcome,
+    SearchStats, StopReason,
+};
+
+fn problem(initial: &str) -> Problem {
+    let initial: EightPuzzle = initial.parse().unwrap();
+    let goal = EightPuzzle::solved_like(&initial);
+    Problem::new(initial, goal)
+}
+
+fn options(name: &str, every: u64) -> CheckpointOptions {
+    CheckpointOptions {
+        path: env::temp_dir().join(format!("checkpoint-{}-{}", std::process::id(), name)),
+        every,
+    }
+}
+
+fn limited(max_expansions: u64) -> SearchLimits {
+    SearchLimits {
+        max_expansions: Some(max_expansions),
+        ..SearchLimits::default()
+    }
+}
+
+// the moves, cost and counts of a search, which a resumed one has to match
+fn summary(
+    (outcome, stats): (SearchOutcome, SearchStats),
+) -> (Option<String>, Option<u64>, SearchStats) {
+    let solution = outcome.solution();
+    (
+        solution.map(|solution| moves_to_string(&solution.moves)),
+        solution.map(|solution| solution.cost),
+        stats,
+    )
+}
+
+fn uninterrupted(
+    problem: &Problem,
+    heuristic: &str,
+    algorithm: Algorithm,
+) -> (SearchOutcome, SearchStats) {
+    let heuristic = parse_heuristic(heuristic).unwrap();
+    search_with_heuristic(
+        problem.clone(),
+        &*heuristic,
+        algorithm,
+        SearchLimits::default(),
+    )
+}
+
+// carries on from the checkpoint to the end
+fn resume(options: &CheckpointOptions) -> (SearchOutcome, SearchStats) {
+    let saved = checkpoint::read_checkpoint(&options.path).unwrap();
+    checkpoint::resume(saved, SearchLimits::default(), options).unwrap()
+}
+
+#[test]
+fn a_resumed_search_ends_like_one_that_never_stopped() {
+    let problem = problem("4 1 2/0 8 7/6 3 5");
+    let heuristic = "max(manhattan, linear-conflict)";
+    let expected = summary(uninterrupted(&problem, heuristic, Algorithm::AStar));
+    assert!(expected.0.is_some());
+
+    let options = options("resumed.json", 10);
+    let (outcome, stats) = checkpoint::search_with_checkpoints(
+        problem.clone(),
+        heuristic,
+        Algorithm::AStar,
+        limited(25),
+        &options,
+    )
+    .unwrap();
+    match outcome {
+        SearchOutcome::Stopped(StopReason::ExpansionLimit) => {}
+        _ => panic!("the search should have stopped"),
+    }
+    assert_eq!(stats.expanded, 25);
+
+    // what the checkpoint holds is the search as it stopped
+    let saved = checkpoint::read_checkpoint(&options.path).unwrap();
+    assert_eq!(
+        ProblemDocument::new(&saved.problem),
+        ProblemDocument::new(&problem)
+    );
+    assert_eq!(saved.heuristic, heuristic);
+    assert_eq!(saved.progress.state.stats, stats);
+
+    // carried on a bit more, then to the end
+    let (outcome, _) = checkpoint::resume(saved, limited(40), &options).unwrap();
+    assert!(outcome.solution().is_none());
+    let resumed = summary(resume(&options));
+    assert_eq!(resumed, expected);
+
+    // and the checkpoint is gone once the search is over
+    assert!(!options.path.exists());
+    assert!(checkpoint::read_checkpoint(&options.path).is_err());
+}
+
+#[test]
+fn a_crash_goes_back_to_the_last_checkpoint() {
+    let problem = problem("4 1 2/0 8 7/6 3 5");
+    let expected = summary(uninterrupted(&problem, "manhattan", Algorithm::Greedy));
+
+    let options = options("crashed.json", 5);
+    checkpoint::search_with_checkpoints(
+        problem.clone(),
+        "manhattan",
+        Algorithm::Greedy,
+        limited(5),
+        &options,
+    )
+    .unwrap();
+
+    // a crash partway through saving leaves the checkpoint before it as it was
+    let partial = PathBuf::from(format!("{}.partial", options.path.display()));
+    fs::write(&partial, "{ \"version\": 1, \"prob").unwrap();
+    let resumed = summary(resume(&options));
+    assert_eq!(resumed, expected);
+    let _ = fs::remove_file(&partial);
+}
+
+#[test]
+fn checkpoints_keep_the_whole_problem() {
+    // a torus with tiles that cost their number, saved as YAML
+    let mut problem = problem("4 1 2/0 8 7/6 3 5");
+    problem.initial_state = problem.initial_state.with_topology(Topology::Torus);
+    problem.goal_state = problem.goal_state.with_topology(Topology::Torus);
+    problem.cost_model = CostModel::TileNumber;
+    let expected = summary(uninterrupted(&problem, "manhattan", Algorithm::AStar));
+
+    let options = options("torus.yaml", 7);
+    checkpoint::search_with_checkpoints(
+        problem.clone(),
+        "manhattan",
+        Algorithm::AStar,
+        limited(7),
+        &options,
+    )
+    .unwrap();
+    assert!(fs::read_to_string(&options.path)
+        .unwrap()
+        .contains("topology: torus"));
+    let saved = checkpoint::read_checkpoint(&options.path).unwrap();
+    assert_eq!(
+        ProblemDocument::new(&saved.problem),
+        ProblemDocument::new(&problem)
+    );
+
+    let resumed = summary(resume(&options));
+    assert_eq!(resumed, expected);
+}
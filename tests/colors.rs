@@ -0,0 +1,286 @@
+extern crate ai_homework;
+extern crate rand;
+
+mod common;
+
+use std::collections::{HashMap, VecDeque};
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use ai_homework::colors::{self, ColoredHeuristic, TileColors};
+use ai_homework::heuristic::{parse_heuristic, Heuristic};
+use ai_homework::schema::{self, DocumentFormat, ProblemDocument};
+use ai_homework::slides::{self, MoveModel};
+use ai_homework::torus::Topology;
+use ai_homework::{search_with_heuristic, Algorithm, EightPuzzle, Problem, SearchLimits, WALL};
+use common::board;
+
+// a 3x3 board where a number can turn up more than once, which the notation doesn't allow
+fn board_with(tiles: &[u16]) -> EightPuzzle {
+    EightPuzzle {
+        rows: 3,
+        cols: 3,
+        tiles: tiles.to_vec(),
+        topology: Topology::Flat,
+    }
+}
+
+fn problem(initial: &EightPuzzle, goal: &EightPuzzle, colors: &TileColors) -> Problem {
+    Problem {
+        colors: colors.clone(),
+        ..Problem::new(initial.clone(), goal.clone())
+    }
+}
+
+// how many moves every canonical board is from the canonical goal, by a breadth first search out
+// from it over boards where tiles of a colour all have the same number
+fn distances(goal: &EightPuzzle, colors: &TileColors) -> HashMap<EightPuzzle, u64> {
+    let goal = colors.canonical(goal);
+    let mut seen = HashMap::new();
+    let mut queue = VecDeque::new();
+    seen.insert(goal.clone(), 0);
+    queue.push_back(goal);
+
+    while let Some(puzzle) = queue.pop_front() {
+        let distance = seen[&puzzle];
+        for (_, next) in slides::slides(&puzzle, MoveModel::SingleTile) {
+            if !seen.contains_key(&next) {
+                seen.insert(next.clone(), distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    seen
+}
+
+// a board with these tiles in the cells the canonical board has their colours in
+fn paint(canonical: &EightPuzzle, goal: &EightPuzzle, colors: &TileColors) -> EightPuzzle {
+    let classes = colors.classes();
+    let mut left: Vec<u16> = goal.tiles.clone();
+    let mut puzzle = canonical.clone();
+
+    for (cell, class) in canonical.tiles.iter().enumerate() {
+        let index = left
+            .iter()
+            .position(|tile| classes.class(*tile) == *class)
+            .unwrap();
+        puzzle.tiles[cell] = left.remove(index);
+    }
+
+    puzzle
+}
+
+// every way of pairing up the cells, for checking the matching against
+fn cheapest_by_trying_everything(costs: &[Vec<u64>], taken: &mut Vec<bool>, row: usize) -> u64 {
+    if row == costs.len() {
+        return 0;
+    }
+
+    let mut best = u64::MAX;
+    for col in 0..costs.len() {
+        if !taken[col] {
+            taken[col] = true;
+            let rest = cheapest_by_trying_everything(costs, taken, row + 1);
+            best = best.min(costs[row][col] + rest);
+            taken[col] = false;
+        }
+    }
+    best
+}
+
+#[test]
+fn colors_are_read_and_written() {
+    let colors = TileColors::parse("red=1,2 blue=3,4").unwrap();
+    assert_eq!(colors.to_string(), "blue=3,4 red=1,2");
+    assert_eq!(TileColors::parse(&colors.to_string()).unwrap(), colors);
+    assert!(TileColors::parse("red=1,2 blue=2").is_err());
+    assert!(TileColors::parse("1,2").is_err());
+    assert!(TileColors::parse("red=1,x").is_err());
+    assert!(TileColors::parse("").unwrap().is_empty());
+
+    let puzzle = board("2 1 3/4 5 6/7 8 0");
+    let goal = board("1 2 3/4 5 6/7 8 0");
+    assert_eq!(
+        colors.canonical(&puzzle),
+        board_with(&[1, 1, 3, 3, 5, 6, 7, 8, 0])
+    );
+    assert!(colors.matches(&puzzle, &goal));
+    assert!(!TileColors::default().matches(&puzzle, &goal));
+    assert!(colors.check(&goal).is_ok());
+    assert!(TileColors::parse("red=1,9").unwrap().check(&goal).is_err());
+
+    let document = ProblemDocument::new(&problem(&puzzle, &goal, &colors));
+    for format in [DocumentFormat::Json, DocumentFormat::Yaml].iter() {
+        let text = schema::to_string(&document, *format).unwrap();
+        assert!(text.contains("red"));
+        let reloaded: ProblemDocument = schema::from_str(&text, *format).unwrap();
+        assert_eq!(reloaded, document);
+        assert_eq!(reloaded.to_problem().unwrap().colors, colors);
+    }
+
+    // no colors, nothing written
+    let document = ProblemDocument::new(&problem(&puzzle, &goal, &TileColors::default()));
+    let text = schema::to_string(&document, DocumentFormat::Json).unwrap();
+    assert!(!text.contains("colors"));
+
+    let text =
+        r#"{"version": 1, "initial": "2 1 3/4 5 6/7 8 0", "colors": {"1": "red", "9": "red"}}"#;
+    let document: ProblemDocument = schema::from_str(text, DocumentFormat::Json).unwrap();
+    assert!(document.to_problem().is_err());
+}
+
+#[test]
+fn manhattan_shares_out_the_goal_cells() {
+    let mut rng = StdRng::seed_from_u64(43);
+    let colors = TileColors::parse("red=1,2,3,4 blue=5,6,7").unwrap();
+    let classes = colors.classes();
+
+    for goal in [
+        board("1 2 3/4 5 6/7 8 0"),
+        board("1 2 3 4/5 # 6 7/8 9 10 0"),
+    ]
+    .iter()
+    {
+        let goal = classes.canonical(goal);
+        let manhattan = parse_heuristic("manhattan").unwrap();
+        let distances = ai_homework::walls::cell_distances(&goal);
+
+        for _ in 0..40 {
+            let mut puzzle = goal.clone();
+            let open: Vec<usize> = (0..goal.tiles.len())
+                .filter(|cell| goal.tiles[*cell] != WALL)
+                .collect();
+            let mut tiles: Vec<u16> = open.iter().map(|cell| goal.tiles[*cell]).collect();
+            rng.shuffle(&mut tiles);
+            for (cell, tile) in open.iter().zip(tiles) {
+                puzzle.tiles[*cell] = tile;
+            }
+
+            let mut expected = 0;
+            let mut seen: Vec<u16> = Vec::new();
+            for tile in puzzle.tiles.iter() {
+                if *tile == 0 || *tile == WALL || seen.contains(tile) {
+                    continue;
+                }
+                seen.push(*tile);
+                let cells = |board: &EightPuzzle| -> Vec<usize> {
+                    (0..board.tiles.len())
+                        .filter(|cell| board.tiles[*cell] == *tile)
+                        .collect()
+                };
+                let costs: Vec<Vec<u64>> = cells(&puzzle)
+                    .iter()
+                    .map(|from| {
+                        cells(&goal)
+                            .iter()
+                            .map(|to| distances.between(*from, *to))
+                            .collect()
+                    })
+                    .collect();
+                expected += cheapest_by_trying_everything(&costs, &mut vec![false; costs.len()], 0);
+            }
+
+            assert!(colors::has_interchangeable_tiles(&puzzle));
+            assert_eq!(manhattan.estimate(&puzzle, &goal), expected, "{}", puzzle);
+        }
+    }
+}
+
+#[test]
+fn solvability_matches_a_full_search() {
+    let mut rng = StdRng::seed_from_u64(44);
+    let cases = [
+        // the parity stops mattering
+        ("1 2 3/4 5 0", "red=1,2", Topology::Flat),
+        ("1 2 3 4/5 6 7 0", "red=1,2 blue=3,4", Topology::Flat),
+        // tiles that can't pass each other
+        ("1/2/3/0", "red=1,3", Topology::Flat),
+        ("1 2 3 4/5 # # 6/7 8 9 0", "red=1,9", Topology::Flat),
+        // walls, a torus and a second space
+        ("1 2 #/3 4 5/6 7 0", "red=6,7", Topology::Flat),
+        ("1 2/3 4/5 0", "red=1,2", Topology::Torus),
+        ("1 2 3/4 0 0", "red=1,4", Topology::Flat),
+    ];
+
+    for (text, colors, topology) in cases.iter() {
+        let goal = board(text).with_topology(*topology);
+        let colors = TileColors::parse(colors).unwrap();
+        let reachable = distances(&goal, &colors);
+        let open: Vec<usize> = (0..goal.tiles.len())
+            .filter(|cell| goal.tiles[*cell] != WALL)
+            .collect();
+
+        for _ in 0..40 {
+            let mut puzzle = goal.clone();
+            let mut tiles: Vec<u16> = open.iter().map(|cell| goal.tiles[*cell]).collect();
+            rng.shuffle(&mut tiles);
+            for (cell, tile) in open.iter().zip(tiles) {
+                puzzle.tiles[*cell] = tile;
+            }
+
+            assert_eq!(
+                problem(&puzzle, &goal, &colors).is_solvable(),
+                reachable.contains_key(&colors.canonical(&puzzle)),
+                "{} on {} with {}",
+                puzzle,
+                text,
+                colors
+            );
+        }
+    }
+}
+
+#[test]
+fn searches_find_the_fewest_moves() {
+    let goal = board("1 2 3/4 5 6/7 8 0");
+    let colors = TileColors::parse("red=1,2,3 blue=4,5,6 green=7,8").unwrap();
+    let distances = distances(&goal, &colors);
+    assert_eq!(distances.len(), 5040);
+
+    for spec in [
+        "manhattan",
+        "linear-conflict",
+        "walking",
+        "gaschnig",
+        "inversion-distance",
+        "sum(manhattan[1 2 3], manhattan[4 5 6 7 8])",
+    ]
+    .iter()
+    {
+        let heuristic = parse_heuristic(spec).unwrap();
+        let colored = ColoredHeuristic::new(&*heuristic, &colors);
+
+        for (canonical, distance) in distances.iter() {
+            let puzzle = paint(canonical, &goal, &colors);
+            let estimate = colored.estimate(&puzzle, &goal);
+            assert!(
+                estimate <= *distance,
+                "{} guessed {} for {}, {} moves away",
+                spec,
+                estimate,
+                puzzle,
+                distance
+            );
+        }
+
+        for (canonical, distance) in distances.iter().step_by(distances.len() / 5) {
+            let puzzle = paint(canonical, &goal, &colors);
+            let (outcome, _) = search_with_heuristic(
+                problem(&puzzle, &goal, &colors),
+                &*heuristic,
+                Algorithm::AStar,
+                SearchLimits::default(),
+            );
+            let solution = outcome.solution().unwrap();
+
+            assert_eq!(solution.cost, *distance, "{} from {}", spec, puzzle);
+            assert!(colors.matches(&solution.puzzle, &goal));
+            assert!(colors.matches(
+                &slides::apply_slides(&puzzle, &solution.slides).unwrap(),
+                &goal
+            ));
+        }
+    }
+}
@@ -0,0 +1,16 @@
+// helpers shared by the integration tests, not every test uses all of them
+#![allow(dead_code)]
+
+use ai_homework::notation::parse_puzzle;
+use ai_homework::parse::Spaces;
+use ai_homework::{EightPuzzle, Problem};
+
+// a board in the notation, with as many spaces as it's written with
+pub fn board(text: &str) -> EightPuzzle {
+    parse_puzzle(text, Spaces::Several).unwrap()
+}
+
+// the usual problem between two boards
+pub fn problem(initial: &EightPuzzle, goal: &EightPuzzle) -> Problem {
+    Problem::new(initial.clone(), goal.clone())
+}
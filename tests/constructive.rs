@@ -0,0 +1,137 @@
+extern crate ai_homework;
+extern crate rand;
+
+mod common;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use ai_homework::colors::TileColors;
+use ai_homework::constructive;
+use ai_homework::paths;
+use ai_homework::torus::Topology;
+use ai_homework::{EightPuzzle, Moves, Problem, BLANK};
+use common::{board, problem};
+
+// a random board the goal can be reached from, two tiles are swapped back when the parity's wrong
+fn scramble(goal: &EightPuzzle, rng: &mut StdRng) -> EightPuzzle {
+    let mut puzzle = goal.clone();
+    rng.shuffle(&mut puzzle.tiles);
+
+    if !problem(&puzzle, goal).is_solvable() {
+        let tiles: Vec<usize> = (0..puzzle.tiles.len())
+            .filter(|cell| puzzle.tiles[*cell] != BLANK)
+            .take(2)
+            .collect();
+        puzzle.tiles.swap(tiles[0], tiles[1]);
+    }
+    puzzle
+}
+
+// follows the moves on the tiles themselves, which is much quicker than a board per move on the
+// big boards, and gives back None for a move off the edge
+fn follow(puzzle: &EightPuzzle, moves: &[Moves]) -> Option<Vec<u16>> {
+    let mut tiles = puzzle.tiles.clone();
+    let mut blank = tiles.iter().position(|tile| *tile == BLANK).unwrap();
+    let (rows, cols) = (puzzle.rows, puzzle.cols);
+
+    for direction in moves {
+        let (row, col) = (blank / cols, blank % cols);
+        let next = match direction {
+            Moves::Up if row > 0 => blank - cols,
+            Moves::Down if row + 1 < rows => blank + cols,
+            Moves::Left if col > 0 => blank - 1,
+            Moves::Right if col + 1 < cols => blank + 1,
+            _ => return None,
+        };
+        tiles.swap(blank, next);
+        blank = next;
+    }
+
+    Some(tiles)
+}
+
+#[test]
+fn big_boards_are_solved() {
+    let mut rng = StdRng::seed_from_u64(45);
+    let goal = EightPuzzle::solved(50, 50);
+
+    for _ in 0..2 {
+        let puzzle = scramble(&goal, &mut rng);
+        let moves = constructive::solve(&problem(&puzzle, &goal)).unwrap();
+        assert_eq!(follow(&puzzle, &moves), Some(goal.tiles.clone()));
+    }
+
+    // already solved, or nearly
+    let moves = constructive::solve(&problem(&goal, &goal)).unwrap();
+    assert_eq!(follow(&goal, &moves), Some(goal.tiles.clone()));
+    let nearly = goal.apply_move(Moves::Up).apply_move(Moves::Left);
+    let moves = constructive::solve(&problem(&nearly, &goal)).unwrap();
+    assert_eq!(follow(&nearly, &moves), Some(goal.tiles.clone()));
+}
+
+#[test]
+fn every_shape_and_goal_is_solved() {
+    let mut rng = StdRng::seed_from_u64(46);
+    let shapes = [
+        (2, 2),
+        (2, 3),
+        (3, 2),
+        (3, 3),
+        (2, 6),
+        (6, 2),
+        (3, 7),
+        (7, 3),
+        (4, 4),
+        (5, 8),
+        (10, 10),
+    ];
+
+    for (rows, cols) in shapes.iter() {
+        let solved = EightPuzzle::solved(*rows, *cols);
+
+        for _ in 0..20 {
+            // the goal's space can be anywhere
+            let goal = scramble(&solved, &mut rng);
+            let puzzle = scramble(&goal, &mut rng);
+            let problem = problem(&puzzle, &goal);
+
+            let moves = constructive::solve(&problem).unwrap();
+            assert_eq!(
+                paths::check_path(&problem, &moves),
+                Ok(moves.len() as u64),
+                "{} to {}",
+                puzzle,
+                goal
+            );
+        }
+    }
+}
+
+#[test]
+fn what_cant_be_solved_is_refused() {
+    let goal = board("1 2 3/4 5 6/7 8 0");
+
+    // the wrong parity
+    let swapped = board("2 1 3/4 5 6/7 8 0");
+    assert!(constructive::solve(&problem(&swapped, &goal)).is_err());
+
+    // boards this doesn't know how to solve
+    let refused = [
+        ("1/2/3/0", "1/2/3/0"),
+        ("1 # 2/3 4 5/6 7 0", "1 # 2/3 4 5/6 7 0"),
+        ("1 2 3/4 5 6/7 0 0", "1 2 3/4 5 6/7 0 0"),
+        ("1 2 3/4 5 6/7 8 0", "1 2/3 0"),
+    ];
+    for (initial, goal) in refused.iter() {
+        assert!(constructive::solve(&problem(&board(initial), &board(goal))).is_err());
+    }
+
+    let torus = goal.with_topology(Topology::Torus);
+    assert!(constructive::solve(&problem(&torus, &torus)).is_err());
+    let colored = Problem {
+        colors: TileColors::parse("red=1,2").unwrap(),
+        ..problem(&goal, &goal)
+    };
+    assert!(constructive::solve(&colored).is_err());
+}
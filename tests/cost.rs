@@ -0,0 +1,244 @@
+extern crate ai_homework;
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+
+use ai_homework::cost::{CostModel, CostWeightedHeuristic};
+use ai_homework::heuristic::{parse_heuristic, Heuristic};
+use ai_homework::schema::{self, DocumentFormat, ProblemDocument, ResultDocument};
+use ai_homework::{
+    search_with_heuristic, Algorithm, EightPuzzle, Moves, Problem, SearchLimits, SearchOutcome,
+};
+
+const MOVES: [Moves; 4] = [Moves::Up, Moves::Down, Moves::Left, Moves::Right];
+
+fn opposite(space_move: Moves) -> Moves {
+    match space_move {
+        Moves::Up => Moves::Down,
+        Moves::Down => Moves::Up,
+        Moves::Left => Moves::Right,
+        Moves::Right => Moves::Left,
+        Moves::Nothing => Moves::Nothing,
+    }
+}
+
+// the cheapest cost from every board to the goal, by dijkstra backwards from the goal
+fn cheapest_costs(goal: &EightPuzzle, costs: &CostModel) -> HashMap<EightPuzzle, u64> {
+    let mut best: HashMap<EightPuzzle, u64> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    best.insert(goal.clone(), 0);
+    queue.push(Reverse((0, goal.tiles.clone())));
+
+    while let Some(Reverse((cost, tiles))) = queue.pop() {
+        let board = EightPuzzle {
+            rows: goal.rows,
+            cols: goal.cols,
+            tiles,
+            topology: goal.topology,
+        };
+        if best[&board] < cost {
+            continue;
+        }
+
+        // the board before is one reverse move back, the tile it slid is where the space goes
+        for space_move in MOVES.iter() {
+            let before = board.apply_move(opposite(*space_move));
+            if before == board {
+                continue;
+            }
+            let blank = before.blank_position();
+            let slid = board.get(blank.row, blank.col);
+            let total = cost + costs.move_cost(slid, *space_move);
+
+            if best.get(&before).is_none_or(|known| total < *known) {
+                best.insert(before.clone(), total);
+                queue.push(Reverse((total, before.tiles)));
+            }
+        }
+    }
+
+    best
+}
+
+fn solve(
+    initial: &EightPuzzle,
+    costs: &CostModel,
+    heuristic: &dyn Heuristic,
+    algorithm: Algorithm,
+) -> u64 {
+    let problem = Problem {
+        cost_model: costs.clone(),
+        ..Problem::new(
+            initial.clone(),
+            EightPuzzle::solved(initial.rows, initial.cols),
+        )
+    };
+    let (outcome, _) = search_with_heuristic(
+        problem.clone(),
+        heuristic,
+        algorithm,
+        SearchLimits::default(),
+    );
+    let solution = outcome.solution().expect("the board can be solved");
+
+    assert_eq!(
+        costs.path_cost(&problem.initial_state, &solution.moves),
+        solution.cost
+    );
+    solution.cost
+}
+
+fn cost_models() -> Vec<CostModel> {
+    let mut weights = BTreeMap::new();
+    weights.insert(1, 10);
+    weights.insert(4, 3);
+
+    vec![
+        CostModel::TileNumber,
+        CostModel::TileWeights { weights },
+        CostModel::Directions {
+            up: 1,
+            down: 4,
+            left: 2,
+            right: 7,
+        },
+        // the space moving down is free for odd tiles
+        CostModel::custom(|tile, space_move| match space_move {
+            Moves::Down if tile % 2 == 1 => 0,
+            _ => 1 + tile as u64 % 3,
+        }),
+    ]
+}
+
+#[test]
+fn searches_find_the_cheapest_path() {
+    let goal = EightPuzzle::solved(2, 3);
+    let manhattan = parse_heuristic("manhattan").unwrap();
+    let combined = parse_heuristic("max(linear-conflict, gaschnig)").unwrap();
+
+    for costs in cost_models() {
+        let cheapest = cheapest_costs(&goal, &costs);
+        assert_eq!(cheapest.len(), 360);
+
+        for (board, cost) in cheapest.iter().step_by(23) {
+            let heuristic = parse_heuristic("ucs").unwrap();
+            assert_eq!(
+                solve(board, &costs, &*heuristic, Algorithm::UniformCost),
+                *cost
+            );
+            assert_eq!(solve(board, &costs, &*manhattan, Algorithm::AStar), *cost);
+            assert_eq!(solve(board, &costs, &*combined, Algorithm::AStar), *cost);
+        }
+    }
+}
+
+#[test]
+fn weighted_estimates_stay_admissible() {
+    let goal = EightPuzzle::solved(2, 3);
+    let specs = [
+        "manhattan",
+        "linear-conflict",
+        "walking",
+        "gaschnig",
+        "inversion-distance",
+        "sum(manhattan[1 2], manhattan[3 4 5])",
+        "max(manhattan, walking-linear-conflict)",
+        "fallback(walking, manhattan)",
+    ];
+
+    for costs in cost_models() {
+        let cheapest = cheapest_costs(&goal, &costs);
+
+        for spec in specs.iter() {
+            let heuristic = parse_heuristic(spec).unwrap();
+            let weighted = CostWeightedHeuristic::new(&*heuristic, &costs, &goal);
+
+            for (board, cost) in cheapest.iter() {
+                let estimate = weighted.estimate(board, &goal);
+                assert!(
+                    estimate <= *cost,
+                    "{} guessed {} for {:?} costing {} under {:?}",
+                    spec,
+                    estimate,
+                    board.tiles,
+                    cost,
+                    costs
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn manhattan_distance_prices_each_tile() {
+    let goal = EightPuzzle::solved(3, 3);
+    // 8 is one spot left of where it goes
+    let board: EightPuzzle = "1 2 3/4 5 6/7 0 8".parse().unwrap();
+    let costs = CostModel::TileNumber;
+
+    let manhattan = parse_heuristic("manhattan").unwrap();
+    let weighted = CostWeightedHeuristic::new(&*manhattan, &costs, &board);
+    assert_eq!(weighted.estimate(&board, &goal), 8);
+
+    // linear conflict doesn't know which tiles it counts, so every move is priced like tile 1's
+    let linear_conflict = parse_heuristic("linear-conflict").unwrap();
+    let weighted = CostWeightedHeuristic::new(&*linear_conflict, &costs, &board);
+    assert_eq!(weighted.estimate(&board, &goal), 1);
+}
+
+#[test]
+fn unit_costs_are_the_move_count() {
+    let board: EightPuzzle = "871602543".parse().unwrap();
+    let manhattan = parse_heuristic("manhattan").unwrap();
+
+    assert_eq!(
+        solve(&board, &CostModel::Unit, &*manhattan, Algorithm::AStar),
+        22
+    );
+}
+
+#[test]
+fn cost_models_are_saved_in_documents() {
+    let mut weights = BTreeMap::new();
+    weights.insert(8, 5);
+    let problem = Problem {
+        cost_model: CostModel::TileWeights { weights },
+        ..Problem::new(
+            "1 2 3/4 5 6/7 0 8".parse().unwrap(),
+            EightPuzzle::solved(3, 3),
+        )
+    };
+
+    let (outcome, stats) = search_with_heuristic(
+        problem.clone(),
+        &*parse_heuristic("manhattan").unwrap(),
+        Algorithm::AStar,
+        SearchLimits::default(),
+    );
+    assert!(matches!(outcome, SearchOutcome::Solved(_)));
+    let result = ResultDocument::new(&problem, Algorithm::AStar, "manhattan", &outcome, stats);
+    assert_eq!(result.cost, Some(5));
+
+    let text = schema::to_string(&result, DocumentFormat::Json).unwrap();
+    assert!(text.contains("tile_weights"));
+    let reloaded: ResultDocument = schema::from_str(&text, DocumentFormat::Json).unwrap();
+    assert_eq!(reloaded, result);
+    let yaml = schema::to_string(&result, DocumentFormat::Yaml).unwrap();
+    let reloaded: ResultDocument = schema::from_str(&yaml, DocumentFormat::Yaml).unwrap();
+    assert_eq!(reloaded, result);
+    assert_eq!(
+        reloaded.problem.to_problem().unwrap().cost_model,
+        problem.cost_model
+    );
+
+    let yaml = "version: 1\ninitial: \"1 2 0/3 4 5\"\ncost_model:\n  type: directions\n  up: 1\n  down: 2\n  left: 3\n  right: 4\n";
+    let document: ProblemDocument = schema::from_str(yaml, DocumentFormat::Yaml).unwrap();
+    assert_eq!(document.cost_model.move_cost(7, Moves::Right), 4);
+
+    // a function can't be written down
+    let custom = ProblemDocument::new(&Problem {
+        cost_model: CostModel::custom(|_, _| 2),
+        ..problem
+    });
+    assert!(schema::to_string(&custom, DocumentFormat::Json).is_err());
+}
@@ -0,0 +1,240 @@
+extern crate ai_homework;
+
+use std::env;
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use ai_homework::colors::TileColors;
+use ai_homework::dot::{LeftOut, SearchTreeExport};
+use ai_homework::trace::{TraceEvent, TraceEventKind};
+use ai_homework::{
+    manhattan_distance_heuristic, search_with_heuristic, Algorithm, EightPuzzle, Moves, Problem,
+    SearchLimits, SearchRecorder,
+};
+
+const GOAL: &str = "123456780";
+
+fn event(
+    kind: TraceEventKind,
+    id: u64,
+    parent: Option<u64>,
+    depth: u64,
+    prev_move: Moves,
+    board: &str,
+) -> TraceEvent {
+    TraceEvent {
+        kind,
+        id,
+        parent,
+        g: depth,
+        h: 2 - depth.min(2),
+        depth,
+        prev_move,
+        puzzle: board.parse().unwrap(),
+    }
+}
+
+// a root with three children, one of them pruned, and the goal two moves down under the first,
+// with a child of its own below the depth cap of 2
+fn small_search(max_nodes: usize) -> String {
+    use ai_homework::trace::TraceEventKind::*;
+
+    let events = [
+        event(Generate, 0, None, 0, Moves::Nothing, "123405786"),
+        event(Pop, 0, None, 0, Moves::Nothing, "123405786"),
+        event(Expand, 0, None, 0, Moves::Nothing, "123405786"),
+        event(Generate, 1, Some(0), 1, Moves::Right, "123450786"),
+        event(Generate, 2, Some(0), 1, Moves::Left, "123045786"),
+        event(Generate, 3, Some(0), 1, Moves::Up, "103425786"),
+        event(Prune, 4, Some(0), 1, Moves::Down, "123485706"),
+        event(Pop, 1, Some(0), 1, Moves::Right, "123450786"),
+        event(Expand, 1, Some(0), 1, Moves::Right, "123450786"),
+        event(Generate, 5, Some(1), 2, Moves::Down, GOAL),
+        event(Pop, 5, Some(1), 2, Moves::Down, GOAL),
+        event(Generate, 6, Some(5), 3, Moves::Up, "123450786"),
+    ];
+
+    let mut export = SearchTreeExport::new(GOAL.parse().unwrap(), max_nodes, 2);
+    for event in events.iter() {
+        export.record(event);
+    }
+    write(&export, &format!("small-{}", max_nodes))
+}
+
+fn write(export: &SearchTreeExport, name: &str) -> String {
+    write_counting(export, name).0
+}
+
+fn write_counting(export: &SearchTreeExport, name: &str) -> (String, LeftOut) {
+    let path = env::temp_dir().join(format!("dot-{}-{}.dot", std::process::id(), name));
+    let left_out = export.write(&path).unwrap();
+    let text = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    (text, left_out)
+}
+
+// solves the problem with the export recording the search, and gives back the export and the
+// number of moves in the solution
+fn exported_search(problem: Problem, export: SearchTreeExport) -> (SearchTreeExport, usize) {
+    let recorder = Arc::new(Mutex::new(SearchRecorder {
+        trace: None,
+        tree: Some(export),
+    }));
+    let limits = SearchLimits {
+        recorder: Some(recorder.clone()),
+        ..SearchLimits::default()
+    };
+    let (outcome, _) = search_with_heuristic(
+        problem,
+        &manhattan_distance_heuristic,
+        Algorithm::AStar,
+        limits,
+    );
+    let moves = outcome.solution().unwrap().moves.len();
+
+    let export = recorder.lock().unwrap().tree.take().unwrap();
+    (export, moves)
+}
+
+fn red(graph: &str) -> Vec<&str> {
+    graph
+        .lines()
+        .filter(|line| line.contains("color=red"))
+        .map(|line| line.trim())
+        .collect()
+}
+
+// the ids of the nodes drawn, in order
+fn nodes(graph: &str) -> Vec<u64> {
+    graph
+        .lines()
+        .filter(|line| line.contains("[label=") && !line.contains("->"))
+        .map(|line| {
+            line.trim()[1..line.trim().find(' ').unwrap()]
+                .parse()
+                .unwrap()
+        })
+        .collect()
+}
+
+fn line<'a>(graph: &'a str, start: &str) -> &'a str {
+    graph
+        .lines()
+        .find(|line| line.trim().starts_with(start))
+        .unwrap_or_else(|| panic!("no line for {} in\n{}", start, graph))
+}
+
+#[test]
+fn every_node_up_to_the_depth_cap_is_drawn() {
+    let graph = small_search(100);
+    assert!(graph.starts_with("digraph search {\n"));
+    assert!(graph.ends_with("}\n"));
+    assert_eq!(nodes(&graph), vec![0, 1, 2, 3, 4, 5]);
+
+    assert_eq!(
+        line(&graph, "n0 "),
+        "\tn0 [label=\"1 2 3\\n4 * 5\\n7 8 6\\ng = 0, h = 2\", shape=box, color=red, penwidth=2];"
+    );
+    assert!(line(&graph, "n3 ").ends_with("shape=box, style=dashed];"));
+    assert!(line(&graph, "n4 ").ends_with("shape=box, style=dotted, fontcolor=gray];"));
+
+    assert_eq!(line(&graph, "n0 -> n2"), "\tn0 -> n2 [label=\"left\"];");
+    assert_eq!(line(&graph, "n0 -> n4"), "\tn0 -> n4 [label=\"down\"];");
+    assert!(!graph.contains("n6"));
+}
+
+#[test]
+fn the_solution_path_is_highlighted() {
+    let graph = small_search(100);
+    let red = red(&graph);
+    assert_eq!(red.len(), 5);
+    assert!(red[0].starts_with("n0 [") && red[1].starts_with("n1 [") && red[2].starts_with("n5 ["));
+    assert_eq!(red[3], "n0 -> n1 [label=\"right\", color=red, penwidth=2];");
+    assert_eq!(red[4], "n1 -> n5 [label=\"down\", color=red, penwidth=2];");
+    assert!(line(&graph, "n5 ").contains("7 8 *\\ng = 2, h = 0"));
+}
+
+#[test]
+fn the_node_cap_keeps_the_solution_and_the_first_nodes() {
+    assert_eq!(nodes(&small_search(4)), vec![0, 1, 2, 5]);
+
+    // with no room for anything else the solution path is still drawn whole
+    let graph = small_search(1);
+    assert_eq!(nodes(&graph), vec![0, 1, 5]);
+    assert!(graph.contains("\tn1 -> n5"));
+
+    // edges to parents that were left out aren't drawn
+    let mut export = SearchTreeExport::new(GOAL.parse().unwrap(), 1, 5);
+    export.record(&event(
+        TraceEventKind::Generate,
+        0,
+        None,
+        0,
+        Moves::Nothing,
+        "123405786",
+    ));
+    export.record(&event(
+        TraceEventKind::Generate,
+        1,
+        Some(0),
+        1,
+        Moves::Right,
+        "123450786",
+    ));
+    let graph = write(&export, "no-parent");
+    assert_eq!(nodes(&graph), vec![0]);
+    assert!(!graph.contains("->"));
+}
+
+#[test]
+fn a_search_is_exported_with_its_solution() {
+    let initial: EightPuzzle = "123405786".parse().unwrap();
+    let goal = EightPuzzle::solved_like(&initial);
+    let export = SearchTreeExport::new(goal.clone(), 200, 10);
+    let (export, moves) = exported_search(Problem::new(initial, goal), export);
+
+    let (graph, left_out) = write_counting(&export, "search");
+    // the boards along the solution and the moves between them
+    assert_eq!(red(&graph).len(), 2 * moves + 1);
+    assert!(nodes(&graph).contains(&0));
+    assert_eq!((left_out.omitted, left_out.deeper), (0, 0));
+}
+
+#[test]
+fn a_goal_below_the_depth_cap_is_still_found() {
+    // two moves from the goal, with only the first of them above the cap
+    let initial: EightPuzzle = "123405786".parse().unwrap();
+    let goal = EightPuzzle::solved_like(&initial);
+    let export = SearchTreeExport::new(goal.clone(), 200, 1);
+    let (export, moves) = exported_search(Problem::new(initial, goal), export);
+    assert_eq!(moves, 2);
+
+    let (graph, left_out) = write_counting(&export, "deeper");
+    let red = red(&graph);
+    // the root, its child on the way and the move between them
+    assert_eq!(red.len(), 3);
+    assert!(red[0].starts_with("n0 ["));
+    assert!(red[2].starts_with("n0 -> "));
+    assert!(left_out.deeper > 0);
+    assert_eq!(left_out.omitted, 0);
+}
+
+#[test]
+fn a_goal_is_matched_by_its_colours() {
+    // 7 and 8 are both red, so the board is solved as it is
+    let initial: EightPuzzle = "1 2 3/4 5 6/8 7 0".parse().unwrap();
+    let goal = EightPuzzle::solved_like(&initial);
+    let colors = TileColors::parse("red=7,8").unwrap();
+    let problem = Problem {
+        colors: colors.clone(),
+        ..Problem::new(initial, goal.clone())
+    };
+    let export = SearchTreeExport::new(goal, 200, 10).with_colors(&colors);
+    let (export, moves) = exported_search(problem, export);
+    assert_eq!(moves, 0);
+
+    let graph = write(&export, "colors");
+    let red = red(&graph);
+    assert_eq!(red.len(), 1);
+    assert!(red[0].starts_with("n0 ["));
+}
@@ -0,0 +1,145 @@
+extern crate ai_homework;
+
+mod common;
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use ai_homework::external_bfs::{self, ExternalBfsOptions};
+use ai_homework::{EightPuzzle, Moves};
+use common::board;
+
+fn scratch_directory(name: &str) -> PathBuf {
+    let directory = env::temp_dir().join(format!("external-bfs-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    directory
+}
+
+// the same search in memory, as the boards at each depth
+fn layers_in_memory(goal: &EightPuzzle) -> Vec<Vec<EightPuzzle>> {
+    let mut seen = HashSet::new();
+    seen.insert(goal.clone());
+    let mut layers = vec![vec![goal.clone()]];
+
+    loop {
+        let mut next = Vec::new();
+        for puzzle in layers.last().unwrap() {
+            for direction in [Moves::Up, Moves::Down, Moves::Left, Moves::Right].iter() {
+                let child = puzzle.apply_move(*direction);
+                if seen.insert(child.clone()) {
+                    next.push(child);
+                }
+            }
+        }
+        if next.is_empty() {
+            return layers;
+        }
+        layers.push(next);
+    }
+}
+
+fn sorted(boards: &[EightPuzzle]) -> Vec<String> {
+    let mut text: Vec<String> = boards.iter().map(|board| board.to_string()).collect();
+    text.sort();
+    text
+}
+
+#[test]
+fn layers_match_the_search_in_memory() {
+    for goal in [board("1 2 3/4 5 6/7 8 0"), board("1 2 3 4/5 0 6 7")].iter() {
+        let directory = scratch_directory(&format!("{}x{}", goal.rows, goal.cols));
+        // a small buffer, so every layer past the first few is merged from several runs
+        let options = ExternalBfsOptions {
+            directory: directory.clone(),
+            buffer_boards: 1000,
+            ..ExternalBfsOptions::default()
+        };
+
+        let progress = external_bfs::run(goal, &options).unwrap();
+        let expected = layers_in_memory(goal);
+        let counts: Vec<u64> = expected.iter().map(|layer| layer.len() as u64).collect();
+        assert!(progress.finished);
+        assert_eq!(progress.layers, counts);
+        assert_eq!(
+            progress.boards() as usize,
+            (1..=goal.tiles.len()).product::<usize>() / 2
+        );
+
+        // the farthest boards are still on disk
+        let deepest = progress.layers.len() - 1;
+        let boards = external_bfs::layer_boards(goal, &directory, deepest).unwrap();
+        assert_eq!(sorted(&boards), sorted(&expected[deepest]));
+
+        // and running again just reads what's there
+        assert_eq!(external_bfs::run(goal, &options).unwrap(), progress);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
+
+#[test]
+fn a_stopped_search_carries_on() {
+    let goal = board("1 2 3 4/5 6 7 0");
+    let directory = scratch_directory("resumed");
+    let options = ExternalBfsOptions {
+        directory: directory.clone(),
+        buffer_boards: 500,
+        max_layers: Some(7),
+        ..ExternalBfsOptions::default()
+    };
+
+    let mut stops = 0;
+    let progress = loop {
+        let progress = external_bfs::run(&goal, &options).unwrap();
+        assert_eq!(
+            external_bfs::read_progress(&directory).unwrap(),
+            Some(progress.clone())
+        );
+        if progress.finished {
+            break progress;
+        }
+        stops += 1;
+        assert_eq!(progress.layers.len(), 1 + 7 * stops);
+
+        // a stop in the middle of a layer leaves its runs and half of it behind
+        fs::write(directory.join("run-0.bin"), [1, 2, 3]).unwrap();
+        fs::write(
+            directory.join(format!("layer-{}.bin.partial", progress.layers.len())),
+            [4],
+        )
+        .unwrap();
+    };
+    assert!(stops > 2);
+
+    let expected: Vec<u64> = layers_in_memory(&goal)
+        .iter()
+        .map(|layer| layer.len() as u64)
+        .collect();
+    assert_eq!(progress.layers, expected);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn what_cant_be_searched_is_refused() {
+    let directory = scratch_directory("refused");
+    let options = ExternalBfsOptions {
+        directory: directory.clone(),
+        max_layers: Some(1),
+        ..ExternalBfsOptions::default()
+    };
+
+    let refused = [
+        board("1 # 2/3 4 5/6 7 0"),
+        board("1 2 3/4 5 6/7 0 0"),
+        EightPuzzle::solved(5, 5),
+    ];
+    for goal in refused.iter() {
+        assert!(external_bfs::run(goal, &options).is_err(), "{}", goal);
+    }
+
+    // a directory holds the search for one goal
+    external_bfs::run(&board("1 2/3 0"), &options).unwrap();
+    assert!(external_bfs::run(&board("0 1/2 3"), &options).is_err());
+    fs::remove_dir_all(&directory).unwrap();
+}
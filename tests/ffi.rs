@@ -0,0 +1,66 @@
+extern crate ai_homework;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use ai_homework::ffi_header;
+
+fn repository() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+// the test binary lives in target/<profile>/deps, next to the cdylib cargo built for it
+fn library_directory() -> PathBuf {
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    if deps.join("libai_homework.so").exists() {
+        deps
+    } else {
+        deps.parent().unwrap().to_path_buf()
+    }
+}
+
+#[test]
+fn the_checked_in_header_is_up_to_date() {
+    let header = fs::read_to_string(repository().join("include/ai_homework.h")).unwrap();
+    assert!(
+        header == ffi_header::generate(),
+        "include/ai_homework.h is stale, run `ai_homework ffi-header > include/ai_homework.h`"
+    );
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn the_c_test_program_passes() {
+    let library = library_directory();
+    let program = env::temp_dir().join(format!("ai_homework_ffi_test_{}", std::process::id()));
+
+    let compiled = Command::new("cc")
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(repository().join("include"))
+        .arg(repository().join("tests/c/ffi_test.c"))
+        .arg("-L")
+        .arg(&library)
+        .arg("-lai_homework")
+        .arg(format!("-Wl,-rpath,{}", library.display()))
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("a C compiler is needed to build tests/c/ffi_test.c");
+    assert!(compiled.success());
+
+    let output = Command::new(&program).output().unwrap();
+    let _ = fs::remove_file(&program);
+
+    assert!(
+        output.status.success(),
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("all checks passed"));
+}
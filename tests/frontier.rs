@@ -0,0 +1,188 @@
+extern crate ai_homework;
+extern crate rand;
+
+mod common;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use ai_homework::colors::TileColors;
+use ai_homework::cost::CostModel;
+use ai_homework::frontier::{self, FrontierAlgorithm};
+use ai_homework::paths;
+use ai_homework::slides::MoveModel;
+use ai_homework::torus::Topology;
+use ai_homework::{
+    linear_conflict_heuristic, manhattan_distance_heuristic, search_with_heuristic, Algorithm,
+    EightPuzzle, Moves, Problem, SearchLimits, SearchOutcome, StopReason,
+};
+use common::{board, problem};
+
+const ALGORITHMS: [FrontierAlgorithm; 2] = [
+    FrontierAlgorithm::FrontierAStar,
+    FrontierAlgorithm::BreadthFirstHeuristic,
+];
+
+// a board some random moves from the goal
+fn wander(goal: &EightPuzzle, moves: usize, rng: &mut StdRng) -> EightPuzzle {
+    let directions = [Moves::Up, Moves::Down, Moves::Left, Moves::Right];
+    let mut puzzle = goal.clone();
+    for _ in 0..moves {
+        puzzle = puzzle.apply_move(*rng.choose(&directions).unwrap());
+    }
+    puzzle
+}
+
+#[test]
+fn paths_are_as_cheap_as_astars() {
+    let mut rng = StdRng::seed_from_u64(49);
+    let goals = [
+        EightPuzzle::solved(3, 3),
+        EightPuzzle::solved(2, 4),
+        EightPuzzle::solved(3, 4),
+        board("1 2 #/3 4 5/6 7 0"),
+        EightPuzzle::solved(3, 3).with_topology(Topology::Torus),
+    ];
+
+    for goal in goals.iter() {
+        for _ in 0..4 {
+            let problem = problem(&wander(goal, 30, &mut rng), goal);
+            let (astar, _) = search_with_heuristic(
+                problem.clone(),
+                &manhattan_distance_heuristic,
+                Algorithm::AStar,
+                SearchLimits::default(),
+            );
+            let cheapest = astar.solution().unwrap().cost;
+
+            for algorithm in ALGORITHMS.iter() {
+                let (outcome, stats) = frontier::search(
+                    &problem,
+                    &manhattan_distance_heuristic,
+                    *algorithm,
+                    SearchLimits::default(),
+                )
+                .unwrap();
+                let solution = outcome.solution().unwrap();
+                assert_eq!(
+                    solution.cost, cheapest,
+                    "{:?} on {}",
+                    algorithm, problem.initial_state
+                );
+                assert_eq!(paths::check_path(&problem, &solution.moves), Ok(cheapest));
+                assert_eq!(solution.slides.len(), solution.moves.len());
+                assert!(stats.searches >= 1);
+            }
+        }
+    }
+}
+
+#[test]
+fn fewer_boards_are_kept_than_by_astar() {
+    let goal = EightPuzzle::solved(3, 3);
+    let problem = problem(&board("8 7 6/0 4 1/2 5 3"), &goal);
+
+    let (astar, astar_stats) = search_with_heuristic(
+        problem.clone(),
+        &linear_conflict_heuristic,
+        Algorithm::AStar,
+        SearchLimits::default(),
+    );
+    // A* keeps every board it expanded and every one in its queue
+    let astar_stored = astar_stats.expanded as usize + astar_stats.max_queue;
+
+    for algorithm in ALGORITHMS.iter() {
+        let (outcome, stats) = frontier::search(
+            &problem,
+            &linear_conflict_heuristic,
+            *algorithm,
+            SearchLimits::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            outcome.solution().unwrap().cost,
+            astar.solution().unwrap().cost
+        );
+        assert!(
+            stats.max_stored * 2 < astar_stored,
+            "{:?} kept {} boards, A* {}",
+            algorithm,
+            stats.max_stored,
+            astar_stored
+        );
+        assert!(stats.closed_boards > stats.max_stored as u64);
+    }
+}
+
+#[test]
+fn unsolvable_and_stopped_searches_say_so() {
+    let goal = EightPuzzle::solved(2, 3);
+    let swapped = problem(&board("2 1 3/4 5 0"), &goal);
+    let hard = problem(&board("5 4 3/2 1 0"), &goal);
+    let limits = SearchLimits {
+        max_expansions: Some(10),
+        ..SearchLimits::default()
+    };
+
+    for algorithm in ALGORITHMS.iter() {
+        let (outcome, stats) = frontier::search(
+            &swapped,
+            &manhattan_distance_heuristic,
+            *algorithm,
+            SearchLimits::default(),
+        )
+        .unwrap();
+        assert!(
+            matches!(outcome, SearchOutcome::Unsolvable),
+            "{:?}",
+            algorithm
+        );
+        // half the boards of a 2x3 board can't reach the goal, the other half are all seen
+        assert!(stats.expanded >= 360);
+
+        let (outcome, _) = frontier::search(
+            &hard,
+            &manhattan_distance_heuristic,
+            *algorithm,
+            limits.clone(),
+        )
+        .unwrap();
+        assert!(matches!(
+            outcome,
+            SearchOutcome::Stopped(StopReason::ExpansionLimit)
+        ));
+    }
+}
+
+#[test]
+fn what_cant_be_searched_is_refused() {
+    let goal = EightPuzzle::solved(3, 3);
+    let plain = problem(&goal.apply_move(Moves::Up), &goal);
+
+    let refused = [
+        Problem {
+            move_model: MoveModel::MultiTile,
+            ..plain.clone()
+        },
+        Problem {
+            cost_model: CostModel::TileNumber,
+            ..plain.clone()
+        },
+        Problem {
+            colors: TileColors::parse("red=1,2").unwrap(),
+            ..plain.clone()
+        },
+        problem(&board("1 2 3/4 5 6/7 0 0"), &board("1 2 3/4 5 6/7 0 0")),
+    ];
+    for problem in refused.iter() {
+        for algorithm in ALGORITHMS.iter() {
+            assert!(frontier::search(
+                problem,
+                &manhattan_distance_heuristic,
+                *algorithm,
+                SearchLimits::default()
+            )
+            .is_err());
+        }
+    }
+}
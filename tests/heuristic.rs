@@ -0,0 +1,177 @@
+extern crate ai_homework;
+
+mod common;
+
+use std::collections::HashMap;
+
+use ai_homework::analysis;
+use ai_homework::heuristic::{parse_heuristic, AdditiveHeuristic, Heuristic, TileManhattan};
+use ai_homework::schema::{self, DocumentFormat, ResultDocument};
+use ai_homework::{
+    linear_conflict_heuristic, manhattan_distance_heuristic, search_with_heuristic, Algorithm,
+    EightPuzzle, Problem, SearchLimits,
+};
+use common::board;
+
+fn problem(initial: &str) -> Problem {
+    let initial_state: EightPuzzle = initial.parse().unwrap();
+    let goal_state = EightPuzzle::solved(initial_state.rows, initial_state.cols);
+    Problem::new(initial_state, goal_state)
+}
+
+#[test]
+fn closures_can_carry_data() {
+    // the true distances as a lookup table, the best heuristic there is
+    let goal = EightPuzzle::solved(2, 3);
+    let distances: HashMap<EightPuzzle, u64> =
+        analysis::exact_distances(&goal).into_iter().collect();
+    let perfect = move |puzzle: &EightPuzzle, _goal: &EightPuzzle| distances[puzzle];
+
+    let (outcome, stats) = search_with_heuristic(
+        problem("4 1 2/5 0 3"),
+        &perfect,
+        Algorithm::AStar,
+        SearchLimits::default(),
+    );
+    let moves = outcome.solution().unwrap().moves.len();
+    assert_eq!(stats.expanded, moves as u64);
+    assert!(stats.max_components.is_empty());
+}
+
+#[test]
+fn max_reports_which_part_won() {
+    let heuristic = parse_heuristic("max(manhattan, linear-conflict)").unwrap();
+    let swapped = board("2 1 3/4 5 6/7 8 0");
+    let goal = EightPuzzle::solved(3, 3);
+    assert_eq!(heuristic.estimate(&swapped, &goal), 4);
+
+    let (outcome, stats) = search_with_heuristic(
+        problem("871602543"),
+        &*heuristic,
+        Algorithm::AStar,
+        SearchLimits::default(),
+    );
+    assert!(outcome.solution().is_some());
+
+    let names: Vec<&str> = stats
+        .max_components
+        .iter()
+        .map(|component| component.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["manhattan", "linear-conflict"]);
+    assert!(stats.max_components[1].count > 0);
+
+    // the counts start over with every search
+    let (_, again) = search_with_heuristic(
+        problem("871602543"),
+        &*heuristic,
+        Algorithm::AStar,
+        SearchLimits::default(),
+    );
+    assert_eq!(again.max_components, stats.max_components);
+}
+
+#[test]
+fn counts_are_saved_with_results() {
+    let problem = problem("1 2 3/4 0 6/7 5 8");
+    let heuristic = parse_heuristic("max(misplaced, manhattan)").unwrap();
+    let (outcome, stats) = search_with_heuristic(
+        problem.clone(),
+        &*heuristic,
+        Algorithm::AStar,
+        SearchLimits::default(),
+    );
+    let result = ResultDocument::new(
+        &problem,
+        Algorithm::AStar,
+        "max(misplaced, manhattan)",
+        &outcome,
+        stats,
+    );
+    assert_eq!(result.stats.max_components.len(), 2);
+
+    let text = schema::to_string(&result, DocumentFormat::Json).unwrap();
+    let reloaded: ResultDocument = schema::from_str(&text, DocumentFormat::Json).unwrap();
+    assert_eq!(reloaded, result);
+}
+
+#[test]
+fn disjoint_patterns_add_up() {
+    let goal = EightPuzzle::solved(3, 3);
+    let sum = parse_heuristic("sum(manhattan[1 2 3 4], manhattan[5 6 7 8])").unwrap();
+
+    for (puzzle, _) in analysis::exact_distances(&goal).iter().step_by(1001) {
+        assert_eq!(
+            sum.estimate(puzzle, &goal),
+            manhattan_distance_heuristic(puzzle, &goal)
+        );
+    }
+
+    let overlapping = vec![
+        Box::new(TileManhattan::new(vec![1, 2]).unwrap()) as Box<dyn Heuristic>,
+        Box::new(TileManhattan::new(vec![2, 3]).unwrap()),
+    ];
+    assert!(AdditiveHeuristic::new(overlapping).is_err());
+    assert!(parse_heuristic("sum(manhattan, misplaced)").is_err());
+}
+
+#[test]
+fn scaling_multiplies_the_estimate() {
+    let goal = EightPuzzle::solved(3, 3);
+    let puzzle = board("871602543");
+
+    let double = parse_heuristic("scale(2, manhattan)").unwrap();
+    assert_eq!(
+        double.estimate(&puzzle, &goal),
+        2 * manhattan_distance_heuristic(&puzzle, &goal)
+    );
+
+    let half = parse_heuristic("scale(0.5, manhattan)").unwrap();
+    assert_eq!(
+        half.estimate(&puzzle, &goal),
+        manhattan_distance_heuristic(&puzzle, &goal) / 2
+    );
+
+    // a weighted sum of disjoint patterns
+    let weighted = parse_heuristic("sum(scale(2, manhattan[1 2 3 4]), manhattan[5 6 7 8])");
+    assert!(weighted.is_ok());
+
+    assert!(parse_heuristic("scale(-1, manhattan)").is_err());
+}
+
+#[test]
+fn fallback_skips_parts_that_cant_handle_the_board() {
+    let heuristic = parse_heuristic("fallback(walking, linear-conflict)").unwrap();
+
+    let big_goal = EightPuzzle::solved(5, 5);
+    let mut big = big_goal.clone();
+    big.tiles.swap(0, 1);
+    assert_eq!(
+        heuristic.estimate(&big, &big_goal),
+        linear_conflict_heuristic(&big, &big_goal)
+    );
+
+    let goal = EightPuzzle::solved(3, 3);
+    let small = board("871602543");
+    assert_eq!(
+        heuristic.estimate(&small, &goal),
+        parse_heuristic("walking").unwrap().estimate(&small, &goal)
+    );
+}
+
+#[test]
+fn bad_specs_are_refused() {
+    for spec in [
+        "nope",
+        "max(manhattan",
+        "max(manhattan,)",
+        "pick(manhattan)",
+        "manhattan[0 1]",
+        "misplaced[1 2]",
+        "scale(manhattan)",
+    ]
+    .iter()
+    {
+        assert!(parse_heuristic(spec).is_err(), "{} was accepted", spec);
+    }
+}
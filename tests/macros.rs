@@ -0,0 +1,178 @@
+extern crate ai_homework;
+extern crate rand;
+
+mod common;
+
+use std::env;
+use std::fs;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use ai_homework::macros::{self, MacroTable};
+use ai_homework::notation::parse_moves;
+use ai_homework::paths;
+use ai_homework::schema;
+use ai_homework::{
+    manhattan_distance_heuristic, search_with_heuristic, Algorithm, EightPuzzle, SearchLimits,
+    BLANK,
+};
+use common::{board, problem};
+
+// a random board the goal can be reached from, two tiles are swapped back when the parity's wrong
+fn scramble(goal: &EightPuzzle, rng: &mut StdRng) -> EightPuzzle {
+    let mut puzzle = goal.clone();
+    rng.shuffle(&mut puzzle.tiles);
+
+    if !problem(&puzzle, goal).is_solvable() {
+        let tiles: Vec<usize> = (0..puzzle.tiles.len())
+            .filter(|cell| puzzle.tiles[*cell] != BLANK)
+            .take(2)
+            .collect();
+        puzzle.tiles.swap(tiles[0], tiles[1]);
+    }
+    puzzle
+}
+
+// every macro, played from the earlier tiles in place, puts its tile in place without moving them
+fn check_table(table: &MacroTable) {
+    let goal = &table.goal;
+
+    for (stage, macros) in table.macros.iter().enumerate() {
+        let tile = table.order[stage];
+        for (cell, text) in macros {
+            // a board with the tile in the cell and the earlier tiles in place
+            let mut puzzle = goal.clone();
+            let home = goal.tiles.iter().position(|other| *other == tile).unwrap();
+            puzzle.tiles.swap(home, *cell);
+            assert!(
+                table.order[..stage].iter().all(|earlier| puzzle
+                    .tiles
+                    .iter()
+                    .position(|other| other == earlier)
+                    == goal.tiles.iter().position(|other| other == earlier)),
+                "tile {} in cell {}",
+                tile,
+                cell
+            );
+
+            let mut end = puzzle.clone();
+            for direction in parse_moves(text).unwrap() {
+                let moved = end.apply_move(direction);
+                assert_ne!(moved, end, "{} runs off the board", text);
+                end = moved;
+            }
+            for placed in table.order[..=stage].iter() {
+                assert_eq!(
+                    end.tiles.iter().position(|other| other == placed),
+                    goal.tiles.iter().position(|other| other == placed),
+                    "{} for tile {} from cell {}",
+                    text,
+                    tile,
+                    cell
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn learned_tables_put_each_tile_in_place() {
+    for goal in [board("1 2 3/4 5 6/7 8 0"), board("1 2 3 4/0 5 6 7")].iter() {
+        let table = macros::learn(goal, macros::DEFAULT_MAX_BOARDS).unwrap();
+        assert_eq!(table.order[0], BLANK);
+        assert_eq!(table.order.len(), goal.tiles.len());
+        check_table(&table);
+
+        // and it's the same table after a trip through a file
+        let path = env::temp_dir().join(format!("macros-{}x{}-test.json", goal.rows, goal.cols));
+        schema::write_document(&path, &table).unwrap();
+        let read: MacroTable = schema::read_document(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(read, table);
+    }
+}
+
+#[test]
+fn tables_solve_any_board_in_bounded_moves() {
+    let mut rng = StdRng::seed_from_u64(46);
+
+    for goal in [board("1 2 3/4 0 5/6 7 8"), board("1 2 3 4/5 6 7 0")].iter() {
+        let table = macros::learn(goal, macros::DEFAULT_MAX_BOARDS).unwrap();
+
+        for _ in 0..50 {
+            let puzzle = scramble(goal, &mut rng);
+            let problem = problem(&puzzle, goal);
+
+            let moves = macros::solve(&table, &problem).unwrap();
+            assert!(moves.len() <= table.longest());
+            assert_eq!(paths::check_path(&problem, &moves), Ok(moves.len() as u64));
+        }
+
+        let solved = macros::solve(&table, &problem(goal, goal)).unwrap();
+        assert!(solved.is_empty());
+    }
+}
+
+#[test]
+fn searching_with_macros_still_finds_the_cheapest_path() {
+    let mut rng = StdRng::seed_from_u64(47);
+    let goal = board("1 2 3 4/5 6 7 0");
+    let table = macros::learn(&goal, macros::DEFAULT_MAX_BOARDS).unwrap();
+
+    for _ in 0..5 {
+        let puzzle = scramble(&goal, &mut rng);
+        let problem = problem(&puzzle, &goal);
+
+        let (plain, _) = search_with_heuristic(
+            problem.clone(),
+            &manhattan_distance_heuristic,
+            Algorithm::AStar,
+            SearchLimits::default(),
+        );
+        let (with_macros, _) = macros::search_with_macros(
+            problem.clone(),
+            &manhattan_distance_heuristic,
+            &table,
+            Algorithm::AStar,
+            SearchLimits::default(),
+        )
+        .unwrap();
+        let cheapest = plain.solution().unwrap().cost;
+        let solution = with_macros.solution().unwrap();
+        assert_eq!(solution.cost, cheapest);
+        assert_eq!(paths::check_path(&problem, &solution.moves), Ok(cheapest));
+
+        let (greedy, _) = macros::search_with_macros(
+            problem.clone(),
+            &manhattan_distance_heuristic,
+            &table,
+            Algorithm::Greedy,
+            SearchLimits::default(),
+        )
+        .unwrap();
+        let solution = greedy.solution().unwrap();
+        assert_eq!(
+            paths::check_path(&problem, &solution.moves),
+            Ok(solution.moves.len() as u64)
+        );
+        assert_eq!(solution.cost, solution.moves.len() as u64);
+    }
+}
+
+#[test]
+fn what_the_table_cant_solve_is_refused() {
+    let goal = board("1 2 3 4/5 6 7 0");
+    let table = macros::learn(&goal, macros::DEFAULT_MAX_BOARDS).unwrap();
+
+    // another goal, the wrong parity and a board with walls
+    let other = board("1 2 3 4/0 5 6 7");
+    assert!(macros::solve(&table, &problem(&other, &other)).is_err());
+    let swapped = board("2 1 3 4/5 6 7 0");
+    assert!(macros::solve(&table, &problem(&swapped, &goal)).is_err());
+    let walled = board("1 # 2 3/4 5 6 0");
+    assert!(macros::solve(&table, &problem(&walled, &walled)).is_err());
+
+    // and learning stops at the limit
+    assert!(macros::learn(&EightPuzzle::solved(4, 4), 10_000).is_err());
+}
@@ -0,0 +1,156 @@
+extern crate ai_homework;
+extern crate rand;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use ai_homework::notation::{
+    moves_to_string, parse_moves, parse_puzzle, parse_slides, slides_to_string, ParseMovesError,
+    ParseSlidesError,
+};
+use ai_homework::parse::{PuzzleError, Spaces};
+use ai_homework::slides::Slide;
+use ai_homework::{EightPuzzle, Moves};
+
+const MOVES: [Moves; 4] = [Moves::Up, Moves::Down, Moves::Left, Moves::Right];
+
+#[test]
+fn boards_read_back_as_they_are_written() {
+    let mut rng = StdRng::seed_from_u64(31);
+    for &(rows, cols) in [(2, 2), (3, 3), (2, 5), (4, 4), (5, 3)].iter() {
+        let mut puzzle = EightPuzzle::solved(rows, cols);
+        for _ in 0..40 {
+            puzzle = puzzle.apply_move(*rng.choose(&MOVES).unwrap());
+        }
+        let text = puzzle.to_string();
+        assert_eq!(text.split('/').count(), rows);
+        assert_eq!(text.parse::<EightPuzzle>(), Ok(puzzle));
+    }
+
+    let walled: EightPuzzle = "1 2 #/3 4 5/6 7 0".parse().unwrap();
+    assert_eq!(walled.to_string(), "1 2 #/3 4 5/6 7 0");
+
+    let spaces = parse_puzzle("1 0 0/2 # 0", Spaces::Several).unwrap();
+    assert_eq!(spaces.to_string(), "1 0 0/2 # 0");
+    assert_eq!(
+        parse_puzzle(&spaces.to_string(), Spaces::Several),
+        Ok(spaces)
+    );
+}
+
+#[test]
+fn square_boards_can_be_written_in_one_row() {
+    let puzzle: EightPuzzle = "1 2 3/4 5 6/7 8 0".parse().unwrap();
+    assert_eq!("1 2 3 4 5 6 7 8 0".parse(), Ok(puzzle.clone()));
+    assert_eq!(" 123456780\n".parse(), Ok(puzzle));
+
+    let big: EightPuzzle = "1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 0".parse().unwrap();
+    assert_eq!((big.rows, big.cols), (4, 4));
+    assert_eq!(big, EightPuzzle::solved(4, 4));
+
+    assert_eq!(
+        "1234567".parse::<EightPuzzle>().unwrap_err().0,
+        vec![PuzzleError::UnknownShape { tiles: 7 }]
+    );
+    assert_eq!(
+        "0".parse::<EightPuzzle>().unwrap_err().0,
+        vec![PuzzleError::UnknownShape { tiles: 1 }]
+    );
+    assert_eq!(
+        "1 2 3/4 5/6 7 8".parse::<EightPuzzle>().unwrap_err().0,
+        vec![PuzzleError::WrongRowLength {
+            row: 1,
+            expected: 3,
+            found: 2
+        }]
+    );
+}
+
+#[test]
+fn moves_read_back_as_they_are_written() {
+    let mut rng = StdRng::seed_from_u64(31);
+    let moves: Vec<Moves> = (0..50).map(|_| *rng.choose(&MOVES).unwrap()).collect();
+    let text = moves_to_string(&moves);
+    assert_eq!(text.len(), 50);
+    assert_eq!(parse_moves(&text), Ok(moves));
+
+    assert_eq!(moves_to_string(&[]), "");
+    assert_eq!(parse_moves(""), Ok(Vec::new()));
+    assert_eq!(
+        parse_moves(" r rD\tu l "),
+        Ok(vec![
+            Moves::Right,
+            Moves::Right,
+            Moves::Down,
+            Moves::Up,
+            Moves::Left
+        ])
+    );
+
+    let error = parse_moves("RR DX").unwrap_err();
+    assert_eq!(
+        error,
+        ParseMovesError {
+            position: 5,
+            found: 'X'
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "character 5: 'X' is not a move, use U, D, L or R"
+    );
+    assert!(parse_moves(".").is_err());
+}
+
+#[test]
+fn slides_read_back_as_they_are_written() {
+    let slides = vec![
+        Slide {
+            row: 2,
+            col: 2,
+            direction: Moves::Up,
+            tiles: 1,
+        },
+        Slide {
+            row: 0,
+            col: 1,
+            direction: Moves::Down,
+            tiles: 2,
+        },
+        Slide {
+            row: 11,
+            col: 9,
+            direction: Moves::Right,
+            tiles: 10,
+        },
+    ];
+    let text = slides_to_string(&slides);
+    assert_eq!(text, "3,3U 1,2D2 12,10R10");
+    assert_eq!(parse_slides(&text), Ok(slides));
+    assert_eq!(parse_slides("  "), Ok(Vec::new()));
+
+    for (text, slide, bad) in [
+        ("1,1U 0,1D", 2, "0,1D"),
+        ("1,1X", 1, "1,1X"),
+        ("1,1U0", 1, "1,1U0"),
+        ("11U", 1, "11U"),
+        ("1,U", 1, "1,U"),
+        ("1,2", 1, "1,2"),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            parse_slides(text),
+            Err(ParseSlidesError {
+                slide: *slide,
+                text: bad.to_string()
+            }),
+            "{}",
+            text
+        );
+    }
+    assert_eq!(
+        parse_slides("2,x").unwrap_err().to_string(),
+        "slide 1: \"2,x\" is not a slide, write them like 2,3U or 2,3U2"
+    );
+}
@@ -0,0 +1,212 @@
+extern crate ai_homework;
+extern crate rand;
+
+mod common;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use ai_homework::colors::TileColors;
+use ai_homework::cost::CostModel;
+use ai_homework::optimal::{self, OptimalOutcome};
+use ai_homework::slides::{self, MoveModel, Slide};
+use ai_homework::torus::Topology;
+use ai_homework::{
+    manhattan_distance_heuristic, EightPuzzle, Moves, Problem, SearchLimits, StopReason,
+};
+use common::{board, problem};
+
+// a board some random moves from the goal
+fn wander(goal: &EightPuzzle, moves: usize, rng: &mut StdRng) -> EightPuzzle {
+    let directions = [Moves::Up, Moves::Down, Moves::Left, Moves::Right];
+    let mut puzzle = goal.clone();
+    for _ in 0..moves {
+        puzzle = puzzle.apply_move(*rng.choose(&directions).unwrap());
+    }
+    puzzle
+}
+
+// every path costing exactly what's left, tried move by move without remembering any board. when
+// one tile moves at a time for at least 1 each, its manhattan distance can't be more than what's left
+fn every_path(
+    problem: &Problem,
+    puzzle: &EightPuzzle,
+    left: u64,
+    path: &mut Vec<Slide>,
+    found: &mut Vec<Vec<Slide>>,
+) {
+    let classes = problem.colors.classes();
+    if classes.canonical(puzzle) == classes.canonical(&problem.goal_state) {
+        if left == 0 {
+            found.push(path.clone());
+        }
+        return;
+    }
+    let moves_one_tile = problem.move_model == MoveModel::SingleTile && problem.colors.is_empty();
+    if moves_one_tile && manhattan_distance_heuristic(puzzle, &problem.goal_state) > left {
+        return;
+    }
+
+    for (slide, child) in slides::slides(puzzle, problem.move_model) {
+        let cost = problem
+            .cost_model
+            .slide_cost(&slides::slid_tiles(puzzle, &slide), slide.direction);
+        if cost <= left {
+            path.push(slide);
+            every_path(problem, &child, left - cost, path, found);
+            path.pop();
+        }
+    }
+}
+
+fn found(problem: &Problem) -> optimal::OptimalGraph {
+    let (outcome, _) = optimal::optimal_paths(
+        problem,
+        &manhattan_distance_heuristic,
+        SearchLimits::default(),
+    )
+    .unwrap();
+    match outcome {
+        OptimalOutcome::Found(graph) => graph,
+        _ => panic!("{} should have been solved", problem.initial_state),
+    }
+}
+
+#[test]
+fn counts_match_trying_every_path() {
+    let mut rng = StdRng::seed_from_u64(50);
+    let plain = [
+        EightPuzzle::solved(3, 3),
+        EightPuzzle::solved(2, 4),
+        board("1 2 #/3 4 5/6 7 0"),
+        EightPuzzle::solved(3, 3).with_topology(Topology::Torus),
+    ];
+    let mut problems: Vec<Problem> = Vec::new();
+    for goal in plain.iter() {
+        for _ in 0..3 {
+            problems.push(problem(&wander(goal, 12, &mut rng), goal));
+        }
+    }
+
+    // several spaces pushing several tiles, tiles costing their number and tiles of one colour
+    let goal = board("1 2 3/4 5 6/7 0 0");
+    let mut several = problem(&wander(&goal, 8, &mut rng), &goal);
+    several.move_model = MoveModel::MultiTile;
+    problems.push(several);
+    let goal = EightPuzzle::solved(3, 3);
+    let mut costed = problem(&wander(&goal, 12, &mut rng), &goal);
+    costed.cost_model = CostModel::TileNumber;
+    problems.push(costed);
+    let mut colored = problem(&wander(&goal, 8, &mut rng), &goal);
+    colored.colors = TileColors::parse("red=1,2,3 blue=4,5").unwrap();
+    problems.push(colored);
+
+    for problem in problems.iter() {
+        let graph = found(problem);
+        let mut expected = Vec::new();
+        every_path(
+            problem,
+            &problem.initial_state,
+            graph.cost,
+            &mut Vec::new(),
+            &mut expected,
+        );
+
+        let paths: Vec<Vec<Slide>> = graph.paths().collect();
+        assert_eq!(
+            graph.count(),
+            expected.len() as u128,
+            "{}",
+            problem.initial_state
+        );
+        // the same paths in the same order, the order slides::slides gives the moves in
+        assert_eq!(paths, expected, "{}", problem.initial_state);
+        assert!(graph.boards() <= paths.iter().map(|path| path.len() + 1).sum());
+    }
+}
+
+#[test]
+fn paths_are_made_as_they_are_asked_for() {
+    // two tiles each crossing a 3x3 corner of their own, in one of 6 ways, with their 4 moves each
+    // taken in any of 70 orders. far more than are asked for, but the first few come straight away
+    let initial = board("1 0 0 0 0 0/0 0 0 0 0 0/0 0 0 0 0 0/0 0 0 2 0 0/0 0 0 0 0 0/0 0 0 0 0 0");
+    let goal = board("0 0 0 0 0 0/0 0 0 0 0 0/0 0 1 0 0 0/0 0 0 0 0 0/0 0 0 0 0 0/0 0 0 0 0 2");
+    let problem = problem(&initial, &goal);
+    let graph = found(&problem);
+    assert_eq!((graph.cost, graph.count()), (8, 6 * 6 * 70));
+
+    let first: Vec<Vec<Slide>> = graph.paths().take(50).collect();
+    assert_eq!(first.len(), 50);
+    for (index, path) in first.iter().enumerate() {
+        assert_eq!(slides::apply_slides(&initial, path), Some(goal.clone()));
+        assert_eq!(path.len() as u64, graph.cost);
+        assert!(!first[..index].contains(path));
+    }
+}
+
+#[test]
+fn the_goal_unsolvable_and_stopped_boards() {
+    let goal = EightPuzzle::solved(2, 3);
+
+    let graph = found(&problem(&goal, &goal));
+    assert_eq!((graph.cost, graph.count(), graph.boards()), (0, 1, 1));
+    assert_eq!(graph.paths().collect::<Vec<_>>(), vec![Vec::<Slide>::new()]);
+
+    let one_move = problem(&goal.apply_move(Moves::Left), &goal);
+    let graph = found(&one_move);
+    assert_eq!((graph.cost, graph.count()), (1, 1));
+
+    let (outcome, _) = optimal::optimal_paths(
+        &problem(&board("2 1 3/4 5 0"), &goal),
+        &manhattan_distance_heuristic,
+        SearchLimits::default(),
+    )
+    .unwrap();
+    assert!(matches!(outcome, OptimalOutcome::Unsolvable));
+
+    let limits = SearchLimits {
+        max_expansions: Some(10),
+        ..SearchLimits::default()
+    };
+    let (outcome, stats) = optimal::optimal_paths(
+        &problem(&board("5 4 3/2 1 0"), &goal),
+        &manhattan_distance_heuristic,
+        limits,
+    )
+    .unwrap();
+    assert!(matches!(
+        outcome,
+        OptimalOutcome::Stopped(StopReason::ExpansionLimit)
+    ));
+    assert!(outcome.graph().is_none());
+    assert_eq!(stats.expanded, 10);
+}
+
+#[test]
+fn free_moves_are_refused() {
+    // sliding tiles up and down costs nothing, so they could go back and forth forever
+    let goal = EightPuzzle::solved(2, 3);
+    let mut free = problem(&board("1 2 3/4 0 5"), &goal);
+    free.cost_model = CostModel::Directions {
+        up: 0,
+        down: 0,
+        left: 1,
+        right: 1,
+    };
+    assert!(optimal::optimal_paths(
+        &free,
+        &manhattan_distance_heuristic,
+        SearchLimits::default()
+    )
+    .is_err());
+
+    free.cost_model = CostModel::TileWeights {
+        weights: vec![(5, 0)].into_iter().collect(),
+    };
+    assert!(optimal::optimal_paths(
+        &free,
+        &manhattan_distance_heuristic,
+        SearchLimits::default()
+    )
+    .is_err());
+}
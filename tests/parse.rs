@@ -0,0 +1,196 @@
+extern crate ai_homework;
+
+use ai_homework::parse::{parse_puzzle_rows, parse_puzzle_rows_with, PuzzleError, Spaces};
+
+fn errors(rows: &[&str], expected_rows: usize, expected_cols: usize) -> Vec<PuzzleError> {
+    parse_puzzle_rows(rows, expected_rows, expected_cols)
+        .unwrap_err()
+        .0
+}
+
+#[test]
+fn good_rows_make_a_board() {
+    let puzzle = parse_puzzle_rows(&["1 2 3", "4 0 5", "7 8 6"], 3, 3).unwrap();
+    assert_eq!((puzzle.rows, puzzle.cols), (3, 3));
+    assert_eq!(puzzle.tiles, vec![1, 2, 3, 4, 0, 5, 7, 8, 6]);
+
+    let puzzle = parse_puzzle_rows(&["  1\t2 ", "0 3"], 2, 2).unwrap();
+    assert_eq!(puzzle.tiles, vec![1, 2, 0, 3]);
+
+    let puzzle = parse_puzzle_rows_with(&["1 0 0", "2 # 0"], 2, 3, Spaces::Several).unwrap();
+    assert_eq!(puzzle.tiles.iter().filter(|tile| **tile == 0).count(), 3);
+}
+
+#[test]
+fn the_shape_is_checked_before_the_tiles() {
+    assert_eq!(
+        errors(&[], 0, 3),
+        vec![PuzzleError::BadSize { rows: 0, cols: 3 }]
+    );
+
+    // a missing row and a short one, the tiles they'd need aren't reported on top
+    assert_eq!(
+        errors(&["1 2 3", "4 5"], 3, 3),
+        vec![
+            PuzzleError::WrongRowCount {
+                expected: 3,
+                found: 2
+            },
+            PuzzleError::WrongRowLength {
+                row: 1,
+                expected: 3,
+                found: 2
+            },
+        ]
+    );
+
+    assert_eq!(
+        errors(&["1 2 3 4", "5 6 0", "7 8 9"], 3, 3),
+        vec![
+            PuzzleError::WrongRowLength {
+                row: 0,
+                expected: 3,
+                found: 4
+            },
+            PuzzleError::TileOutOfRange {
+                row: 2,
+                col: 2,
+                tile: 9,
+                largest: 8
+            },
+        ]
+    );
+}
+
+#[test]
+fn bad_tokens_are_reported_where_they_are() {
+    assert_eq!(
+        errors(&["1 x 3", "4 0 5", "7 8 -6"], 3, 3),
+        vec![
+            PuzzleError::NotANumber {
+                row: 0,
+                col: 1,
+                token: "x".to_string()
+            },
+            PuzzleError::TileOutOfRange {
+                row: 2,
+                col: 2,
+                tile: -6,
+                largest: 8
+            },
+        ]
+    );
+}
+
+#[test]
+fn every_tile_problem_is_reported_at_once() {
+    assert_eq!(
+        errors(&["1 2 3", "4 5 5", "0 7 0"], 3, 3),
+        vec![
+            PuzzleError::DuplicateTile {
+                row: 1,
+                col: 2,
+                tile: 5,
+                first_row: 1,
+                first_col: 1
+            },
+            PuzzleError::MultipleBlanks {
+                positions: vec![(2, 0), (2, 2)]
+            },
+            PuzzleError::MissingTile { tile: 6 },
+            PuzzleError::MissingTile { tile: 8 },
+        ]
+    );
+
+    assert_eq!(
+        errors(&["1 2", "3 3"], 2, 2),
+        vec![
+            PuzzleError::DuplicateTile {
+                row: 1,
+                col: 1,
+                tile: 3,
+                first_row: 1,
+                first_col: 0
+            },
+            PuzzleError::NoBlank,
+        ]
+    );
+}
+
+#[test]
+fn walls_lower_the_largest_tile() {
+    assert_eq!(
+        errors(&["1 2 #", "3 4 5", "6 7 8"], 3, 3),
+        vec![PuzzleError::TileOutOfRange {
+            row: 2,
+            col: 2,
+            tile: 8,
+            largest: 7
+        }]
+    );
+}
+
+#[test]
+fn messages_count_rows_and_columns_from_one() {
+    let messages: Vec<String> = [
+        PuzzleError::BadSize { rows: 0, cols: 3 },
+        PuzzleError::UnknownShape { tiles: 5 },
+        PuzzleError::WrongRowCount {
+            expected: 3,
+            found: 2,
+        },
+        PuzzleError::WrongRowLength {
+            row: 1,
+            expected: 3,
+            found: 2,
+        },
+        PuzzleError::NotANumber {
+            row: 0,
+            col: 1,
+            token: "x".to_string(),
+        },
+        PuzzleError::TileOutOfRange {
+            row: 2,
+            col: 2,
+            tile: 9,
+            largest: 8,
+        },
+        PuzzleError::DuplicateTile {
+            row: 1,
+            col: 2,
+            tile: 5,
+            first_row: 1,
+            first_col: 1,
+        },
+        PuzzleError::MissingTile { tile: 6 },
+        PuzzleError::NoBlank,
+        PuzzleError::MultipleBlanks {
+            positions: vec![(2, 0), (2, 2)],
+        },
+    ]
+    .iter()
+    .map(|error| error.to_string())
+    .collect();
+
+    assert_eq!(
+        messages,
+        vec![
+            "a 0x3 board can't be played",
+            "5 tiles don't make a square board, put a / between rows",
+            "expected 3 rows but got 2",
+            "row 2 has 2 tiles but should have 3",
+            "row 1, column 2: \"x\" is not a number",
+            "row 3, column 3: 9 is out of range, tiles go from 0 to 8",
+            "row 2, column 3: tile 5 is already at row 2, column 2",
+            "tile 6 is missing",
+            "there is no blank, use a 0 for it",
+            "there should be exactly one blank but there are 2: row 3, column 1; row 3, column 3",
+        ]
+    );
+
+    let all = parse_puzzle_rows(&["1 2", "3 3"], 2, 2).unwrap_err();
+    assert_eq!(
+        all.to_string(),
+        "row 2, column 2: tile 3 is already at row 2, column 1; there is no blank, use a 0 for it"
+    );
+}
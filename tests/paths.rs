@@ -0,0 +1,206 @@
+extern crate ai_homework;
+extern crate rand;
+
+mod common;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use ai_homework::colors::TileColors;
+use ai_homework::cost::CostModel;
+use ai_homework::notation::parse_moves;
+use ai_homework::paths::{self, PathError};
+use ai_homework::{
+    manhattan_distance_heuristic, search_with_heuristic, Algorithm, EightPuzzle, Moves, Problem,
+    SearchLimits,
+};
+use common::board;
+
+fn problem(initial: &EightPuzzle, costs: CostModel) -> Problem {
+    Problem {
+        cost_model: costs,
+        ..Problem::new(initial.clone(), EightPuzzle::solved_like(initial))
+    }
+}
+
+fn solve(problem: &Problem, algorithm: Algorithm) -> Vec<Moves> {
+    let (outcome, _) = search_with_heuristic(
+        problem.clone(),
+        &manhattan_distance_heuristic,
+        algorithm,
+        SearchLimits::default(),
+    );
+    outcome.solution().unwrap().moves.clone()
+}
+
+// moves picked at random, only ones that can be made, and the board they end at
+fn random_moves(
+    puzzle: &EightPuzzle,
+    length: usize,
+    rng: &mut StdRng,
+) -> (Vec<Moves>, EightPuzzle) {
+    let all = [Moves::Up, Moves::Down, Moves::Left, Moves::Right];
+    let mut moves = Vec::new();
+    let mut board = puzzle.clone();
+
+    while moves.len() < length {
+        let direction = all[rng.gen_range(0, all.len())];
+        let next = board.apply_move(direction);
+        if next != board {
+            moves.push(direction);
+            board = next;
+        }
+    }
+
+    (moves, board)
+}
+
+// wanders off from the board and back, leaving a path that ends where it started
+fn wander(puzzle: &EightPuzzle, length: usize, rng: &mut StdRng) -> Vec<Moves> {
+    let (mut moves, end) = random_moves(puzzle, length, rng);
+    let back = Problem {
+        goal_state: puzzle.clone(),
+        ..problem(&end, CostModel::Unit)
+    };
+    moves.extend(solve(&back, Algorithm::AStar));
+    moves
+}
+
+#[test]
+fn paths_are_checked_move_by_move() {
+    let usual = problem(&board("1 2 3/4 5 6/7 0 8"), CostModel::TileNumber);
+    assert_eq!(paths::check_path(&usual, &parse_moves("R").unwrap()), Ok(8));
+    assert_eq!(
+        paths::check_path(&usual, &parse_moves("LRUDR").unwrap()),
+        Ok(7 + 7 + 5 + 5 + 8)
+    );
+
+    // off the edge
+    assert_eq!(
+        paths::check_path(&usual, &parse_moves("DR").unwrap()),
+        Err(PathError::IllegalMove {
+            step: 1,
+            direction: Moves::Down
+        })
+    );
+    // not far enough
+    match paths::check_path(&usual, &parse_moves("U").unwrap()) {
+        Err(PathError::NotAtGoal { end }) => assert_eq!(end, board("1 2 3/4 0 6/7 5 8")),
+        other => panic!("{:?}", other),
+    }
+
+    // into a wall, which moving the board on its own quietly ignores
+    let walled = problem(&board("1 # 2/3 0 4/5 6 7"), CostModel::Unit);
+    let up = walled.initial_state.apply_move(Moves::Up);
+    assert_eq!(up, walled.initial_state);
+    let error = paths::check_path(&walled, &parse_moves("URD").unwrap()).unwrap_err();
+    assert_eq!(
+        error,
+        PathError::IllegalMove {
+            step: 1,
+            direction: Moves::Up
+        }
+    );
+    assert!(error.to_string().contains("move 1 (U)"));
+
+    // with colours only the colours have to be right at the end
+    let colored = Problem {
+        colors: TileColors::parse("red=7,8").unwrap(),
+        ..problem(&board("1 2 3/4 5 6/8 7 0"), CostModel::Unit)
+    };
+    assert_eq!(paths::check_path(&colored, &[]), Ok(0));
+    assert!(paths::check_path(&problem(&colored.initial_state, CostModel::Unit), &[]).is_err());
+}
+
+#[test]
+fn loops_come_out() {
+    let mut rng = StdRng::seed_from_u64(44);
+    let initial = board("1 2 3/4 0 5/7 8 6");
+    let usual = problem(&initial, CostModel::Unit);
+    let best = solve(&usual, Algorithm::AStar);
+
+    for _ in 0..10 {
+        // a detour at the start, back and forth pairs and the way to the goal
+        let mut moves = wander(&initial, 8, &mut rng);
+        moves.extend(parse_moves("LRUD").unwrap());
+        moves.extend(best.iter().cloned());
+        assert!(paths::check_path(&usual, &moves).is_ok());
+
+        // only cutting the loops already gets back to the best path
+        let shorter = paths::shorten_path(&usual, &moves, 1).unwrap();
+        assert_eq!(shorter, best);
+    }
+
+    // a path that's already as short as it gets stays as it is
+    assert_eq!(
+        paths::shorten_path(&usual, &best, paths::DEFAULT_WINDOW).unwrap(),
+        best
+    );
+    assert!(paths::shorten_path(&usual, &parse_moves("U").unwrap(), 4).is_err());
+}
+
+#[test]
+fn stretches_are_solved_again() {
+    let mut rng = StdRng::seed_from_u64(45);
+    let goal = board("1 2 3 4/5 6 7 0");
+    let costs = [CostModel::Unit, CostModel::TileNumber];
+    let mut improved = 0;
+
+    for costs in costs.iter() {
+        for _ in 0..4 {
+            // a walk comes back on itself too much to end far off, so the tiles are shuffled
+            let mut shuffled = problem(&goal, costs.clone());
+            while shuffled.initial_state == goal || !shuffled.is_solvable() {
+                rng.shuffle(&mut shuffled.initial_state.tiles);
+            }
+
+            let greedy = solve(&shuffled, Algorithm::Greedy);
+            let greedy_cost = paths::check_path(&shuffled, &greedy).unwrap();
+            let best_cost =
+                paths::check_path(&shuffled, &solve(&shuffled, Algorithm::AStar)).unwrap();
+
+            let shorter = paths::shorten_path(&shuffled, &greedy, 8).unwrap();
+            let shorter_cost = paths::check_path(&shuffled, &shorter).unwrap();
+            assert!(shorter_cost <= greedy_cost);
+            assert!(shorter_cost >= best_cost);
+            if shorter_cost < greedy_cost {
+                improved += 1;
+            }
+
+            // a window as long as the whole path finds the best one
+            if greedy.len() <= 24 {
+                let best = paths::shorten_path(&shuffled, &greedy, 24).unwrap();
+                assert_eq!(paths::check_path(&shuffled, &best), Ok(best_cost));
+            }
+        }
+    }
+
+    assert!(improved > 0);
+}
+
+#[test]
+fn walls_and_colours_are_kept_to() {
+    let mut rng = StdRng::seed_from_u64(46);
+
+    let walled = problem(&board("1 2 #/3 0 4/5 6 7"), CostModel::Unit);
+    let mut moves = wander(&walled.initial_state, 10, &mut rng);
+    moves.extend(solve(&walled, Algorithm::AStar));
+    let shorter = paths::shorten_path(&walled, &moves, 6).unwrap();
+    assert_eq!(
+        paths::check_path(&walled, &shorter),
+        paths::check_path(&walled, &solve(&walled, Algorithm::AStar))
+    );
+
+    // the red tiles can end either way round
+    let colored = Problem {
+        colors: TileColors::parse("red=1,2").unwrap(),
+        ..problem(&board("2 1 3/4 5 6/7 0 8"), CostModel::Unit)
+    };
+    assert!(paths::check_path(&colored, &parse_moves("R").unwrap()).is_ok());
+    let mut padded = wander(&colored.initial_state, 6, &mut rng);
+    padded.push(Moves::Right);
+    assert_eq!(
+        paths::shorten_path(&colored, &padded, paths::DEFAULT_WINDOW).unwrap(),
+        vec![Moves::Right]
+    );
+}
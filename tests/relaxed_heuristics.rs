@@ -0,0 +1,122 @@
+extern crate ai_homework;
+
+mod common;
+
+use ai_homework::analysis::{self, AnalysisOptions};
+use ai_homework::{
+    gaschnig_heuristic, heuristic_from_name, inversion_distance_heuristic,
+    manhattan_distance_heuristic, misplaced_tile_heuristic, EightPuzzle,
+};
+use common::board;
+
+type Heuristic = fn(&EightPuzzle, &EightPuzzle) -> u64;
+
+const HEURISTICS: [(&str, Heuristic); 2] = [
+    ("gaschnig", gaschnig_heuristic),
+    ("inversion-distance", inversion_distance_heuristic),
+];
+
+// checks the heuristic against the true distance of every board that can reach the goal
+fn assert_admissible(name: &str, heuristic: Heuristic, goal: &EightPuzzle) {
+    for (puzzle, distance) in analysis::exact_distances(goal) {
+        let estimate = heuristic(&puzzle, goal);
+        assert!(
+            estimate <= distance,
+            "{} guessed {} for {:?}, which is {} moves away",
+            name,
+            estimate,
+            puzzle.tiles,
+            distance
+        );
+    }
+}
+
+#[test]
+fn both_are_admissible_and_consistent_on_every_3x3_board() {
+    let options = AnalysisOptions {
+        searches: 3,
+        ..AnalysisOptions::default()
+    };
+
+    for (name, heuristic) in HEURISTICS.iter() {
+        let report = analysis::analyze(heuristic, &options);
+        assert!(report.exhaustive);
+        assert_eq!(report.boards, 181_440);
+        assert!(report.is_admissible(), "{} overestimates", name);
+        assert!(report.is_consistent(), "{} is inconsistent", name);
+        assert_eq!(report.suboptimal_searches, 0);
+    }
+}
+
+#[test]
+fn both_are_admissible_for_other_goals_and_sizes() {
+    let goals = [
+        board("0 1 2/3 4 5/6 7 8"),
+        board("1 2 3/8 0 4/7 6 5"),
+        board("1 2 3 4/5 6 7 0"),
+        board("1 2/3 4/5 0"),
+    ];
+
+    for goal in goals.iter() {
+        for (name, heuristic) in HEURISTICS.iter() {
+            assert_admissible(name, *heuristic, goal);
+        }
+    }
+}
+
+#[test]
+fn known_values() {
+    let goal = EightPuzzle::solved(3, 3);
+    let two_away = board("1 2 3/4 5 6/0 7 8");
+
+    for (_, heuristic) in HEURISTICS.iter() {
+        assert_eq!(heuristic(&goal, &goal), 0);
+        assert_eq!(heuristic(&two_away, &goal), 2);
+    }
+
+    // the blank starts home, so it has to swap out once before it can sort 1 and 2
+    let rotated = board("3 1 2/4 5 6/7 8 0");
+    assert_eq!(gaschnig_heuristic(&rotated, &goal), 4);
+    assert_eq!(misplaced_tile_heuristic(&rotated, &goal), 3);
+
+    // read across the rows 3 is ahead of 1 and 2, one vertical move could fix both. read down the
+    // columns there are 10 pairs out of order and a horizontal move fixes at most 2 of them
+    assert_eq!(inversion_distance_heuristic(&rotated, &goal), 1 + 5);
+}
+
+#[test]
+fn gaschnig_never_counts_fewer_than_the_misplaced_tiles() {
+    let goal = EightPuzzle::solved(3, 3);
+
+    for (puzzle, _) in analysis::exact_distances(&goal).iter().step_by(89) {
+        let misplaced = puzzle
+            .tiles
+            .iter()
+            .zip(goal.tiles.iter())
+            .filter(|(tile, goal_tile)| **tile != 0 && tile != goal_tile)
+            .count() as u64;
+        assert!(gaschnig_heuristic(puzzle, &goal) >= misplaced);
+    }
+}
+
+#[test]
+fn inversion_distance_beats_manhattan_on_the_15_puzzle_sometimes() {
+    let goal = EightPuzzle::solved(4, 4);
+    // the first row reversed keeps every tile close but puts them badly out of order
+    let puzzle = board("4 3 2 1/5 6 7 8/9 10 11 12/13 14 15 0");
+
+    assert_eq!(manhattan_distance_heuristic(&puzzle, &goal), 8);
+    assert!(inversion_distance_heuristic(&puzzle, &goal) > 8);
+}
+
+#[test]
+fn both_have_names() {
+    for (name, heuristic) in HEURISTICS.iter() {
+        let goal = EightPuzzle::solved(3, 3);
+        let puzzle = board("871602543");
+        assert_eq!(
+            heuristic_from_name(name).unwrap()(&puzzle, &goal),
+            heuristic(&puzzle, &goal)
+        );
+    }
+}
@@ -0,0 +1,123 @@
+extern crate ai_homework;
+
+use ai_homework::schema::{
+    self, CostModel, DocumentFormat, ProblemDocument, ResultDocument, SolveStatus,
+};
+use ai_homework::{
+    manhattan_distance_heuristic, search_with_options, Algorithm, EightPuzzle, Moves, Problem,
+    SearchLimits,
+};
+
+fn problem(initial: &str) -> Problem {
+    let initial_state: EightPuzzle = initial.parse().unwrap();
+    let goal_state = EightPuzzle::solved(initial_state.rows, initial_state.cols);
+    Problem::new(initial_state, goal_state)
+}
+
+fn solve(problem: &Problem) -> ResultDocument {
+    let (outcome, stats) = search_with_options(
+        problem.clone(),
+        manhattan_distance_heuristic,
+        Algorithm::AStar,
+        SearchLimits::default(),
+    );
+    ResultDocument::new(problem, Algorithm::AStar, "manhattan", &outcome, stats)
+}
+
+fn round_trip<T>(document: &T, format: DocumentFormat) -> T
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    let text = schema::to_string(document, format).unwrap();
+    schema::from_str(&text, format).unwrap()
+}
+
+#[test]
+fn problems_round_trip() {
+    let document = ProblemDocument::new(&problem("1 2 3/4 0 6/7 5 8"));
+
+    assert_eq!(round_trip(&document, DocumentFormat::Json), document);
+    assert_eq!(round_trip(&document, DocumentFormat::Yaml), document);
+}
+
+#[test]
+fn solved_results_reload_exactly() {
+    let problem = problem("1 2 3/4 0 6/7 5 8");
+    let result = solve(&problem);
+    assert_eq!(result.status, SolveStatus::Solved);
+    assert_eq!(result.path, Some(vec![Moves::Down, Moves::Right]));
+    assert_eq!(result.cost, Some(2));
+
+    for format in [DocumentFormat::Json, DocumentFormat::Yaml].iter() {
+        let reloaded = round_trip(&result, *format);
+        assert_eq!(reloaded, result);
+
+        let text = schema::to_string(&result, *format).unwrap();
+        assert_eq!(schema::to_string(&reloaded, *format).unwrap(), text);
+    }
+}
+
+#[test]
+fn unsolvable_results_reload_exactly() {
+    let result = solve(&problem("2 1/3 0"));
+    assert_eq!(result.status, SolveStatus::Unsolvable);
+    assert_eq!(result.path, None);
+    assert_eq!(result.cost, None);
+
+    assert_eq!(round_trip(&result, DocumentFormat::Json), result);
+    assert_eq!(round_trip(&result, DocumentFormat::Yaml), result);
+}
+
+#[test]
+fn reloaded_paths_reach_the_goal() {
+    let problem = problem("0 1 2/4 5 3/7 8 6");
+    let text = schema::to_string(&solve(&problem), DocumentFormat::Json).unwrap();
+    let reloaded: ResultDocument = schema::from_str(&text, DocumentFormat::Json).unwrap();
+
+    let reloaded_problem = reloaded.problem.to_problem().unwrap();
+    let mut puzzle = reloaded_problem.initial_state;
+    for space_move in reloaded.path.unwrap() {
+        puzzle = puzzle.apply_move(space_move);
+    }
+    assert_eq!(puzzle, reloaded_problem.goal_state);
+}
+
+#[test]
+fn short_problems_get_defaults() {
+    let text = "version: 1\ninitial: \"1 2 3 4 5 6 7 8 9 10 11 12 13 14 0 15\"\n";
+    let document: ProblemDocument = schema::from_str(text, DocumentFormat::Yaml).unwrap();
+    assert_eq!(document.cost_model, CostModel::Unit);
+
+    let problem = document.to_problem().unwrap();
+    assert_eq!(problem.goal_state, EightPuzzle::solved(4, 4));
+}
+
+#[test]
+fn bad_documents_are_refused() {
+    let bad = [
+        r#"{"version": 2, "initial": "123456780"}"#,
+        r#"{"initial": "123456780"}"#,
+        r#"{"version": 1, "initial": "123456780", "heuristic": "manhattan"}"#,
+        r#"{"version": 1, "initial": [[1, 2, 3], [4, 5, 6], [7, 8, 8]]}"#,
+        r#"{"version": 1, "initial": [[1, 2], [3, 0]], "cost_model": {"type": "free"}}"#,
+    ];
+
+    for text in bad.iter() {
+        assert!(
+            schema::from_str::<ProblemDocument>(text, DocumentFormat::Json).is_err(),
+            "{} was accepted",
+            text
+        );
+    }
+}
+
+#[test]
+fn mismatched_sizes_are_refused() {
+    let text = r#"{"version": 1, "size": {"rows": 3, "cols": 3}, "initial": "1 2/3 0"}"#;
+    let document: ProblemDocument = schema::from_str(text, DocumentFormat::Json).unwrap();
+    assert!(document.to_problem().is_err());
+
+    let text = r#"{"version": 1, "initial": "1 2/3 0", "goal": "123456780"}"#;
+    let document: ProblemDocument = schema::from_str(text, DocumentFormat::Json).unwrap();
+    assert!(document.to_problem().is_err());
+}
@@ -0,0 +1,273 @@
+extern crate ai_homework;
+
+use std::io::prelude::*;
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use ai_homework::notation::parse_moves;
+use ai_homework::server::{self, ServerConfig, ServerHandle};
+use ai_homework::EightPuzzle;
+use serde_json::{json, Value};
+
+fn start(max_solves: usize) -> ServerHandle {
+    start_with_ttl(max_solves, 600_000)
+}
+
+fn start_with_ttl(max_solves: usize, job_ttl_ms: u64) -> ServerHandle {
+    server::start(ServerConfig {
+        address: "127.0.0.1:0".to_string(),
+        max_solves,
+        max_timeout_ms: 10_000,
+        workers: 4,
+        job_ttl_ms,
+    })
+    .unwrap()
+}
+
+// a bare HTTP/1.1 client, one connection per request
+fn request(address: SocketAddr, method: &str, path: &str, body: Option<&Value>) -> (u16, Value) {
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        method,
+        path,
+        address,
+        body.len(),
+        body
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let status = response[9..12].parse().unwrap();
+    let start = response.find("\r\n\r\n").unwrap() + 4;
+    (status, serde_json::from_str(&response[start..]).unwrap())
+}
+
+fn solve_request(initial: &str) -> Value {
+    json!({ "problem": { "version": 1, "initial": initial } })
+}
+
+fn wait_for_job(address: SocketAddr, id: u64) -> Value {
+    for _ in 0..200 {
+        let (status, reply) = request(address, "GET", &format!("/jobs/{}", id), None);
+        assert_eq!(status, 200);
+        if reply["status"] == "done" {
+            return reply["result"].clone();
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    panic!("job {} never finished", id);
+}
+
+#[test]
+fn solves_problems() {
+    let handle = start(2);
+    let address = handle.address();
+
+    let (status, result) = request(address, "POST", "/solve", Some(&solve_request("871602543")));
+    assert_eq!(status, 200);
+    assert_eq!(result["status"], "solved");
+    assert_eq!(result["algorithm"], "astar");
+
+    let path = parse_moves(result["path"].as_str().unwrap()).unwrap();
+    assert_eq!(result["cost"], path.len() as u64);
+
+    let mut puzzle: EightPuzzle = "871602543".parse().unwrap();
+    for space_move in path {
+        puzzle = puzzle.apply_move(space_move);
+    }
+    assert_eq!(puzzle, EightPuzzle::solved(3, 3));
+
+    handle.shutdown();
+}
+
+#[test]
+fn algorithms_and_heuristics_can_be_chosen() {
+    let handle = start(2);
+    let address = handle.address();
+
+    let mut body = solve_request("1 2 3/4 0 6/7 5 8");
+    body["algorithm"] = json!("greedy");
+    body["heuristic"] = json!("misplaced");
+    let (status, result) = request(address, "POST", "/solve", Some(&body));
+    assert_eq!(status, 200);
+    assert_eq!(result["algorithm"], "greedy");
+    assert_eq!(result["heuristic"], "misplaced");
+    assert_eq!(result["path"], "DR");
+
+    body["heuristic"] = json!("max(manhattan, linear-conflict)");
+    let (status, result) = request(address, "POST", "/solve", Some(&body));
+    assert_eq!(status, 200);
+    assert_eq!(
+        result["stats"]["max_components"][1]["name"],
+        "linear-conflict"
+    );
+
+    body["heuristic"] = json!("nope");
+    let (status, reply) = request(address, "POST", "/solve", Some(&body));
+    assert_eq!(status, 400);
+    assert!(reply["error"].as_str().unwrap().contains("nope"));
+
+    handle.shutdown();
+}
+
+#[test]
+fn searches_stop_at_their_limits() {
+    let handle = start(2);
+    let address = handle.address();
+
+    let mut body = solve_request("871602543");
+    body["max_expansions"] = json!(10);
+    let (_, result) = request(address, "POST", "/solve", Some(&body));
+    assert_eq!(result["status"], "expansion_limit");
+    assert_eq!(result["stats"]["expanded"], 10);
+    assert_eq!(result["path"], Value::Null);
+
+    let mut body = solve_request("123456870");
+    body["timeout_ms"] = json!(0);
+    let (_, result) = request(address, "POST", "/solve", Some(&body));
+    assert_eq!(result["status"], "time_limit");
+
+    handle.shutdown();
+}
+
+#[test]
+fn checks_solvability() {
+    let handle = start(1);
+    let address = handle.address();
+
+    let problem = json!({ "version": 1, "initial": "123456870" });
+    let (status, reply) = request(address, "POST", "/solvable", Some(&problem));
+    assert_eq!(status, 200);
+    assert_eq!(reply["solvable"], false);
+
+    let problem = json!({ "version": 1, "initial": "1 2 3 4/5 6 7 8/9 10 11 12/13 14 0 15" });
+    let (_, reply) = request(address, "POST", "/solvable", Some(&problem));
+    assert_eq!(reply["solvable"], true);
+
+    handle.shutdown();
+}
+
+#[test]
+fn generates_random_problems() {
+    let handle = start(1);
+    let address = handle.address();
+
+    let (status, first) = request(address, "GET", "/random?rows=4&cols=4&seed=7", None);
+    assert_eq!(status, 200);
+    let (_, second) = request(address, "GET", "/random?rows=4&cols=4&seed=7", None);
+    assert_eq!(first, second);
+    assert_eq!(first["size"], json!({ "rows": 4, "cols": 4 }));
+
+    for seed in 0..10 {
+        for solvable in [true, false].iter() {
+            let path = format!("/random?seed={}&solvable={}", seed, solvable);
+            let (_, problem) = request(address, "GET", &path, None);
+            let (_, reply) = request(address, "POST", "/solvable", Some(&problem));
+            assert_eq!(reply["solvable"], *solvable);
+        }
+    }
+
+    let (status, _) = request(address, "GET", "/random?rows=1", None);
+    assert_eq!(status, 400);
+
+    handle.shutdown();
+}
+
+#[test]
+fn long_solves_run_as_jobs() {
+    let handle = start(2);
+    let address = handle.address();
+
+    let (status, reply) = request(address, "POST", "/jobs", Some(&solve_request("012453786")));
+    assert_eq!(status, 202);
+    assert_eq!(reply["status"], "running");
+
+    let id = reply["id"].as_u64().unwrap();
+    let result = wait_for_job(address, id);
+    assert_eq!(result["status"], "solved");
+    assert_eq!(result["cost"], 4);
+
+    // the result is handed out once and then forgotten
+    let (status, _) = request(address, "GET", &format!("/jobs/{}", id), None);
+    assert_eq!(status, 404);
+
+    let (status, _) = request(address, "GET", "/jobs/999", None);
+    assert_eq!(status, 404);
+
+    handle.shutdown();
+}
+
+#[test]
+fn results_nobody_fetches_are_forgotten() {
+    let handle = start_with_ttl(2, 0);
+    let address = handle.address();
+
+    let (status, reply) = request(address, "POST", "/jobs", Some(&solve_request("012453786")));
+    assert_eq!(status, 202);
+    let path = format!("/jobs/{}", reply["id"]);
+
+    // the job can be asked about while it runs, and is gone as soon as it's done
+    for _ in 0..200 {
+        let (status, reply) = request(address, "GET", &path, None);
+        if status == 404 {
+            handle.shutdown();
+            return;
+        }
+        assert_eq!(reply["status"], "running");
+        thread::sleep(Duration::from_millis(50));
+    }
+    panic!("the job was never forgotten");
+}
+
+#[test]
+fn refuses_solves_past_the_limit() {
+    let handle = start(1);
+    let address = handle.address();
+
+    // an unsolvable 4x4 board keeps the only solve busy until its timeout
+    let mut body = solve_request("1 2 3 4/5 6 7 8/9 10 11 12/13 15 14 0");
+    body["timeout_ms"] = json!(500);
+    let (status, job) = request(address, "POST", "/jobs", Some(&body));
+    assert_eq!(status, 202);
+
+    let (status, reply) = request(address, "POST", "/solve", Some(&solve_request("123456708")));
+    assert_eq!(status, 503);
+    assert!(reply["error"].is_string());
+
+    let result = wait_for_job(address, job["id"].as_u64().unwrap());
+    assert_eq!(result["status"], "time_limit");
+
+    let (status, _) = request(address, "POST", "/solve", Some(&solve_request("123456708")));
+    assert_eq!(status, 200);
+
+    handle.shutdown();
+}
+
+#[test]
+fn bad_requests_get_errors() {
+    let handle = start(1);
+    let address = handle.address();
+
+    let (status, _) = request(address, "GET", "/nowhere", None);
+    assert_eq!(status, 404);
+
+    let (status, _) = request(address, "POST", "/solve", Some(&json!({ "problem": 3 })));
+    assert_eq!(status, 400);
+
+    let (status, reply) = request(
+        address,
+        "POST",
+        "/solve",
+        Some(&solve_request("1 2 3/4 5 6/7 8 8")),
+    );
+    assert_eq!(status, 400);
+    assert!(reply["error"].as_str().unwrap().contains("tile 8"));
+
+    handle.shutdown();
+}
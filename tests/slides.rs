@@ -0,0 +1,342 @@
+extern crate ai_homework;
+extern crate rand;
+
+mod common;
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::io;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use ai_homework::cost::CostModel;
+use ai_homework::heuristic::{parse_heuristic, Heuristic};
+use ai_homework::notation::{parse_puzzle, parse_slides, slides_to_string};
+use ai_homework::parse::{PuzzleError, Spaces};
+use ai_homework::schema::{self, DocumentFormat, ProblemDocument, ResultDocument};
+use ai_homework::slides::{self, MoveModel, Slide, SlideCountHeuristic};
+use ai_homework::{
+    manhattan_distance_heuristic, playback, search_with_heuristic, Algorithm, EightPuzzle, Moves,
+    Problem, SearchLimits,
+};
+use common::board;
+
+fn problem(initial: &EightPuzzle, costs: &CostModel, moves: MoveModel) -> Problem {
+    Problem {
+        cost_model: costs.clone(),
+        move_model: moves,
+        ..Problem::new(initial.clone(), EightPuzzle::solved_like(initial))
+    }
+}
+
+fn opposite(space_move: Moves) -> Moves {
+    match space_move {
+        Moves::Up => Moves::Down,
+        Moves::Down => Moves::Up,
+        Moves::Left => Moves::Right,
+        Moves::Right => Moves::Left,
+        Moves::Nothing => Moves::Nothing,
+    }
+}
+
+// the cheapest cost from every board to the goal, by dijkstra backwards from the goal. every slide
+// can be undone by pushing the same tiles back, so the boards one slide away are the ones before
+fn cheapest_costs(
+    goal: &EightPuzzle,
+    moves: MoveModel,
+    costs: &CostModel,
+) -> HashMap<EightPuzzle, u64> {
+    let mut best: HashMap<EightPuzzle, u64> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    best.insert(goal.clone(), 0);
+    queue.push(Reverse((0, goal.tiles.clone())));
+
+    while let Some(Reverse((cost, tiles))) = queue.pop() {
+        let puzzle = EightPuzzle {
+            rows: goal.rows,
+            cols: goal.cols,
+            tiles,
+            topology: goal.topology,
+        };
+        if best[&puzzle] < cost {
+            continue;
+        }
+
+        for (slide, before) in slides::slides(&puzzle, moves) {
+            let pushed = slides::slid_tiles(&puzzle, &slide);
+            let total = cost + costs.slide_cost(&pushed, opposite(slide.direction));
+
+            if best.get(&before).is_none_or(|known| total < *known) {
+                best.insert(before.clone(), total);
+                queue.push(Reverse((total, before.tiles)));
+            }
+        }
+    }
+
+    best
+}
+
+#[test]
+fn boards_can_have_several_spaces() {
+    let puzzle = board("1 0 2/0 3 4");
+    assert_eq!(slides::space_count(&puzzle), 2);
+    assert_eq!(puzzle.to_string(), "1 0 2/0 3 4");
+    assert_eq!(EightPuzzle::solved_like(&puzzle), board("1 2 3/4 0 0"));
+    assert_eq!(
+        EightPuzzle::solved_like(&board("1 0 #/0 2 3")),
+        board("1 2 #/3 0 0")
+    );
+
+    // with two spaces the tiles only go up to 4
+    assert!(parse_puzzle("1 0 2/0 3 5", Spaces::Several).is_err());
+    assert!(parse_puzzle("1 0 2/0 3 3", Spaces::Several).is_err());
+
+    // unless asked for, a second space is a mistake, like a missing 8 on the usual board
+    let errors = "123456700".parse::<EightPuzzle>().unwrap_err();
+    assert_eq!(
+        errors.0,
+        vec![
+            PuzzleError::MultipleBlanks {
+                positions: vec![(2, 1), (2, 2)]
+            },
+            PuzzleError::MissingTile { tile: 8 },
+        ]
+    );
+
+    // the goal needs as many spaces
+    let text = r#"{"version": 1, "initial": "1 0 2/0 3 4", "goal": "1 2 3/4 5 0"}"#;
+    let document: ProblemDocument = schema::from_str(text, DocumentFormat::Json).unwrap();
+    assert!(document.to_problem().is_err());
+}
+
+#[test]
+fn spaces_push_runs_of_tiles() {
+    let puzzle = board("1 2 3/4 5 6/7 8 0");
+    assert_eq!(slides::slides(&puzzle, MoveModel::SingleTile).len(), 2);
+    assert_eq!(slides::slides(&puzzle, MoveModel::MultiTile).len(), 4);
+
+    let up_two = Slide {
+        row: 2,
+        col: 2,
+        direction: Moves::Up,
+        tiles: 2,
+    };
+    assert_eq!(slides::slid_tiles(&puzzle, &up_two), vec![6, 3]);
+    assert_eq!(
+        slides::apply_slide(&puzzle, &up_two),
+        Some(board("1 2 0/4 5 3/7 8 6"))
+    );
+    let too_far = Slide { tiles: 3, ..up_two };
+    assert_eq!(slides::apply_slide(&puzzle, &too_far), None);
+
+    // a space stops at another space and at a wall
+    let puzzle = board("1 # 0/2 3 0");
+    let moves: Vec<Slide> = slides::slides(&puzzle, MoveModel::MultiTile)
+        .into_iter()
+        .map(|(slide, _)| slide)
+        .collect();
+    assert_eq!(slides_to_string(&moves), "2,3L 2,3L2");
+    assert_eq!(parse_slides("2,3L 2,3L2").unwrap(), moves);
+    assert!(parse_slides("2,3L 0,1U").is_err());
+    assert!(parse_slides("2,3X").is_err());
+}
+
+#[test]
+fn solvability_matches_a_full_search() {
+    let mut rng = StdRng::seed_from_u64(41);
+    let goals = [
+        "1 2/0 0",
+        "1 2 3/4 0 0",
+        "1 2 0 0",
+        "1 2 3 4/5 6 0 0",
+        "1 # 2/3 4 5/6 0 0",
+        "1 2 #/3 4 #/5 0 0",
+    ];
+
+    for text in goals.iter() {
+        let goal = board(text);
+        let reachable = cheapest_costs(&goal, MoveModel::SingleTile, &CostModel::Unit);
+        let open: Vec<usize> = (0..goal.tiles.len())
+            .filter(|cell| goal.tiles[*cell] != ai_homework::WALL)
+            .collect();
+
+        for _ in 0..40 {
+            let mut puzzle = goal.clone();
+            let mut tiles: Vec<u16> = open.iter().map(|cell| goal.tiles[*cell]).collect();
+            rng.shuffle(&mut tiles);
+            for (cell, tile) in open.iter().zip(tiles) {
+                puzzle.tiles[*cell] = tile;
+            }
+
+            let shuffled = problem(&puzzle, &CostModel::Unit, MoveModel::SingleTile);
+            assert_eq!(
+                shuffled.is_solvable(),
+                reachable.contains_key(&puzzle),
+                "{} on {}",
+                puzzle,
+                text
+            );
+        }
+    }
+}
+
+#[test]
+fn searches_find_the_fewest_moves() {
+    let specs = [
+        "manhattan",
+        "linear-conflict",
+        "walking",
+        "gaschnig",
+        "inversion-distance",
+    ];
+
+    for text in ["1 2 3/4 5 0", "1 2 3/4 0 0", "1 2/3 4/0 0"].iter() {
+        let goal = board(text);
+
+        for moves in [MoveModel::SingleTile, MoveModel::MultiTile].iter() {
+            let distances = cheapest_costs(&goal, *moves, &CostModel::Unit);
+
+            for spec in specs.iter() {
+                let heuristic = parse_heuristic(spec).unwrap();
+
+                // every estimate is admissible once it counts slides
+                let counted = SlideCountHeuristic::new(&*heuristic, &goal);
+                for (puzzle, distance) in distances.iter() {
+                    let estimate = match moves {
+                        MoveModel::SingleTile => heuristic.estimate(puzzle, &goal),
+                        MoveModel::MultiTile => counted.estimate(puzzle, &goal),
+                    };
+                    assert!(
+                        estimate <= *distance,
+                        "{} guessed {} for {}, {} {} moves away",
+                        spec,
+                        estimate,
+                        puzzle,
+                        distance,
+                        moves.name()
+                    );
+                }
+
+                for (puzzle, distance) in distances.iter().step_by(distances.len() / 6) {
+                    let (outcome, _) = search_with_heuristic(
+                        problem(puzzle, &CostModel::Unit, *moves),
+                        &*heuristic,
+                        Algorithm::AStar,
+                        SearchLimits::default(),
+                    );
+                    let solution = outcome.solution().unwrap();
+
+                    assert_eq!(solution.slides.len() as u64, *distance);
+                    assert_eq!(solution.cost, *distance);
+                    assert_eq!(
+                        slides::apply_slides(puzzle, &solution.slides),
+                        Some(goal.clone())
+                    );
+                    if *moves == MoveModel::SingleTile {
+                        assert!(solution.slides.iter().all(|slide| slide.tiles == 1));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn slides_are_priced_by_their_dearest_tile() {
+    let goal = board("1 2 3/4 0 0");
+    let mut weights = BTreeMap::new();
+    weights.insert(2, 5);
+    let cost_models = [
+        CostModel::TileNumber,
+        CostModel::TileWeights { weights },
+        CostModel::Directions {
+            up: 3,
+            down: 1,
+            left: 2,
+            right: 1,
+        },
+    ];
+
+    assert_eq!(CostModel::TileNumber.slide_cost(&[3, 1], Moves::Up), 3);
+    assert_eq!(CostModel::Unit.slide_cost(&[3, 1], Moves::Up), 1);
+
+    for costs in cost_models.iter() {
+        for moves in [MoveModel::SingleTile, MoveModel::MultiTile].iter() {
+            let cheapest = cheapest_costs(&goal, *moves, costs);
+            let manhattan = parse_heuristic("max(manhattan, linear-conflict)").unwrap();
+
+            for (puzzle, cost) in cheapest.iter().step_by(29) {
+                let (outcome, _) = search_with_heuristic(
+                    problem(puzzle, costs, *moves),
+                    &*manhattan,
+                    Algorithm::AStar,
+                    SearchLimits::default(),
+                );
+                let solution = outcome.solution().unwrap();
+
+                assert_eq!(solution.cost, *cost, "{} under {:?}", puzzle, costs);
+                assert_eq!(costs.slides_cost(puzzle, &solution.slides), Some(*cost));
+            }
+        }
+    }
+}
+
+#[test]
+fn results_write_out_the_slides() {
+    let initial = board("1 0 2/0 3 4");
+    let multi = problem(&initial, &CostModel::Unit, MoveModel::MultiTile);
+    let heuristic = parse_heuristic("manhattan").unwrap();
+    let (outcome, stats) = search_with_heuristic(
+        multi.clone(),
+        &*heuristic,
+        Algorithm::AStar,
+        SearchLimits::default(),
+    );
+    let result = ResultDocument::new(&multi, Algorithm::AStar, "manhattan", &outcome, stats);
+    let solution = outcome.solution().unwrap();
+    assert_eq!(result.slides.as_ref(), Some(&solution.slides));
+
+    for format in [DocumentFormat::Json, DocumentFormat::Yaml].iter() {
+        let text = schema::to_string(&result, *format).unwrap();
+        assert!(text.contains("multi_tile"));
+        let reloaded: ResultDocument = schema::from_str(&text, *format).unwrap();
+        assert_eq!(reloaded, result);
+        assert_eq!(
+            reloaded.problem.to_problem().unwrap().move_model,
+            MoveModel::MultiTile
+        );
+    }
+
+    // the usual puzzle doesn't need them
+    let usual = problem(
+        &board("1 2 3/4 5 6/7 0 8"),
+        &CostModel::Unit,
+        MoveModel::SingleTile,
+    );
+    let (outcome, stats) = search_with_heuristic(
+        usual.clone(),
+        &*heuristic,
+        Algorithm::AStar,
+        SearchLimits::default(),
+    );
+    let result = ResultDocument::new(&usual, Algorithm::AStar, "manhattan", &outcome, stats);
+    assert_eq!(result.slides, None);
+    let text = schema::to_string(&result, DocumentFormat::Json).unwrap();
+    assert!(!text.contains("\"slides\""));
+}
+
+#[test]
+fn boards_with_several_spaces_are_not_played_back() {
+    let initial = board("1 2 3/4 0 5/6 7 0");
+    let goal = EightPuzzle::solved_like(&initial);
+    let error = playback::play(
+        initial,
+        goal,
+        &[Moves::Right],
+        &manhattan_distance_heuristic,
+        playback::DEFAULT_DELAY_MS,
+    )
+    .unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+}
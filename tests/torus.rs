@@ -0,0 +1,211 @@
+extern crate ai_homework;
+extern crate rand;
+
+mod common;
+
+use std::collections::{HashMap, VecDeque};
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use ai_homework::heuristic::{parse_heuristic, Heuristic};
+use ai_homework::schema::{self, DocumentFormat, ProblemDocument};
+use ai_homework::slides::{self, MoveModel, SlideCountHeuristic};
+use ai_homework::torus::Topology;
+use ai_homework::{
+    search_with_heuristic, Algorithm, EightPuzzle, Moves, Problem, SearchLimits, WALL,
+};
+use common::board;
+
+fn torus(text: &str) -> EightPuzzle {
+    board(text).with_topology(Topology::Torus)
+}
+
+fn problem(initial: &EightPuzzle, goal: &EightPuzzle, moves: MoveModel) -> Problem {
+    Problem {
+        move_model: moves,
+        ..Problem::new(initial.clone(), goal.clone())
+    }
+}
+
+// how many moves every board is from the goal, by a breadth first search out from it. every move
+// on a torus can be undone, so the boards that reach the goal are the ones it reaches
+fn distances(goal: &EightPuzzle, moves: MoveModel) -> HashMap<EightPuzzle, u64> {
+    let mut seen = HashMap::new();
+    let mut queue = VecDeque::new();
+    seen.insert(goal.clone(), 0);
+    queue.push_back(goal.clone());
+
+    while let Some(puzzle) = queue.pop_front() {
+        let distance = seen[&puzzle];
+        for (_, next) in slides::slides(&puzzle, moves) {
+            if !seen.contains_key(&next) {
+                seen.insert(next.clone(), distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    seen
+}
+
+#[test]
+fn the_space_wraps_around_the_edges() {
+    let puzzle = torus("1 2 3/4 5 6/7 8 0");
+    assert_eq!(puzzle.apply_move(Moves::Down), torus("1 2 0/4 5 6/7 8 3"));
+    assert_eq!(puzzle.apply_move(Moves::Right), torus("1 2 3/4 5 6/0 8 7"));
+    assert_eq!(puzzle.apply_move(Moves::Up), torus("1 2 3/4 5 0/7 8 6"));
+
+    // the same board with edges doesn't go anywhere
+    let flat = puzzle.with_topology(Topology::Flat);
+    assert_eq!(flat.apply_move(Moves::Down), flat);
+    assert_eq!(flat.apply_move(Moves::Right), flat);
+
+    // a slide can carry tiles across the edge
+    assert_eq!(slides::slides(&flat, MoveModel::MultiTile).len(), 4);
+    let wrapped = slides::slides(&puzzle, MoveModel::MultiTile);
+    assert_eq!(wrapped.len(), 8);
+    assert!(wrapped
+        .iter()
+        .any(|(_, next)| *next == torus("1 2 3/4 5 6/8 0 7")));
+
+    // tiles across an edge from their goal are close to it
+    let goal = torus("1 2 3/4 5 6/7 8 0");
+    let heuristic = parse_heuristic("manhattan").unwrap();
+    assert_eq!(heuristic.estimate(&torus("3 2 1/4 5 6/7 8 0"), &goal), 2);
+    assert_eq!(heuristic.estimate(&board("3 2 1/4 5 6/7 8 0"), &flat), 4);
+}
+
+#[test]
+fn solvability_matches_a_full_search() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let goals = [
+        // both sides even, the usual parity test
+        "1 2/3 0",
+        "1 2 3 4/5 6 7 0",
+        // an odd ring, every arrangement
+        "1 2 3/4 5 0",
+        // one ring, the tiles only turn round it
+        "1/2/3/0",
+        "1/2/3/4/0/0",
+        // walls and a second space
+        "1 # 2/3 4 5/6 7 0",
+        "1 2 #/3 4 #/5 6 0",
+        "1 2 3/4 0 0",
+    ];
+
+    for text in goals.iter() {
+        let goal = torus(text);
+        let reachable = distances(&goal, MoveModel::SingleTile);
+        let open: Vec<usize> = (0..goal.tiles.len())
+            .filter(|cell| goal.tiles[*cell] != WALL)
+            .collect();
+
+        for _ in 0..40 {
+            let mut puzzle = goal.clone();
+            let mut tiles: Vec<u16> = open.iter().map(|cell| goal.tiles[*cell]).collect();
+            rng.shuffle(&mut tiles);
+            for (cell, tile) in open.iter().zip(tiles) {
+                puzzle.tiles[*cell] = tile;
+            }
+
+            assert_eq!(
+                problem(&puzzle, &goal, MoveModel::SingleTile).is_solvable(),
+                reachable.contains_key(&puzzle),
+                "{} on {}",
+                puzzle,
+                text
+            );
+        }
+    }
+
+    // a goal on a flat board is never reached from a torus
+    let goal = torus("1 2 3/4 5 0");
+    let flat_goal = goal.with_topology(Topology::Flat);
+    assert!(!problem(&goal, &flat_goal, MoveModel::SingleTile).is_solvable());
+}
+
+#[test]
+fn searches_find_the_fewest_moves() {
+    let specs = [
+        "manhattan",
+        "linear-conflict",
+        "walking",
+        "gaschnig",
+        "inversion-distance",
+    ];
+
+    for text in ["1 2 3/4 5 0", "1 2 #/3 4 5/6 7 0"].iter() {
+        let goal = torus(text);
+
+        for moves in [MoveModel::SingleTile, MoveModel::MultiTile].iter() {
+            let distances = distances(&goal, *moves);
+
+            for spec in specs.iter() {
+                let heuristic = parse_heuristic(spec).unwrap();
+                let counted = SlideCountHeuristic::new(&*heuristic, &goal);
+
+                for (puzzle, distance) in distances.iter() {
+                    let estimate = match moves {
+                        MoveModel::SingleTile => heuristic.estimate(puzzle, &goal),
+                        MoveModel::MultiTile => counted.estimate(puzzle, &goal),
+                    };
+                    assert!(
+                        estimate <= *distance,
+                        "{} guessed {} for {}, {} {} moves away",
+                        spec,
+                        estimate,
+                        puzzle,
+                        distance,
+                        moves.name()
+                    );
+                }
+
+                for (puzzle, distance) in distances.iter().step_by(distances.len() / 4) {
+                    let (outcome, _) = search_with_heuristic(
+                        problem(puzzle, &goal, *moves),
+                        &*heuristic,
+                        Algorithm::AStar,
+                        SearchLimits::default(),
+                    );
+                    let solution = outcome.solution().unwrap();
+
+                    assert_eq!(solution.cost, *distance, "{} from {}", spec, puzzle);
+                    assert_eq!(
+                        slides::apply_slides(puzzle, &solution.slides),
+                        Some(goal.clone())
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn documents_keep_the_topology() {
+    let initial = torus("1 2 3/4 0 5");
+    let goal = torus("1 2 3/4 5 0");
+    let document = ProblemDocument::new(&problem(&initial, &goal, MoveModel::SingleTile));
+
+    for format in [DocumentFormat::Json, DocumentFormat::Yaml].iter() {
+        let text = schema::to_string(&document, *format).unwrap();
+        assert!(text.contains("torus"));
+        let reloaded: ProblemDocument = schema::from_str(&text, *format).unwrap();
+        assert_eq!(reloaded, document);
+
+        let reloaded = reloaded.to_problem().unwrap();
+        assert_eq!(reloaded.initial_state, initial);
+        assert_eq!(reloaded.goal_state, goal);
+    }
+
+    // boards have edges unless the document says otherwise
+    let text = r#"{"version": 1, "initial": "1 2 3/4 0 5"}"#;
+    let document: ProblemDocument = schema::from_str(text, DocumentFormat::Json).unwrap();
+    let flat = document.to_problem().unwrap();
+    assert_eq!(flat.initial_state.topology, Topology::Flat);
+    assert_eq!(flat.goal_state.topology, Topology::Flat);
+
+    let text = r#"{"version": 1, "initial": "1 2 3/4 0 5", "topology": "torus"}"#;
+    let document: ProblemDocument = schema::from_str(text, DocumentFormat::Json).unwrap();
+    assert_eq!(document.to_problem().unwrap().goal_state, goal);
+}
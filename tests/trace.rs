@@ -0,0 +1,225 @@
+extern crate ai_homework;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use ai_homework::trace::{self, TraceEvent, TraceEventKind, TraceReader, TraceWriter};
+use ai_homework::{
+    manhattan_distance_heuristic, search_with_heuristic, Algorithm, EightPuzzle, Moves, Problem,
+    SearchLimits, SearchRecorder,
+};
+
+fn path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("trace-{}-{}", std::process::id(), name))
+}
+
+// what an event says, TraceEvent has nothing to compare with
+fn fields(event: &TraceEvent) -> (u64, Option<u64>, u64, u64, u64, &'static str, String) {
+    (
+        event.id,
+        event.parent,
+        event.g,
+        event.h,
+        event.depth,
+        event.prev_move.name(),
+        event.puzzle.to_string(),
+    )
+}
+
+fn event(kind: TraceEventKind, id: u64, parent: Option<u64>, board: &str) -> TraceEvent {
+    TraceEvent {
+        kind,
+        id,
+        parent,
+        g: 3,
+        h: 5,
+        depth: 2,
+        prev_move: Moves::Left,
+        puzzle: board.parse().unwrap(),
+    }
+}
+
+// a search of a few moves recorded to the file
+fn traced_search(path: &Path) -> u64 {
+    let initial: EightPuzzle = "123405786".parse().unwrap();
+    let goal = EightPuzzle::solved_like(&initial);
+    let problem = Problem::new(initial, goal);
+    let recorder = Arc::new(Mutex::new(SearchRecorder {
+        trace: Some(TraceWriter::create(path).unwrap()),
+        tree: None,
+    }));
+    let limits = SearchLimits {
+        recorder: Some(recorder.clone()),
+        ..SearchLimits::default()
+    };
+    let (outcome, stats) = search_with_heuristic(
+        problem,
+        &manhattan_distance_heuristic,
+        Algorithm::AStar,
+        limits,
+    );
+    assert_eq!(outcome.solution().unwrap().cost, 2);
+
+    let writer = recorder.lock().unwrap().trace.take().unwrap();
+    writer.finish().unwrap();
+    stats.expanded
+}
+
+fn read(path: &Path) -> Vec<TraceEvent> {
+    TraceReader::open(path)
+        .unwrap()
+        .collect::<io::Result<Vec<_>>>()
+        .unwrap()
+}
+
+fn error(path: &Path) -> io::Error {
+    match TraceReader::open(path) {
+        Ok(reader) => reader
+            .filter_map(|event| event.err())
+            .next()
+            .expect("the trace should not have been read"),
+        Err(error) => error,
+    }
+}
+
+#[test]
+fn events_read_back_as_they_were_written() {
+    let events = [
+        event(TraceEventKind::Generate, 0, None, "123405786"),
+        event(TraceEventKind::Pop, 0, None, "123405786"),
+        event(TraceEventKind::Expand, 0, None, "123405786"),
+        event(TraceEventKind::Prune, 4, Some(0), "1 2 #/3 4 5/6 7 0"),
+    ];
+
+    for name in ["events.jsonl", "events.json", "events.trace"].iter() {
+        let path = path(name);
+        let mut writer = TraceWriter::create(&path).unwrap();
+        for event in events.iter() {
+            writer.write_event(event).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let read = read(&path);
+        assert_eq!(read.len(), events.len(), "{}", name);
+        for (read, written) in read.iter().zip(events.iter()) {
+            assert!(read.kind == written.kind, "{}", name);
+            assert_eq!(fields(read), fields(written), "{}", name);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn json_lines_are_written_with_f() {
+    let path = path("f.jsonl");
+    let mut writer = TraceWriter::create(&path).unwrap();
+    writer
+        .write_event(&event(TraceEventKind::Expand, 7, Some(2), "123405786"))
+        .unwrap();
+    writer.finish().unwrap();
+
+    let text = fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        text,
+        "{\"event\":\"expand\",\"id\":7,\"parent\":2,\"g\":3,\"h\":5,\"depth\":2,\"move\":\"left\",\
+         \"board\":[[1,2,3],[4,0,5],[7,8,6]],\"f\":8}\n"
+    );
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn a_search_is_traced_in_both_formats() {
+    for name in ["search.jsonl", "search.trace"].iter() {
+        let path = path(name);
+        let expanded = traced_search(&path);
+
+        let events = read(&path);
+        let count = |kind: TraceEventKind| events.iter().filter(|event| event.kind == kind).count();
+        assert_eq!(count(TraceEventKind::Expand) as u64, expanded, "{}", name);
+        assert!(events[0].kind == TraceEventKind::Generate);
+        assert_eq!(events[0].parent, None);
+        assert!(count(TraceEventKind::Pop) >= count(TraceEventKind::Expand));
+
+        trace::print_summary(&path).unwrap();
+        trace::replay(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn broken_traces_are_refused() {
+    let cases: Vec<(&str, Vec<u8>, &str)> = vec![
+        ("magic.trace", b"NOPE\x02".to_vec(), "not a search trace"),
+        (
+            "version.trace",
+            b"8PTR\x09".to_vec(),
+            "unsupported trace version 9",
+        ),
+        (
+            "kind.trace",
+            b"8PTR\x02\x07".to_vec(),
+            "unknown event kind 7",
+        ),
+        ("json.jsonl", b"\n{\"event\":\"pop\"\n".to_vec(), "line 2"),
+        (
+            "move.jsonl",
+            b"{\"event\":\"pop\",\"id\":0,\"parent\":null,\"g\":0,\"h\":0,\"depth\":0,\
+              \"move\":\"sideways\",\"board\":[[1,0]]}\n"
+                .to_vec(),
+            "unknown move \"sideways\"",
+        ),
+    ];
+
+    for (name, bytes, message) in cases {
+        let path = path(name);
+        fs::write(&path, bytes).unwrap();
+        let error = error(&path);
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{}", name);
+        assert!(error.to_string().contains(message), "{}: {}", name, error);
+        assert!(trace::print_summary(&path).is_err(), "{}", name);
+        assert!(trace::replay(&path).is_err(), "{}", name);
+        fs::remove_file(&path).unwrap();
+    }
+
+    // a record cut off part way through
+    let path = path("short.trace");
+    fs::write(&path, b"8PTR\x02\x00\x01\x00").unwrap();
+    assert_eq!(error(&path).kind(), io::ErrorKind::UnexpectedEof);
+    fs::remove_file(&path).unwrap();
+
+    assert!(TraceReader::open(&self::path("missing.trace")).is_err());
+}
+
+#[test]
+fn a_trace_that_cant_be_written_fails_at_the_end() {
+    // every write to /dev/full runs out of space
+    let full = Path::new("/dev/full");
+    if !full.exists() {
+        return;
+    }
+
+    // far enough from the goal that the events don't fit in the write buffer
+    let initial: EightPuzzle = "8 6 7/2 5 4/3 0 1".parse().unwrap();
+    let goal = EightPuzzle::solved_like(&initial);
+    let recorder = Arc::new(Mutex::new(SearchRecorder {
+        trace: Some(TraceWriter::create(full).unwrap()),
+        tree: None,
+    }));
+    let limits = SearchLimits {
+        recorder: Some(recorder.clone()),
+        ..SearchLimits::default()
+    };
+    let (outcome, _) = search_with_heuristic(
+        Problem::new(initial, goal),
+        &manhattan_distance_heuristic,
+        Algorithm::AStar,
+        limits,
+    );
+    assert_eq!(outcome.solution().unwrap().cost, 31);
+
+    let writer = recorder.lock().unwrap().trace.take().unwrap();
+    assert!(writer.finish().is_err());
+}
@@ -0,0 +1,187 @@
+extern crate ai_homework;
+
+use std::env;
+use std::fs;
+use std::time::{Duration, Instant};
+
+use ai_homework::analysis::{self, AnalysisOptions};
+use ai_homework::heuristic::Heuristic;
+use ai_homework::walking_distance::{
+    self, walking_distance_heuristic, WalkingDistance, WalkingDistanceTable,
+};
+use ai_homework::{
+    linear_conflict_heuristic, manhattan_distance_heuristic, search_with_options,
+    walking_linear_conflict_heuristic, Algorithm, EightPuzzle, Problem, SearchLimits,
+};
+
+fn solution_length(initial: &str, heuristic: fn(&EightPuzzle, &EightPuzzle) -> u64) -> usize {
+    let initial_state: EightPuzzle = initial.parse().unwrap();
+    let goal_state = EightPuzzle::solved(initial_state.rows, initial_state.cols);
+    let problem = Problem::new(initial_state, goal_state);
+    let (outcome, _) = search_with_options(
+        problem,
+        heuristic,
+        Algorithm::AStar,
+        SearchLimits::default(),
+    );
+    outcome.solution().unwrap().moves.len()
+}
+
+#[test]
+fn tables_have_the_known_sizes() {
+    assert_eq!(WalkingDistanceTable::build(3, 3, 2).len(), 105);
+    assert_eq!(WalkingDistanceTable::build(4, 4, 3).len(), 24_964);
+}
+
+#[test]
+fn walking_distance_is_admissible_consistent_and_above_manhattan() {
+    let options = AnalysisOptions {
+        searches: 5,
+        ..AnalysisOptions::default()
+    };
+    let report = analysis::analyze(&walking_distance_heuristic, &options);
+    assert!(report.exhaustive);
+    assert!(report.is_admissible());
+    assert!(report.is_consistent());
+    assert_eq!(report.suboptimal_searches, 0);
+
+    let goal = EightPuzzle::solved(3, 3);
+    for (puzzle, _) in analysis::exact_distances(&goal).iter().step_by(97) {
+        assert!(
+            walking_distance_heuristic(puzzle, &goal)
+                >= manhattan_distance_heuristic(puzzle, &goal)
+        );
+    }
+}
+
+#[test]
+fn linear_conflict_and_the_combination_are_admissible() {
+    for (rows, cols) in [(2, 3), (2, 4)].iter() {
+        let options = AnalysisOptions {
+            rows: *rows,
+            cols: *cols,
+            searches: 0,
+            ..AnalysisOptions::default()
+        };
+
+        assert!(analysis::analyze(&linear_conflict_heuristic, &options).is_admissible());
+        assert!(analysis::analyze(&walking_linear_conflict_heuristic, &options).is_admissible());
+    }
+}
+
+#[test]
+fn linear_conflict_counts_tiles_in_each_others_way() {
+    let goal = EightPuzzle::solved(3, 3);
+
+    // 2 and 1 are both in their goal row but the wrong way round
+    let swapped: EightPuzzle = "2 1 3/4 5 6/7 8 0".parse().unwrap();
+    assert_eq!(manhattan_distance_heuristic(&swapped, &goal), 2);
+    assert_eq!(linear_conflict_heuristic(&swapped, &goal), 4);
+
+    assert_eq!(linear_conflict_heuristic(&goal, &goal), 0);
+}
+
+#[test]
+fn searches_stay_optimal() {
+    for preset in ["120453786", "012453786", "871602543"].iter() {
+        let optimal = solution_length(preset, manhattan_distance_heuristic);
+        assert_eq!(solution_length(preset, walking_distance_heuristic), optimal);
+        assert_eq!(
+            solution_length(preset, walking_linear_conflict_heuristic),
+            optimal
+        );
+    }
+
+    assert_eq!(
+        solution_length(
+            "2 4 7 8/5 1 10 3/0 6 9 12/13 14 11 15",
+            walking_distance_heuristic
+        ),
+        20
+    );
+}
+
+#[test]
+fn tables_are_saved_and_loaded() {
+    let directory = env::temp_dir().join(format!("ai_homework_walking_{}", std::process::id()));
+    let goal = EightPuzzle::solved(3, 4);
+
+    let paths = walking_distance::save_tables(&directory, &goal).unwrap();
+    assert_eq!(paths.len(), 2);
+
+    for path in paths.iter() {
+        let loaded = WalkingDistanceTable::load(path).unwrap();
+        let built = WalkingDistanceTable::build(loaded.lines, loaded.width, loaded.goal_blank_line);
+        assert_eq!(loaded.len(), built.len());
+    }
+
+    fs::write(&paths[0], b"not a table").unwrap();
+    assert!(WalkingDistanceTable::load(&paths[0]).is_err());
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn big_boards_fall_back_to_manhattan_distance() {
+    let goal = EightPuzzle::solved(6, 6);
+    let puzzle = goal.apply_move(ai_homework::Moves::Up);
+
+    assert_eq!(
+        walking_distance_heuristic(&puzzle, &goal),
+        manhattan_distance_heuristic(&puzzle, &goal)
+    );
+}
+
+#[test]
+fn long_lines_fall_back_without_building_a_table() {
+    // four rows would have a table, but not with ten tiles in each
+    let goal = EightPuzzle::solved(4, 10);
+    let puzzle = goal.apply_move(ai_homework::Moves::Left);
+
+    let start = Instant::now();
+    assert_eq!(
+        walking_distance_heuristic(&puzzle, &goal),
+        manhattan_distance_heuristic(&puzzle, &goal)
+    );
+    assert_eq!(WalkingDistance::new().estimate(&puzzle, &goal), 1);
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    let directory =
+        env::temp_dir().join(format!("ai_homework_walking_long_{}", std::process::id()));
+    assert!(walking_distance::save_tables(&directory, &goal)
+        .unwrap()
+        .is_empty());
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn the_heuristic_keeps_its_tables_for_one_goal() {
+    let heuristic = WalkingDistance::new();
+    let goal = EightPuzzle::solved(3, 3);
+    let other: EightPuzzle = "0 1 2/3 4 5/6 7 8".parse().unwrap();
+    let wide = EightPuzzle::solved(2, 4);
+
+    // the first goal's tables are kept, the others are looked up each time and give the same
+    for (puzzle, _) in analysis::exact_distances(&goal).iter().step_by(211) {
+        for goal in [&goal, &other].iter() {
+            assert_eq!(
+                heuristic.estimate(puzzle, goal),
+                walking_distance_heuristic(puzzle, goal)
+            );
+        }
+    }
+    for (puzzle, _) in analysis::exact_distances(&wide).iter().step_by(97) {
+        assert_eq!(
+            heuristic.estimate(puzzle, &wide),
+            walking_distance_heuristic(puzzle, &wide)
+        );
+    }
+
+    // boards without tables still get manhattan distance
+    let walled: EightPuzzle = "1 2 #/3 4 5/6 0 7".parse().unwrap();
+    let walled_goal: EightPuzzle = "1 2 #/3 4 5/6 7 0".parse().unwrap();
+    assert_eq!(
+        WalkingDistance::new().estimate(&walled, &walled_goal),
+        manhattan_distance_heuristic(&walled, &walled_goal)
+    );
+}
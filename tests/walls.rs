@@ -0,0 +1,216 @@
+extern crate ai_homework;
+extern crate rand;
+
+mod common;
+
+use std::collections::HashMap;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use ai_homework::analysis;
+use ai_homework::heuristic::parse_heuristic;
+use ai_homework::schema::{self, DocumentFormat, ProblemDocument};
+use ai_homework::{
+    manhattan_distance_heuristic, search_with_heuristic, Algorithm, EightPuzzle, Moves,
+    SearchLimits, WALL,
+};
+use common::{board, problem};
+
+// the shapes, with o for an open cell
+const SHAPES: [&str; 5] = [
+    // a ring round a hole
+    "o o o/o # o/o o o",
+    // a corner cut off, still two ways round every cell
+    "# o o/o o o/o o o",
+    // an L, the corner cell hangs off a single neighbour
+    "o o #/o o #/o o o",
+    // two halves that never meet
+    "o # o/o # o",
+    // a hole in a wider board
+    "o o o o/o # o o",
+];
+
+fn goal_for(shape: &str) -> EightPuzzle {
+    let cells: Vec<&str> = shape.split('/').collect();
+    let open = shape.matches('o').count();
+    let mut next = 1;
+    let rows: Vec<String> = cells
+        .iter()
+        .map(|row| {
+            let tiles: Vec<String> = row
+                .split_whitespace()
+                .map(|cell| {
+                    if cell == "#" {
+                        "#".to_string()
+                    } else {
+                        next += 1;
+                        if next - 1 == open {
+                            "0".to_string()
+                        } else {
+                            (next - 1).to_string()
+                        }
+                    }
+                })
+                .collect();
+            tiles.join(" ")
+        })
+        .collect();
+    board(&rows.join("/"))
+}
+
+#[test]
+fn walls_are_read_and_written_with_a_hash() {
+    let puzzle = board("1 2 #/3 0 4/5 6 7");
+    assert_eq!(puzzle.tiles[2], WALL);
+    assert_eq!(puzzle.to_string(), "1 2 #/3 0 4/5 6 7");
+    assert_eq!(board("12#304567"), puzzle);
+
+    // with a wall there are only 7 tiles
+    assert!("1 2 #/3 0 4/5 6 8".parse::<EightPuzzle>().is_err());
+    assert!("# #/# #".parse::<EightPuzzle>().is_err());
+
+    let document = ProblemDocument::new(&problem(&puzzle, &EightPuzzle::solved_like(&puzzle)));
+    let text = schema::to_string(&document, DocumentFormat::Json).unwrap();
+    assert!(text.contains("\"#\""));
+    for format in [DocumentFormat::Json, DocumentFormat::Yaml].iter() {
+        let text = schema::to_string(&document, *format).unwrap();
+        let reloaded: ProblemDocument = schema::from_str(&text, *format).unwrap();
+        assert_eq!(reloaded, document);
+    }
+
+    // a goal with its walls somewhere else is refused
+    let moved = r##"{"version": 1, "initial": "1 2 #/3 0 4/5 6 7", "goal": "# 1 2/3 4 5/6 7 0"}"##;
+    let document: ProblemDocument = schema::from_str(moved, DocumentFormat::Json).unwrap();
+    assert!(document.to_problem().is_err());
+}
+
+#[test]
+fn goals_count_around_the_walls() {
+    assert_eq!(
+        EightPuzzle::solved_like(&board("1 2 #/3 0 4/5 6 7")),
+        board("1 2 #/3 4 5/6 7 0")
+    );
+    assert_eq!(
+        EightPuzzle::solved_like(&board("1 2 3/4 0 5/6 7 #")),
+        board("1 2 3/4 5 6/7 0 #")
+    );
+    for shape in SHAPES.iter() {
+        assert_eq!(EightPuzzle::solved_like(&goal_for(shape)), goal_for(shape));
+    }
+}
+
+#[test]
+fn the_space_cant_move_into_a_wall() {
+    let puzzle = board("1 # 2/3 0 4/5 6 7");
+    assert_eq!(puzzle.apply_move(Moves::Up), puzzle);
+    assert_ne!(puzzle.apply_move(Moves::Down), puzzle);
+}
+
+#[test]
+fn manhattan_distance_goes_around_walls() {
+    let goal = board("1 2 3/4 # 5/6 7 0");
+    // 4 and 5 swapped sides, and the hole is between them
+    let swapped = board("1 2 3/5 # 4/6 7 0");
+
+    assert_eq!(manhattan_distance_heuristic(&swapped, &goal), 8);
+}
+
+#[test]
+fn solvability_matches_a_full_search() {
+    let mut rng = StdRng::seed_from_u64(40);
+
+    for shape in SHAPES.iter() {
+        let goal = goal_for(shape);
+        let reachable: HashMap<EightPuzzle, u64> =
+            analysis::exact_distances(&goal).into_iter().collect();
+        let open: Vec<usize> = (0..goal.tiles.len())
+            .filter(|cell| goal.tiles[*cell] != WALL)
+            .collect();
+
+        let mut unsolvable = 0;
+        for _ in 0..200 {
+            let mut puzzle = goal.clone();
+            let mut tiles: Vec<u16> = open.iter().map(|cell| goal.tiles[*cell]).collect();
+            rng.shuffle(&mut tiles);
+            for (cell, tile) in open.iter().zip(tiles) {
+                puzzle.tiles[*cell] = tile;
+            }
+
+            let expected = reachable.contains_key(&puzzle);
+            assert_eq!(
+                problem(&puzzle, &goal).is_solvable(),
+                expected,
+                "{} on {}",
+                puzzle,
+                shape
+            );
+            if !expected {
+                unsolvable += 1;
+            }
+        }
+
+        // few shuffles land on a board that can be solved, so those are checked separately
+        for puzzle in reachable.keys().step_by(7) {
+            assert!(
+                problem(puzzle, &goal).is_solvable(),
+                "{} on {}",
+                puzzle,
+                shape
+            );
+        }
+        assert!(unsolvable > 0, "{}", shape);
+    }
+}
+
+#[test]
+fn heuristics_stay_admissible_around_walls() {
+    let specs = [
+        "manhattan",
+        "linear-conflict",
+        "walking",
+        "walking-linear-conflict",
+        "gaschnig",
+        "inversion-distance",
+    ];
+
+    for shape in SHAPES.iter() {
+        let goal = goal_for(shape);
+        let distances = analysis::exact_distances(&goal);
+
+        for spec in specs.iter() {
+            let heuristic = parse_heuristic(spec).unwrap();
+            for (puzzle, distance) in distances.iter() {
+                let estimate = heuristic.estimate(puzzle, &goal);
+                assert!(
+                    estimate <= *distance,
+                    "{} guessed {} for {}, {} moves from {}",
+                    spec,
+                    estimate,
+                    puzzle,
+                    distance,
+                    goal
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn searches_find_the_shortest_way_around() {
+    let heuristic = parse_heuristic("manhattan").unwrap();
+
+    for shape in SHAPES.iter() {
+        let goal = goal_for(shape);
+        for (puzzle, distance) in analysis::exact_distances(&goal).iter().step_by(101) {
+            let (outcome, _) = search_with_heuristic(
+                problem(puzzle, &goal),
+                &*heuristic,
+                Algorithm::AStar,
+                SearchLimits::default(),
+            );
+            let solution = outcome.solution().unwrap();
+            assert_eq!(solution.moves.len() as u64, *distance);
+        }
+    }
+}